@@ -4,22 +4,26 @@ use std::{
     sync::mpsc,
     sync::mpsc::TryRecvError,
     thread,
-    time::SystemTime,
+    time::{Instant, SystemTime},
 };
 
 use crate::{
-    AI_LOADING_FRAMES, AiTaskMessage, App, AppView, config, knowledge_store,
+    AI_LOADING_FRAMES, AiTaskMessage, App, AppView, FollowupTaskMessage, GradingMessage,
+    config::{self, AppConfig},
+    knowledge_store,
     log_util::{self, log_debug},
     output_manager::OutputManager,
-    reset_learning_feedback,
-    view_managers::LearningManager,
+    prompt_templates,
+    question_history::LearnerHistory,
+    quiz_validation, reset_learning_feedback, shared_bank,
+    view_managers::{LearningManager, results_manager},
 };
 use color_eyre::eyre::{Context, ContextCompat, Result, eyre};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json, to_string_pretty};
 
-const JSON_SCHEMA: &str = r#"{
+pub(crate) const JSON_SCHEMA: &str = r#"{
   "type": "object",
   "additionalProperties": false,
   "properties": {
@@ -47,11 +51,16 @@ const JSON_SCHEMA: &str = r#"{
               "properties": {
                 "question": {
                   "type": "string",
-                  "description": "a question about this knowledge type that will test the user"
+                  "description": "a question about this knowledge type that will test the user. For fill_in_blank, mark the blank with ____"
+                },
+                "kind": {
+                  "type": "string",
+                  "enum": ["multiple_choice", "true_false", "multi_select", "fill_in_blank", "order_lines", "free_text"],
+                  "description": "multiple_choice: exactly one correct option. true_false: options True and False with one correct. multi_select: choose all that apply, one or more correct options. fill_in_blank: options empty, answers in accepted_answers. order_lines: options are code lines listed in the correct order. free_text: the student explains a concept in their own words; options empty, grading guidance in rubric"
                 },
                 "options": {
                   "type": "array",
-                  "description": "a multi-choice list of answer options",
+                  "description": "a list of answer options; for order_lines, the lines in their correct order",
                   "items": {
                     "type": "object",
                     "additionalProperties": false,
@@ -63,26 +72,59 @@ const JSON_SCHEMA: &str = r#"{
                       "is_correct_answer": {
                         "type": "boolean",
                         "description": "this should be set to true if it's the correct answer to the question"
+                      },
+                      "explanation": {
+                        "type": "string",
+                        "description": "one or two sentences explaining why this option is right or wrong; for order_lines, what this line does"
                       }
                     },
                     "required": [
                       "selection",
-                      "is_correct_answer"
+                      "is_correct_answer",
+                      "explanation"
                     ]
                   }
                 },
+                "accepted_answers": {
+                  "type": "array",
+                  "description": "for fill_in_blank, every exact text that should be accepted for the blank; empty for other kinds",
+                  "items": {
+                    "type": "string"
+                  }
+                },
+                "rubric": {
+                  "type": "string",
+                  "description": "for free_text, the key points a complete answer must cover and how to weigh them; empty for other kinds"
+                },
                 "resources": {
                   "type": "array",
                   "description": "an optional list of resources that can help the user learn more about this specific question",
                   "items": {
                     "type": "string"
                   }
+                },
+                "difficulty": {
+                  "type": "string",
+                  "enum": ["easy", "medium", "hard"],
+                  "description": "how hard the question is for the learner, following the difficulty guidance for its knowledge group"
+                },
+                "source_refs": {
+                  "type": "array",
+                  "description": "the session events this question was based on, copied from the summary headings: the call_id when the heading has one, otherwise the event timestamp",
+                  "items": {
+                    "type": "string"
+                  }
                 }
               },
               "required": [
                 "question",
+                "kind",
                 "options",
-                "resources"
+                "accepted_answers",
+                "rubric",
+                "resources",
+                "difficulty",
+                "source_refs"
               ]
             }
           },
@@ -105,7 +147,40 @@ const JSON_SCHEMA: &str = r#"{
   ]
 }"#;
 
+const GRADING_SCHEMA: &str = r#"{
+  "type": "object",
+  "additionalProperties": false,
+  "properties": {
+    "score": {
+      "type": "integer",
+      "description": "how well the answer satisfies the rubric, from 0 (nothing correct) to 100 (complete and accurate)"
+    },
+    "feedback": {
+      "type": "string",
+      "description": "two or three sentences telling the student what they got right and what was missing or wrong"
+    }
+  },
+  "required": [
+    "score",
+    "feedback"
+  ]
+}"#;
+
+const GRADING_SYSTEM_PROMPT: &str = "You grade a student's free-text answer to a programming question. Judge only against the rubric and the concept summary, ignore spelling and grammar, and be encouraging but honest. Return JSON that strictly matches the provided schema.";
+
+const FOLLOWUP_SYSTEM_PROMPT: &str = "You are a patient programming tutor. The student has just answered a quiz question generated from their own coding session and wants to dig deeper. Answer their follow-up questions concisely in plain text, use short code examples when they help, and connect the explanation to the session excerpt where you can.";
+
+/// Maximum characters of the session summary included as follow-up context.
+const FOLLOWUP_EXCERPT_CHARS: usize = 4000;
+
+/// Minimum LLM score for a free-text answer to count as correct.
+pub(crate) const FREE_TEXT_PASS_SCORE: u8 = 60;
+
 const DEFAULT_API_BASE: &str = "https://api.openai.com/v1";
+/// Initial request plus re-prompts carrying validation errors back to the model.
+const MAX_GENERATION_ATTEMPTS: usize = 2;
+/// Most questions loaded into one review lesson.
+const REVIEW_QUEUE_SIZE: usize = 10;
 
 /// Structured representation returned from the LLM.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -130,10 +205,102 @@ pub struct KnowledgeResponse {
 pub struct QuizItem {
     #[serde(default)]
     pub question: String,
+    /// Older responses predate question kinds and are all multiple choice.
+    #[serde(default)]
+    pub kind: QuizKind,
     #[serde(default)]
     pub options: Vec<QuizOption>,
+    /// Acceptable answers for fill-in-the-blank questions.
+    #[serde(default)]
+    pub accepted_answers: Vec<String>,
+    /// Grading guidance for free-text questions, generated alongside the question.
+    #[serde(default)]
+    pub rubric: String,
     #[serde(default)]
     pub resources: Vec<String>,
+    /// Older responses predate difficulty ratings and are treated as medium.
+    #[serde(default)]
+    pub difficulty: QuizDifficulty,
+    /// Call ids or timestamps of the session events that inspired the question.
+    #[serde(default)]
+    pub source_refs: Vec<String>,
+    /// Who published the question to the shared bank; unset for personal questions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// When the question was published to the shared bank, as an RFC 3339 timestamp.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
+}
+
+/// How a quiz question is answered and graded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuizKind {
+    #[default]
+    MultipleChoice,
+    TrueFalse,
+    MultiSelect,
+    FillInBlank,
+    OrderLines,
+    FreeText,
+}
+
+impl QuizKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::MultipleChoice => "Multiple choice",
+            Self::TrueFalse => "True or false",
+            Self::MultiSelect => "Choose all that apply",
+            Self::FillInBlank => "Fill in the blank",
+            Self::OrderLines => "Order these lines",
+            Self::FreeText => "Explain in your own words",
+        }
+    }
+
+    /// Whether the question is answered by typing rather than choosing options.
+    pub fn is_typed_answer(self) -> bool {
+        matches!(self, Self::FillInBlank | Self::FreeText)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MultipleChoice => "multiple_choice",
+            Self::TrueFalse => "true_false",
+            Self::MultiSelect => "multi_select",
+            Self::FillInBlank => "fill_in_blank",
+            Self::OrderLines => "order_lines",
+            Self::FreeText => "free_text",
+        }
+    }
+}
+
+/// How demanding a quiz question is, requested per knowledge group from past accuracy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuizDifficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+impl QuizDifficulty {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Medium => "medium",
+            Self::Hard => "hard",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "easy" => Some(Self::Easy),
+            "medium" => Some(Self::Medium),
+            "hard" => Some(Self::Hard),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -142,6 +309,184 @@ pub struct QuizOption {
     pub selection: String,
     #[serde(default)]
     pub is_correct_answer: bool,
+    /// Why this option is right or wrong, shown after it is chosen.
+    #[serde(default)]
+    pub explanation: String,
+    /// Original index of the option before shuffling, used to grade order-lines questions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+}
+
+/// LLM assessment of a free-text answer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct FreeTextGrade {
+    #[serde(default)]
+    pub score: u8,
+    #[serde(default)]
+    pub feedback: String,
+}
+
+impl FreeTextGrade {
+    pub fn passed(&self) -> bool {
+        self.score >= FREE_TEXT_PASS_SCORE
+    }
+}
+
+/// Everything the grader needs to assess a free-text answer.
+#[derive(Debug, Clone)]
+pub struct GradingRequest {
+    pub group_index: usize,
+    pub quiz_index: usize,
+    /// `knowledge_store::question_id` of the graded question, so a grade that arrives after the
+    /// lesson changed is not applied to whatever question now sits at the same indices.
+    pub question_id: String,
+    pub question: String,
+    pub summary: String,
+    pub rubric: String,
+    pub answer: String,
+    /// Time since the question was shown when the answer was submitted.
+    pub elapsed_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatRole {
+    User,
+    Assistant,
+}
+
+impl ChatRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Assistant => "assistant",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "user" => Some(Self::User),
+            "assistant" => Some(Self::Assistant),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub content: String,
+}
+
+/// The quiz question, concept summary and session excerpt a follow-up conversation is about.
+#[derive(Debug, Clone, Default)]
+pub struct FollowupContext {
+    pub knowledge_type_group: String,
+    pub summary: String,
+    pub question: QuizItem,
+    pub session_excerpt: String,
+}
+
+impl FollowupContext {
+    pub fn new(group: &KnowledgeResponse, question: &QuizItem, session_summary: &str) -> Self {
+        Self {
+            knowledge_type_group: group.knowledge_type_group.clone(),
+            summary: group.summary.clone(),
+            question: question.clone(),
+            session_excerpt: session_excerpt(session_summary, FOLLOWUP_EXCERPT_CHARS),
+        }
+    }
+
+    /// Context message sent ahead of the conversation so the tutor knows what was asked.
+    fn prompt(&self) -> String {
+        let mut lines = vec![
+            format!("Concept: {}", self.knowledge_type_group),
+            format!("Summary: {}", self.summary),
+            format!(
+                "Question ({}): {}",
+                self.question.kind.label(),
+                self.question.question
+            ),
+        ];
+        for option in &self.question.options {
+            let marker = if option.is_correct_answer {
+                "correct"
+            } else {
+                "incorrect"
+            };
+            lines.push(format!(
+                "- [{}] {} {}",
+                marker, option.selection, option.explanation
+            ));
+        }
+        if !self.question.accepted_answers.is_empty() {
+            lines.push(format!(
+                "Accepted answers: {}",
+                self.question.accepted_answers.join(" | ")
+            ));
+        }
+        if !self.question.rubric.is_empty() {
+            lines.push(format!("Rubric: {}", self.question.rubric));
+        }
+        if !self.session_excerpt.is_empty() {
+            lines.push(format!(
+                "Session excerpt:\n```markdown\n{}\n```",
+                self.session_excerpt
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// An open follow-up conversation in the Learning view.
+#[derive(Debug, Clone, Default)]
+pub struct FollowupChat {
+    pub group_index: usize,
+    pub quiz_index: usize,
+    pub context: FollowupContext,
+    pub messages: Vec<ChatMessage>,
+    pub input: String,
+}
+
+/// Keep the most recent `max_chars` of the session summary, starting on a line boundary.
+fn session_excerpt(summary: &str, max_chars: usize) -> String {
+    let total = summary.chars().count();
+    if total <= max_chars {
+        return summary.trim().to_string();
+    }
+    let tail: String = summary.chars().skip(total - max_chars).collect();
+    match tail.find('\n') {
+        Some(newline) => tail[newline + 1..].trim().to_string(),
+        None => tail.trim().to_string(),
+    }
+}
+
+/// Token accounting for a single OpenAI request.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GenerationUsage {
+    pub model_name: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub latency_ms: u64,
+}
+
+impl GenerationUsage {
+    /// Estimated USD cost using the published per-million-token prices for the model.
+    pub fn estimated_cost_usd(&self) -> f64 {
+        let Some(model) = config::OpenAiModelKind::from_model_name(&self.model_name) else {
+            return 0.0;
+        };
+        let (input_price, output_price) = model.price_per_million_tokens();
+        (self.prompt_tokens as f64 * input_price + self.completion_tokens as f64 * output_price)
+            / 1_000_000.0
+    }
+}
+
+/// A freshly generated response along with the metadata needed to cache it.
+#[derive(Debug)]
+pub struct GeneratedLesson {
+    pub response: StructuredLearningResponse,
+    pub cache_key: Option<String>,
+    pub model_name: String,
 }
 
 /// Coordinates LLM requests informed by the most recent markdown session summary.
@@ -190,6 +535,11 @@ impl AiManager {
         self
     }
 
+    /// Name of the OpenAI model used for requests.
+    pub fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
     /// Locate the most recent markdown file under the configured output directory.
     fn latest_markdown_file(&self) -> Result<PathBuf> {
         let root = self.output_root.as_path();
@@ -229,9 +579,13 @@ impl AiManager {
     }
 
     /// Execute the OpenAI request using the provided markdown summary (or the most recent file) and return a structured response.
+    /// Token usage for every request made (including re-prompts) is appended to `usage_log`.
+    /// Questions repeating `history` are dropped and re-requested.
     pub async fn generate_learning_response(
         &self,
         summary_override: Option<String>,
+        history: &LearnerHistory,
+        usage_log: &mut Vec<GenerationUsage>,
     ) -> Result<StructuredLearningResponse> {
         let summary_content = if let Some(summary) = summary_override {
             log_util::log_debug(&format!(
@@ -258,35 +612,186 @@ impl AiManager {
             summary
         };
 
-        let prompt = self.build_prompt(&summary_content);
-        let schema = schema_value();
+        let config_snapshot = config::current();
+        let prompt = prompt_templates::render_prompt(
+            &config_snapshot,
+            &summary_content,
+            JSON_SCHEMA,
+            history,
+        )?;
+        let min_questions = config_snapshot.min_quiz_questions;
+        let mut messages = vec![
+            json!({
+                "role": "system",
+                "content": prompt.system,
+            }),
+            json!({
+                "role": "user",
+                "content": prompt.user,
+            }),
+        ];
+
+        for attempt in 1..=MAX_GENERATION_ATTEMPTS {
+            let primary_text = self.request_completion(&messages, usage_log).await?;
+
+            let mut structured: StructuredLearningResponse = serde_json::from_str(&primary_text)
+                .wrap_err(
+                    "failed to deserialize OpenAI response into StructuredLearningResponse",
+                )?;
+            log_util::log_debug("AiManager: deserialization completed successfully");
+
+            let report = quiz_validation::validate_and_repair(
+                &mut structured,
+                min_questions,
+                &history.past_questions,
+            );
+            log_util::log_debug(&format!(
+                "AiManager: validation attempt {}: {}",
+                attempt,
+                report.summary()
+            ));
+
+            if !report.needs_reprompt() {
+                return Ok(structured);
+            }
+
+            if attempt == MAX_GENERATION_ATTEMPTS {
+                if report.kept_questions == 0 {
+                    return Err(eyre!(
+                        "OpenAI response failed quiz validation after {} attempt(s): {}",
+                        attempt,
+                        report.summary()
+                    ));
+                }
+                log_util::log_debug(
+                    "AiManager: accepting partially valid response after final attempt",
+                );
+                return Ok(structured);
+            }
+
+            log_util::log_debug("AiManager: re-prompting model with validation errors");
+            messages.push(json!({
+                "role": "assistant",
+                "content": primary_text,
+            }));
+            messages.push(json!({
+                "role": "user",
+                "content": report.reprompt_message(),
+            }));
+        }
+
+        unreachable!("generation loop always returns on the final attempt")
+    }
+
+    /// Send the chat messages with the structured response format and return the assistant text.
+    async fn request_completion(
+        &self,
+        messages: &[Value],
+        usage_log: &mut Vec<GenerationUsage>,
+    ) -> Result<String> {
+        self.request_structured_completion(
+            messages,
+            "structured_learning_response",
+            schema_value(),
+            usage_log,
+        )
+        .await
+    }
+
+    /// Grade a free-text answer against its rubric.
+    pub async fn grade_free_text_answer(
+        &self,
+        request: &GradingRequest,
+        usage_log: &mut Vec<GenerationUsage>,
+    ) -> Result<FreeTextGrade> {
+        let messages = vec![
+            json!({
+                "role": "system",
+                "content": GRADING_SYSTEM_PROMPT,
+            }),
+            json!({
+                "role": "user",
+                "content": format!(
+                    "Question:\n{}\n\nConcept summary:\n{}\n\nRubric:\n{}\n\nStudent answer:\n{}",
+                    request.question, request.summary, request.rubric, request.answer
+                ),
+            }),
+        ];
+        let schema: Value =
+            serde_json::from_str(GRADING_SCHEMA).expect("grading schema must be valid JSON");
+        let text = self
+            .request_structured_completion(&messages, "free_text_grade", schema, usage_log)
+            .await?;
+        let mut grade: FreeTextGrade = serde_json::from_str(&text)
+            .wrap_err("failed to deserialize OpenAI response into FreeTextGrade")?;
+        grade.score = grade.score.min(100);
+        Ok(grade)
+    }
+
+    async fn request_structured_completion(
+        &self,
+        messages: &[Value],
+        schema_name: &str,
+        schema: Value,
+        usage_log: &mut Vec<GenerationUsage>,
+    ) -> Result<String> {
         let payload = json!({
             "model": self.model_name.as_str(),
-            "messages": [
-                {
-                    "role": "system",
-                    "content": config::system_prompt(),
-                },
-                {
-                    "role": "user",
-                    "content": prompt,
-                }
-            ],
+            "messages": messages,
             "response_format": {
                 "type": "json_schema",
                 "json_schema": {
-                    "name": "structured_learning_response",
+                    "name": schema_name,
                     "schema": schema,
                     "strict": true,
                 }
             }
         });
+        self.send_chat_payload(payload, usage_log).await
+    }
+
+    /// Answer a free-form follow-up question about a quiz item in plain text.
+    pub async fn followup_reply(
+        &self,
+        context: &FollowupContext,
+        history: &[ChatMessage],
+        usage_log: &mut Vec<GenerationUsage>,
+    ) -> Result<String> {
+        let mut messages = vec![
+            json!({
+                "role": "system",
+                "content": FOLLOWUP_SYSTEM_PROMPT,
+            }),
+            json!({
+                "role": "user",
+                "content": context.prompt(),
+            }),
+        ];
+        messages.extend(history.iter().map(|message| {
+            json!({
+                "role": message.role.as_str(),
+                "content": message.content,
+            })
+        }));
+        let payload = json!({
+            "model": self.model_name.as_str(),
+            "messages": messages,
+        });
+        self.send_chat_payload(payload, usage_log).await
+    }
 
+    /// POST a chat completions payload and return the assistant text, logging token usage.
+    async fn send_chat_payload(
+        &self,
+        payload: Value,
+        usage_log: &mut Vec<GenerationUsage>,
+    ) -> Result<String> {
         let endpoint = format!("{}/chat/completions", self.api_base);
         log_util::log_debug(&format!(
             "AiManager: invoking {} with model {}",
             endpoint, self.model_name
         ));
+        let started_at = Instant::now();
         let response = self
             .client
             .post(&endpoint)
@@ -317,33 +822,26 @@ impl AiManager {
             .wrap_err("failed to parse OpenAI response body as JSON")?;
         log_util::log_debug("AiManager: received OpenAI response");
 
+        if let Some((prompt_tokens, completion_tokens)) = extract_usage(&response_value) {
+            usage_log.push(GenerationUsage {
+                model_name: self.model_name.clone(),
+                prompt_tokens,
+                completion_tokens,
+                latency_ms: started_at.elapsed().as_millis() as u64,
+            });
+        }
+
         let primary_text = extract_completion_text(&response_value)
             .context("OpenAI response did not include assistant content")?;
         log_util::log_debug("AiManager: extracted assistant content");
 
-        let structured: StructuredLearningResponse = serde_json::from_str(&primary_text)
-            .wrap_err("failed to deserialize OpenAI response into StructuredLearningResponse")?;
-        log_util::log_debug("AiManager: deserialization completed successfully");
-
-        Ok(structured)
-    }
-
-    fn build_prompt(&self, summary: &str) -> String {
-        format!(
-            "Analyse the following session summary and produce a JSON payload that adheres to the provided schema. Return only valid JSON with double-quoted keys and strings.\n\nSchema:\n```json\n{}\n```\n\nSession summary:\n```markdown\n{}\n```",
-            JSON_SCHEMA, summary
-        )
+        Ok(primary_text)
     }
 }
 
 pub(crate) fn handle_ai_success(app: &mut App, mut structured: StructuredLearningResponse) {
+    canonicalize_topics(app, &mut structured);
     LearningManager::shuffle_quiz_options(&mut structured);
-    let group_count = structured.response.len();
-    let total_questions: usize = structured
-        .response
-        .iter()
-        .map(|group| group.quiz.len())
-        .sum();
 
     let save_result = write_ai_response(app, &structured);
     let store_result = if app.write_output_artifacts {
@@ -393,6 +891,165 @@ pub(crate) fn handle_ai_success(app: &mut App, mut structured: StructuredLearnin
         }
     }
 
+    show_learning_response(app, structured, status_parts, false);
+}
+
+/// Persist the generated lesson in the response cache before presenting it.
+pub(crate) fn handle_generated_lesson(app: &mut App, lesson: GeneratedLesson) {
+    if let Some(cache_key) = lesson.cache_key.as_deref() {
+        if app.write_output_artifacts {
+            match knowledge_store::store_cached_response(
+                cache_key,
+                &lesson.model_name,
+                &lesson.response,
+            ) {
+                Ok(_) => log_debug("App: cached learning response"),
+                Err(err) => log_debug(&format!("App: failed to cache learning response: {}", err)),
+            }
+        } else {
+            log_debug("App: skipping response cache (artifacts disabled)");
+        }
+    }
+    handle_ai_success(app, lesson.response);
+}
+
+/// Map generated knowledge group names onto canonical topics so stats do not fragment.
+fn canonicalize_topics(app: &App, structured: &mut StructuredLearningResponse) {
+    if !app.write_output_artifacts {
+        return;
+    }
+    if let Err(err) = knowledge_store::canonicalize_knowledge_groups(structured) {
+        log_debug(&format!(
+            "App: failed to match knowledge groups to topics: {}",
+            err
+        ));
+    }
+}
+
+/// Reopen a lesson from the knowledge store. It goes through the same presentation path as a
+/// freshly generated lesson but is not saved or recorded again, and its answers are logged as
+/// retries so they do not count as first tries.
+pub(crate) fn replay_past_lesson(
+    app: &mut App,
+    mut structured: StructuredLearningResponse,
+    session_date: &str,
+) {
+    LearningManager::shuffle_quiz_options(&mut structured);
+    show_learning_response(
+        app,
+        structured,
+        vec![format!("Replaying lesson from {}", session_date)],
+        true,
+    );
+}
+
+/// Open bookmarked questions in the Learning view. Like replays, they are not saved again and
+/// answers are logged as retries.
+pub(crate) fn practice_bookmarks(
+    app: &mut App,
+    mut structured: StructuredLearningResponse,
+    count: usize,
+) {
+    LearningManager::shuffle_quiz_options(&mut structured);
+    show_learning_response(
+        app,
+        structured,
+        vec![format!("Practicing {} bookmarked question(s)", count)],
+        true,
+    );
+}
+
+/// Drill only the questions missed on the first try in the current lesson. Answers are logged
+/// as retries, so the first-try results stay as they were.
+pub(crate) fn retry_missed_questions(app: &mut App) {
+    let Some(response) = app.learning_response.as_ref() else {
+        return;
+    };
+    let mut missed = results_manager::missed_questions(response, &app.quiz_first_attempts);
+    let count: usize = missed.response.iter().map(|group| group.quiz.len()).sum();
+    if count == 0 {
+        App::push_error(
+            &mut app.error,
+            "No questions were missed on the first try.".to_string(),
+        );
+        return;
+    }
+    LearningManager::shuffle_quiz_options(&mut missed);
+    show_learning_response(
+        app,
+        missed,
+        vec![format!("Retrying {} missed question(s)", count)],
+        true,
+    );
+}
+
+/// Open a review lesson of missed and unanswered questions from the knowledge store and the
+/// shared question bank. Like replays, review lessons are not saved again and answers are
+/// logged as retries.
+pub(crate) fn start_review(app: &mut App) {
+    let mut status_parts = Vec::new();
+    let shared = match shared_bank::configured_bank_dir() {
+        Some(bank_dir) => match shared_bank::load_bank(&bank_dir) {
+            Ok(shared) => shared,
+            Err(err) => {
+                log_debug(&format!("AI: failed to load shared bank: {}", err));
+                status_parts.push("Shared bank unavailable".to_string());
+                Vec::new()
+            }
+        },
+        None => Vec::new(),
+    };
+    let mut structured = match knowledge_store::load_review_queue(&shared, REVIEW_QUEUE_SIZE) {
+        Ok(structured) => structured,
+        Err(err) => {
+            App::push_error(
+                &mut app.error,
+                format!("Failed to build the review queue: {}", err),
+            );
+            return;
+        }
+    };
+    if structured.response.is_empty() {
+        App::push_error(
+            &mut app.error,
+            "Nothing to review: every stored and shared question was last answered correctly."
+                .to_string(),
+        );
+        return;
+    }
+
+    let shared_count = structured
+        .response
+        .iter()
+        .flat_map(|group| &group.quiz)
+        .filter(|item| item.author.is_some())
+        .count();
+    status_parts.insert(
+        0,
+        format!(
+            "Reviewing missed and unanswered questions ({} shared)",
+            shared_count
+        ),
+    );
+    LearningManager::shuffle_quiz_options(&mut structured);
+    show_learning_response(app, structured, status_parts, true);
+}
+
+/// Load a learning response into the Learning view without persisting it again. With `retry`,
+/// answers are logged as retries because the questions were already asked before.
+fn show_learning_response(
+    app: &mut App,
+    structured: StructuredLearningResponse,
+    mut status_parts: Vec<String>,
+    retry: bool,
+) {
+    let group_count = structured.response.len();
+    let total_questions: usize = structured
+        .response
+        .iter()
+        .map(|group| group.quiz.len())
+        .sum();
+
     status_parts.push(format!("Knowledge groups: {}", group_count));
     status_parts.push(format!("Total quiz questions: {}", total_questions));
     app.ai_status = Some(status_parts.join(" • "));
@@ -400,12 +1057,19 @@ pub(crate) fn handle_ai_success(app: &mut App, mut structured: StructuredLearnin
     app.learning_group_index = 0;
     app.learning_quiz_index = 0;
     app.learning_option_index = 0;
+    app.learning_marked_options.clear();
+    app.learning_text_input = None;
+    app.close_followup();
     reset_learning_feedback(
         &mut app.learning_feedback,
         &mut app.learning_summary_revealed,
         &mut app.learning_waiting_for_next,
     );
     app.quiz_first_attempts.clear();
+    app.learning_question_shown_at = None;
+    app.learning_started_at = Some(Instant::now());
+    app.learning_retry = retry;
+    app.challenge = None;
     app.analytics_snapshot = None;
     app.analytics_refreshed_at = None;
     app.learning_response = Some(structured);
@@ -435,8 +1099,19 @@ pub(crate) fn handle_ai_error(app: &mut App, message: String) {
     }
 }
 
+/// Generate a learning response, reusing a cached one for the same summary when available.
 pub(crate) fn trigger_learning_response(app: &mut App) {
     log_debug("App: menu option 'Generate learning response' selected");
+    start_learning_generation(app, false);
+}
+
+/// Generate a learning response from the model even if a cached one exists.
+pub(crate) fn trigger_fresh_learning_response(app: &mut App) {
+    log_debug("App: fresh learning response requested");
+    start_learning_generation(app, true);
+}
+
+fn start_learning_generation(app: &mut App, force_refresh: bool) {
     if app.ai_loading {
         log_debug("App: AI generation already in progress; ignoring duplicate request");
         return;
@@ -452,6 +1127,64 @@ pub(crate) fn trigger_learning_response(app: &mut App) {
         }
     };
 
+    let summary_override = app.summary_content.clone();
+    let model_name = manager.model_name().to_string();
+    let history = if app.write_output_artifacts {
+        LearnerHistory::load()
+    } else {
+        LearnerHistory::default()
+    };
+    let cache_key = match summary_override.as_deref() {
+        Some(summary) => match response_cache_key(&config::current(), summary, &model_name) {
+            Ok(key) => Some(key),
+            Err(err) => {
+                App::push_error(&mut app.error, format!("Prompt template error: {}", err));
+                app.ai_status = Some("Fix the prompt templates to generate lessons".to_string());
+                log_debug(&format!("App: prompt template error: {}", err));
+                return;
+            }
+        },
+        None => None,
+    };
+
+    if !force_refresh
+        && app.write_output_artifacts
+        && let Some(key) = cache_key.as_deref()
+    {
+        match knowledge_store::load_cached_response(key) {
+            Ok(Some(mut cached)) => {
+                log_debug("App: reusing cached learning response");
+                canonicalize_topics(app, &mut cached);
+                LearningManager::shuffle_quiz_options(&mut cached);
+                show_learning_response(
+                    app,
+                    cached,
+                    vec!["Loaded cached questions (press f for fresh ones)".to_string()],
+                    false,
+                );
+                return;
+            }
+            Ok(None) => log_debug("App: no cached learning response for this summary"),
+            Err(err) => log_debug(&format!("App: failed to read response cache: {}", err)),
+        }
+    }
+
+    let monthly_budget = config::current().monthly_budget_usd;
+    if monthly_budget > 0.0 {
+        match knowledge_store::month_to_date_cost() {
+            Ok(spent) => {
+                if let Some(message) = budget_reached_message(monthly_budget, spent) {
+                    App::push_error(&mut app.error, message.clone());
+                    app.ai_status = Some(message);
+                    log_debug("App: monthly budget exceeded; aborting generation");
+                    return;
+                }
+            }
+            Err(err) => log_debug(&format!("App: failed to read month-to-date cost: {}", err)),
+        }
+    }
+
+    let record_usage = records_usage(app.write_output_artifacts, monthly_budget);
     let (sender, receiver) = mpsc::channel();
     app.ai_result_receiver = Some(receiver);
     app.ai_loading = true;
@@ -461,8 +1194,6 @@ pub(crate) fn trigger_learning_response(app: &mut App) {
     log_debug("App: displaying learning loading spinner");
     log_debug("App: starting OpenAI generation task");
 
-    let summary_override = app.summary_content.clone();
-
     thread::spawn(move || {
         log_debug("App: background OpenAI generation task started");
         let runtime = match tokio::runtime::Runtime::new() {
@@ -476,12 +1207,22 @@ pub(crate) fn trigger_learning_response(app: &mut App) {
             }
         };
 
-        let result = runtime.block_on(manager.generate_learning_response(summary_override));
+        let mut usage_log = Vec::new();
+        let result = runtime.block_on(manager.generate_learning_response(
+            summary_override,
+            &history,
+            &mut usage_log,
+        ));
         drop(runtime);
+        record_generation_usage(&usage_log, "lesson", record_usage);
 
         match result {
-            Ok(structured) => {
-                let _ = sender.send(AiTaskMessage::Success(structured));
+            Ok(response) => {
+                let _ = sender.send(AiTaskMessage::Success(GeneratedLesson {
+                    response,
+                    cache_key,
+                    model_name,
+                }));
             }
             Err(err) => {
                 let _ = sender.send(AiTaskMessage::Error(err.to_string()));
@@ -490,6 +1231,170 @@ pub(crate) fn trigger_learning_response(app: &mut App) {
     });
 }
 
+/// Send a free-text answer to the grader on a background thread.
+pub(crate) fn trigger_free_text_grading(app: &mut App, request: GradingRequest) {
+    if app.grading_receiver.is_some() {
+        app.learning_feedback = Some("Still grading your previous answer…".to_string());
+        return;
+    }
+    let Some(manager) = app.ai_manager.clone() else {
+        App::push_error(&mut app.error, crate::OPENAI_KEY_HELP.to_string());
+        app.learning_feedback =
+            Some("Free-text answers need an OpenAI API key to be graded.".to_string());
+        return;
+    };
+
+    let record_usage = records_usage(
+        app.write_output_artifacts,
+        config::current().monthly_budget_usd,
+    );
+    let (sender, receiver) = mpsc::channel();
+    app.grading_receiver = Some(receiver);
+    app.learning_feedback = Some("Grading your answer…".to_string());
+    log_debug("App: starting free-text grading task");
+
+    thread::spawn(move || {
+        let runtime = match tokio::runtime::Runtime::new() {
+            Ok(runtime) => runtime,
+            Err(err) => {
+                let _ = sender.send(GradingMessage::Error(format!(
+                    "Failed to build Tokio runtime: {}",
+                    err
+                )));
+                return;
+            }
+        };
+
+        let mut usage_log = Vec::new();
+        let result = runtime.block_on(manager.grade_free_text_answer(&request, &mut usage_log));
+        drop(runtime);
+        record_generation_usage(&usage_log, "grading", record_usage);
+
+        let message = match result {
+            Ok(grade) => GradingMessage::Success(request, grade),
+            Err(err) => GradingMessage::Error(err.to_string()),
+        };
+        let _ = sender.send(message);
+    });
+}
+
+pub(crate) fn poll_grading_messages(app: &mut App) {
+    let Some(receiver) = app.grading_receiver.as_ref() else {
+        return;
+    };
+    let message = match receiver.try_recv() {
+        Ok(message) => message,
+        Err(TryRecvError::Empty) => return,
+        Err(TryRecvError::Disconnected) => {
+            GradingMessage::Error("Background grading worker disconnected".to_string())
+        }
+    };
+    app.grading_receiver = None;
+
+    match message {
+        GradingMessage::Success(request, grade) => {
+            LearningManager::new(app).apply_free_text_grade(request, grade)
+        }
+        GradingMessage::Error(message) => {
+            App::push_error(&mut app.error, format!("Grading failed: {}", message));
+            app.learning_feedback =
+                Some("Could not grade that answer. Press Enter to try again.".to_string());
+            log_debug(&format!("App: free-text grading failed: {}", message));
+        }
+    }
+}
+
+/// Send the open follow-up conversation to the tutor on a background thread.
+pub(crate) fn trigger_followup_reply(app: &mut App) {
+    let Some(chat) = app.followup.as_ref() else {
+        return;
+    };
+    let Some(manager) = app.ai_manager.clone() else {
+        App::push_error(&mut app.error, crate::OPENAI_KEY_HELP.to_string());
+        return;
+    };
+    let context = chat.context.clone();
+    let history = chat.messages.clone();
+    let (group_index, quiz_index) = (chat.group_index, chat.quiz_index);
+    let question_id = knowledge_store::question_id(&chat.context.question);
+    let record_usage = records_usage(
+        app.write_output_artifacts,
+        config::current().monthly_budget_usd,
+    );
+    let (sender, receiver) = mpsc::channel();
+    app.followup_receiver = Some(receiver);
+    log_debug("App: starting follow-up reply task");
+
+    thread::spawn(move || {
+        let runtime = match tokio::runtime::Runtime::new() {
+            Ok(runtime) => runtime,
+            Err(err) => {
+                let _ = sender.send(FollowupTaskMessage::Error(format!(
+                    "Failed to build Tokio runtime: {}",
+                    err
+                )));
+                return;
+            }
+        };
+
+        let mut usage_log = Vec::new();
+        let result = runtime.block_on(manager.followup_reply(&context, &history, &mut usage_log));
+        drop(runtime);
+        record_generation_usage(&usage_log, "followup", record_usage);
+
+        let message = match result {
+            Ok(reply) => FollowupTaskMessage::Reply {
+                group_index,
+                quiz_index,
+                question_id,
+                reply,
+            },
+            Err(err) => FollowupTaskMessage::Error(err.to_string()),
+        };
+        let _ = sender.send(message);
+    });
+}
+
+pub(crate) fn poll_followup_messages(app: &mut App) {
+    let Some(receiver) = app.followup_receiver.as_ref() else {
+        return;
+    };
+    let message = match receiver.try_recv() {
+        Ok(message) => message,
+        Err(TryRecvError::Empty) => return,
+        Err(TryRecvError::Disconnected) => {
+            FollowupTaskMessage::Error("Background follow-up worker disconnected".to_string())
+        }
+    };
+    app.followup_receiver = None;
+
+    match message {
+        FollowupTaskMessage::Reply {
+            group_index,
+            quiz_index,
+            question_id,
+            reply,
+        } => {
+            let same_question = app.followup.as_ref().is_some_and(|chat| {
+                (chat.group_index, chat.quiz_index) == (group_index, quiz_index)
+                    && knowledge_store::question_id(&chat.context.question) == question_id
+            });
+            if !same_question {
+                log_debug("App: dropping follow-up reply for a question that is no longer open");
+                return;
+            }
+            LearningManager::new(app).add_followup_message(ChatMessage {
+                role: ChatRole::Assistant,
+                content: reply.trim().to_string(),
+            })
+        }
+        FollowupTaskMessage::Error(message) => {
+            App::push_error(&mut app.error, format!("Follow-up failed: {}", message));
+            log_debug(&format!("App: follow-up reply failed: {}", message));
+        }
+    }
+}
+
 impl App {
     pub(crate) fn update_loading_status(&mut self) {
         if self.ai_loading {
@@ -499,6 +1404,52 @@ impl App {
     }
 }
 
+/// Usage is logged with artifacts disabled too while a monthly budget is set, since the budget
+/// is checked against the logged spend.
+fn records_usage(write_output_artifacts: bool, monthly_budget: f64) -> bool {
+    write_output_artifacts || monthly_budget > 0.0
+}
+
+/// Message shown instead of generating once the month's spend has reached the budget.
+fn budget_reached_message(monthly_budget: f64, spent: f64) -> Option<String> {
+    (spent >= monthly_budget).then(|| {
+        format!(
+            "Monthly AI budget of ${:.2} reached (${:.2} spent). Raise it in the Config view to keep generating.",
+            monthly_budget, spent
+        )
+    })
+}
+
+/// Persist token usage for completed requests; called from background workers.
+fn record_generation_usage(usage_log: &[GenerationUsage], purpose: &str, persist: bool) {
+    if !persist {
+        log_debug("App: skipping usage persistence (artifacts disabled, no budget set)");
+        return;
+    }
+    for usage in usage_log {
+        if let Err(err) = knowledge_store::record_generation_usage(usage, purpose) {
+            log_debug(&format!("App: failed to record generation usage: {}", err));
+        }
+    }
+}
+
+/// Cache key for a generation: the same summary, model, templates and settings yield the same
+/// key. The prompts are rendered without learner history, so answering questions does not
+/// change the key and `r` keeps reusing the cached lesson until fresh questions are requested.
+pub(crate) fn response_cache_key(
+    config: &AppConfig,
+    summary: &str,
+    model_name: &str,
+) -> Result<String> {
+    let prompt =
+        prompt_templates::render_prompt(config, summary, JSON_SCHEMA, &LearnerHistory::default())?;
+    Ok(knowledge_store::content_hash(&[
+        &prompt.user,
+        model_name,
+        &prompt.system,
+    ]))
+}
+
 fn schema_value() -> Value {
     serde_json::from_str(JSON_SCHEMA).expect("JSON_SCHEMA is valid")
 }
@@ -534,6 +1485,13 @@ fn extract_completion_text(value: &Value) -> Option<String> {
     }
 }
 
+fn extract_usage(value: &Value) -> Option<(u64, u64)> {
+    let usage = value.get("usage")?;
+    let prompt_tokens = usage.get("prompt_tokens")?.as_u64()?;
+    let completion_tokens = usage.get("completion_tokens")?.as_u64()?;
+    Some((prompt_tokens, completion_tokens))
+}
+
 fn write_ai_response(app: &App, response: &StructuredLearningResponse) -> Result<PathBuf> {
     if !app.write_output_artifacts {
         let serialized =
@@ -578,7 +1536,7 @@ pub(crate) fn poll_ai_messages(app: &mut App) {
                 app.ai_loading = false;
                 clear_receiver = true;
                 match message {
-                    AiTaskMessage::Success(response) => handle_ai_success(app, response),
+                    AiTaskMessage::Success(lesson) => handle_generated_lesson(app, lesson),
                     AiTaskMessage::Error(message) => handle_ai_error(app, message),
                 }
             }
@@ -599,9 +1557,12 @@ pub(crate) fn poll_ai_messages(app: &mut App) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{AppConfig, ConfigForm, OpenAiModelKind};
+    use crate::config::{ConfigForm, OpenAiModelKind};
+    use crate::view_managers::{
+        BookmarksState, HistoryState, LeaderboardState, ResultsState, TopicsState,
+    };
     use std::{
-        collections::HashSet,
+        collections::{HashMap, HashSet},
         path::{Path, PathBuf},
         sync::mpsc,
     };
@@ -625,6 +1586,9 @@ mod tests {
             ai_loading: false,
             ai_loading_frame: 0,
             ai_result_receiver: None,
+            grading_receiver: None,
+            followup: None,
+            followup_receiver: None,
             learning_response: None,
             learning_group_index: 0,
             learning_quiz_index: 0,
@@ -632,13 +1596,26 @@ mod tests {
             learning_feedback: None,
             learning_summary_revealed: false,
             learning_waiting_for_next: false,
+            learning_marked_options: Vec::new(),
+            learning_text_input: None,
+            learning_flag_note: None,
             config_form: ConfigForm::from_config(AppConfig::default()),
             write_output_artifacts: false,
             openai_model: OpenAiModelKind::Gpt5Mini,
-            quiz_first_attempts: HashSet::new(),
+            quiz_first_attempts: HashMap::new(),
+            learning_started_at: None,
+            learning_retry: false,
+            learning_question_shown_at: None,
+            question_marks: HashSet::new(),
+            challenge: None,
             analytics_snapshot: None,
             analytics_error: None,
             analytics_refreshed_at: None,
+            topics: TopicsState::default(),
+            history: HistoryState::default(),
+            bookmarks: BookmarksState::default(),
+            leaderboard: LeaderboardState::default(),
+            results: ResultsState::default(),
         }
     }
 
@@ -653,13 +1630,16 @@ mod tests {
                         QuizOption {
                             selection: "Memory safety".to_string(),
                             is_correct_answer: true,
+                            ..Default::default()
                         },
                         QuizOption {
                             selection: "Runtime polymorphism".to_string(),
                             is_correct_answer: false,
+                            ..Default::default()
                         },
                     ],
                     resources: vec!["https://doc.rust-lang.org/".to_string()],
+                    ..Default::default()
                 }],
                 knowledge_type_language: "Rust".to_string(),
             }],
@@ -703,6 +1683,33 @@ mod tests {
         assert!(extract_completion_text(&missing).is_none());
     }
 
+    #[test]
+    fn extract_usage_reads_token_counts_and_estimates_cost() {
+        let value = json!({
+            "usage": {
+                "prompt_tokens": 2_000_000,
+                "completion_tokens": 500_000,
+                "total_tokens": 2_500_000
+            }
+        });
+        assert_eq!(extract_usage(&value), Some((2_000_000, 500_000)));
+        assert!(extract_usage(&json!({})).is_none());
+
+        let usage = GenerationUsage {
+            model_name: "gpt-5-mini".to_string(),
+            prompt_tokens: 2_000_000,
+            completion_tokens: 500_000,
+            latency_ms: 10,
+        };
+        assert!((usage.estimated_cost_usd() - 1.5).abs() < 1e-9);
+
+        let unknown = GenerationUsage {
+            model_name: "custom-model".to_string(),
+            ..usage
+        };
+        assert_eq!(unknown.estimated_cost_usd(), 0.0);
+    }
+
     #[test]
     fn write_ai_response_returns_in_memory_path_when_not_persisting() {
         let app = test_app();
@@ -730,6 +1737,104 @@ mod tests {
         assert!(status.contains("Total quiz questions: 1"));
     }
 
+    #[test]
+    fn replay_past_lesson_opens_learning_view_with_fresh_state() {
+        let mut app = test_app();
+        app.quiz_first_attempts.insert((0, 0), false);
+        app.learning_feedback = Some("stale".to_string());
+
+        replay_past_lesson(&mut app, sample_response(), "2024-04-20");
+
+        assert_eq!(app.view, AppView::Learning);
+        assert!(app.quiz_first_attempts.is_empty());
+        assert!(app.learning_feedback.is_none());
+        let status = app.ai_status.as_deref().unwrap();
+        assert!(status.starts_with("Replaying lesson from 2024-04-20"));
+        assert!(!status.contains("Knowledge history updated"));
+        let options = &app.learning_response.as_ref().unwrap().response[0].quiz[0].options;
+        assert!(options.iter().all(|option| option.position.is_some()));
+    }
+
+    #[test]
+    fn replayed_answers_do_not_count_as_first_tries() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-replay-retry-{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let mut app = test_app();
+        let record = |app: &App, db_path: &Path| {
+            let response = app.learning_response.as_ref().unwrap();
+            let group = &response.response[0];
+            let question = &group.quiz[0];
+            knowledge_store::record_quiz_attempt_at_path(
+                db_path,
+                &app.session_date,
+                &group.knowledge_type_group,
+                Some(&group.knowledge_type_language),
+                question,
+                true,
+                &app.attempt_answer(0, 0, question, &knowledge_store::AttemptAnswer::default()),
+            )
+            .unwrap();
+        };
+
+        replay_past_lesson(&mut app, sample_response(), "2024-04-20");
+        assert!(app.learning_retry);
+        let replay_db = temp_dir.join("replay.sqlite");
+        record(&app, &replay_db);
+        assert!(
+            knowledge_store::load_group_accuracy_at_path(&replay_db)
+                .unwrap()
+                .is_empty(),
+            "a replayed answer is logged as a retry"
+        );
+
+        show_learning_response(&mut app, sample_response(), Vec::new(), false);
+        let fresh_db = temp_dir.join("fresh.sqlite");
+        record(&app, &fresh_db);
+        let accuracy = knowledge_store::load_group_accuracy_at_path(&fresh_db).unwrap();
+        assert_eq!(
+            accuracy.len(),
+            1,
+            "a new lesson is answered for the first time"
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn monthly_budget_applies_with_artifacts_disabled() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-budget-{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+        let monthly_budget = 0.5;
+        assert!(
+            !records_usage(false, 0.0),
+            "nothing is logged without a budget"
+        );
+        assert!(records_usage(false, monthly_budget));
+
+        let usage = GenerationUsage {
+            model_name: "gpt-5-mini".to_string(),
+            prompt_tokens: 1_000_000,
+            completion_tokens: 1_000_000,
+            latency_ms: 1_000,
+        };
+        knowledge_store::record_generation_usage_at_path(&db_path, &usage, "lesson").unwrap();
+        let spent = knowledge_store::month_to_date_cost_at_path(&db_path).unwrap();
+        let message = budget_reached_message(monthly_budget, spent).unwrap();
+        assert!(message.starts_with("Monthly AI budget of $0.50 reached"));
+        assert!(budget_reached_message(monthly_budget, 0.0).is_none());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
     #[test]
     fn handle_ai_error_distinguishes_runtime_failure() {
         let mut app = test_app();
@@ -770,7 +1875,11 @@ mod tests {
         let (sender, receiver) = mpsc::channel();
         app.ai_result_receiver = Some(receiver);
         sender
-            .send(AiTaskMessage::Success(sample_response()))
+            .send(AiTaskMessage::Success(GeneratedLesson {
+                response: sample_response(),
+                cache_key: Some("cache-key".to_string()),
+                model_name: "gpt-5-mini".to_string(),
+            }))
             .unwrap();
 
         poll_ai_messages(&mut app);
@@ -799,6 +1908,98 @@ mod tests {
         assert!(error.contains("AI generation failed: failure"));
     }
 
+    #[test]
+    fn response_cache_key_changes_with_each_input() {
+        let config = AppConfig::default();
+        let base = response_cache_key(&config, "summary", "gpt-5-mini").unwrap();
+        assert_eq!(
+            base,
+            response_cache_key(&config, "summary", "gpt-5-mini").unwrap()
+        );
+        assert_ne!(
+            base,
+            response_cache_key(&config, "summary 2", "gpt-5-mini").unwrap()
+        );
+        assert_ne!(
+            base,
+            response_cache_key(&config, "summary", "gpt-5").unwrap()
+        );
+        let more_questions = AppConfig {
+            min_quiz_questions: config.min_quiz_questions + 1,
+            ..config.clone()
+        };
+        assert_ne!(
+            base,
+            response_cache_key(&more_questions, "summary", "gpt-5-mini").unwrap()
+        );
+    }
+
+    #[test]
+    fn response_cache_key_ignores_answers_recorded_between_generations() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-cache-key-{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+        let config = AppConfig::default();
+        let history_at = |db_path: &Path| LearnerHistory {
+            mastered_concepts: knowledge_store::load_mastered_concepts_at_path(db_path).unwrap(),
+            group_accuracy: knowledge_store::load_group_accuracy_at_path(db_path).unwrap(),
+            ..Default::default()
+        };
+
+        let before = response_cache_key(&config, "summary", "gpt-5-mini").unwrap();
+        let prompt_before =
+            prompt_templates::render_prompt(&config, "summary", JSON_SCHEMA, &history_at(&db_path))
+                .unwrap();
+        for question in ["Borrow?", "Move?", "Copy?"] {
+            knowledge_store::record_quiz_attempt_at_path(
+                &db_path,
+                "2024-05-01",
+                "Ownership",
+                Some("Rust"),
+                &QuizItem {
+                    question: question.to_string(),
+                    ..Default::default()
+                },
+                true,
+                &knowledge_store::AttemptAnswer::default(),
+            )
+            .unwrap();
+        }
+        let prompt_after =
+            prompt_templates::render_prompt(&config, "summary", JSON_SCHEMA, &history_at(&db_path))
+                .unwrap();
+        let after = response_cache_key(&config, "summary", "gpt-5-mini").unwrap();
+
+        assert_ne!(
+            prompt_before.system, prompt_after.system,
+            "the prompt sent to the model reflects the new answers"
+        );
+        assert_eq!(before, after, "but the cached lesson is still found");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn quiz_kind_defaults_to_multiple_choice_for_older_payloads() {
+        let legacy: QuizItem = serde_json::from_str(
+            r#"{"question": "q", "options": [{"selection": "a", "is_correct_answer": true}], "resources": []}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.kind, QuizKind::MultipleChoice);
+        assert!(legacy.accepted_answers.is_empty());
+
+        let blank: QuizItem = serde_json::from_str(
+            r#"{"question": "let x = ____;", "kind": "fill_in_blank", "options": [], "accepted_answers": ["5"], "resources": []}"#,
+        )
+        .unwrap();
+        assert_eq!(blank.kind, QuizKind::FillInBlank);
+        assert_eq!(blank.accepted_answers, vec!["5".to_string()]);
+    }
+
     #[test]
     fn is_markdown_detects_md_extension() {
         assert!(is_markdown(Path::new("note.md")));
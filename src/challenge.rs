@@ -0,0 +1,128 @@
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+
+use crate::{ai_manager::StructuredLearningResponse, knowledge_store::ChallengeRun};
+
+/// Countdown for each question in a timed challenge.
+pub const SECONDS_PER_QUESTION: u64 = 30;
+/// Points for a question answered correctly on the first try.
+pub const CORRECT_POINTS: u32 = 100;
+/// Extra points for answering instantly, shrinking linearly to zero as the countdown runs out.
+pub const MAX_SPEED_BONUS: u32 = 100;
+/// Number of runs shown on the challenge leaderboard.
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// A timed challenge in progress over the lesson shown in the Learning view. Each question
+/// gets one scored attempt; questions whose countdown runs out count as wrong.
+#[derive(Debug, Clone)]
+pub struct ChallengeState {
+    pub started_at: DateTime<Utc>,
+    pub started: Instant,
+    pub seconds_per_question: u64,
+    pub question_count: usize,
+    pub answered: usize,
+    pub correct: usize,
+    pub timed_out: usize,
+    pub score: u32,
+}
+
+impl ChallengeState {
+    pub fn new(question_count: usize, seconds_per_question: u64) -> Self {
+        Self {
+            started_at: Utc::now(),
+            started: Instant::now(),
+            seconds_per_question,
+            question_count,
+            answered: 0,
+            correct: 0,
+            timed_out: 0,
+            score: 0,
+        }
+    }
+
+    pub fn limit_ms(&self) -> u64 {
+        self.seconds_per_question * 1000
+    }
+
+    /// Milliseconds left on the countdown for a question shown `elapsed_ms` ago.
+    pub fn remaining_ms(&self, elapsed_ms: u64) -> u64 {
+        self.limit_ms().saturating_sub(elapsed_ms)
+    }
+
+    /// Score the first attempt at a question and return the points it earned.
+    pub fn record_answer(&mut self, correct: bool, elapsed_ms: u64) -> u32 {
+        let points = question_points(correct, elapsed_ms, self.limit_ms());
+        self.answered += 1;
+        if correct {
+            self.correct += 1;
+        }
+        self.score += points;
+        points
+    }
+
+    /// Count a question whose countdown ran out before it was answered.
+    pub fn record_timeout(&mut self) {
+        self.answered += 1;
+        self.timed_out += 1;
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.answered >= self.question_count
+    }
+
+    /// The finished run as stored on the leaderboard.
+    pub fn to_run(&self, session_date: &str, lesson: &StructuredLearningResponse) -> ChallengeRun {
+        ChallengeRun {
+            session_date: session_date.to_string(),
+            started_at: self.started_at.to_rfc3339(),
+            finished_at: Utc::now().to_rfc3339(),
+            knowledge_groups: lesson
+                .response
+                .iter()
+                .filter(|group| !group.quiz.is_empty())
+                .map(|group| group.knowledge_type_group.clone())
+                .collect(),
+            question_count: self.question_count,
+            correct_count: self.correct,
+            timed_out_count: self.timed_out,
+            score: self.score,
+            seconds_per_question: self.seconds_per_question,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+/// Points for one question: nothing when wrong, otherwise the base points plus a speed bonus.
+pub fn question_points(correct: bool, elapsed_ms: u64, limit_ms: u64) -> u32 {
+    if !correct || limit_ms == 0 {
+        return 0;
+    }
+    let remaining = limit_ms.saturating_sub(elapsed_ms);
+    CORRECT_POINTS + (MAX_SPEED_BONUS as u64 * remaining / limit_ms) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed_bonus_shrinks_with_time_and_wrong_answers_score_nothing() {
+        assert_eq!(question_points(true, 0, 30_000), 200);
+        assert_eq!(question_points(true, 15_000, 30_000), 150);
+        assert_eq!(question_points(true, 45_000, 30_000), 100);
+        assert_eq!(question_points(false, 1_000, 30_000), 0);
+
+        let mut challenge = ChallengeState::new(3, 30);
+        assert_eq!(challenge.record_answer(true, 15_000), 150);
+        assert_eq!(challenge.record_answer(false, 2_000), 0);
+        assert!(!challenge.is_complete());
+        challenge.record_timeout();
+        assert!(challenge.is_complete());
+        assert_eq!(
+            (challenge.correct, challenge.timed_out, challenge.score),
+            (1, 1, 150)
+        );
+        assert_eq!(challenge.remaining_ms(31_000), 0);
+    }
+}
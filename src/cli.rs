@@ -0,0 +1,208 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDate, Utc};
+use color_eyre::eyre::{Result, eyre};
+
+use crate::{
+    ai_manager::JSON_SCHEMA,
+    config,
+    knowledge_store::{self, ExportFilter},
+    output_manager::OutputManager,
+    prompt_templates::{self, TemplateKind},
+    question_history::LearnerHistory,
+    quiz_export::{self, PortableFormat},
+    session_manager::SessionManager,
+};
+
+const EXPORT_USAGE: &str = "Usage: learnchain export --format anki|json|csv [--since YYYY-MM-DD] [--until YYYY-MM-DD] [--group NAME] [--language LANG] [--output PATH]";
+
+/// Options shared by every export format.
+struct ExportOptions {
+    format: String,
+    filter: ExportFilter,
+    output: Option<PathBuf>,
+}
+
+/// Handle `learnchain export`: write stored quizzes matching the filters to a file, either as
+/// an Anki deck or as a JSON/CSV copy of the knowledge store.
+pub fn run_export_command(args: &[String]) -> Result<()> {
+    let options = parse_export_options(args)?;
+    let label = Utc::now().format("%Y-%m-%d").to_string();
+    if options.format == "anki" {
+        let responses = knowledge_store::load_stored_responses(&options.filter)
+            .map_err(|err| eyre!("failed to read knowledge history: {}", err))?;
+        let (path, notes) =
+            quiz_export::write_anki_deck(&responses, options.output.as_deref(), &label)?;
+        println!(
+            "Exported {} note(s) to {}. Import it in Anki with File > Import.",
+            notes,
+            path.display()
+        );
+        return Ok(());
+    }
+
+    let Some(format) = PortableFormat::parse(&options.format) else {
+        return Err(eyre!(
+            "unknown export format '{}'. {}",
+            options.format,
+            EXPORT_USAGE
+        ));
+    };
+    let store = knowledge_store::export_portable_store(&options.filter)
+        .map_err(|err| eyre!("failed to read knowledge history: {}", err))?;
+    let path =
+        quiz_export::write_portable_store(&store, format, options.output.as_deref(), &label)?;
+    println!(
+        "Exported {} knowledge response(s) and {} attempt(s) to {}.",
+        store.responses.len(),
+        store.attempts.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Handle `learnchain import <file>`: merge a JSON or CSV export into the knowledge store.
+pub fn run_import_command(args: &[String]) -> Result<()> {
+    let [file] = args else {
+        eprintln!("Usage: learnchain import <file.json|file.csv>");
+        std::process::exit(1);
+    };
+    let store = quiz_export::read_portable_store(Path::new(file))?;
+    let summary = knowledge_store::import_portable_store(&store)
+        .map_err(|err| eyre!("failed to import {}: {}", file, err))?;
+    println!(
+        "Imported {} knowledge response(s) with {} new question(s) and {} attempt(s).",
+        summary.responses_added, summary.questions_added, summary.attempts_added
+    );
+    if summary.questions_skipped + summary.attempts_skipped > 0 {
+        println!(
+            "Skipped {} question(s) and {} attempt(s) already in the knowledge store.",
+            summary.questions_skipped, summary.attempts_skipped
+        );
+    }
+    Ok(())
+}
+
+fn parse_export_options(args: &[String]) -> Result<ExportOptions> {
+    let mut args = args.iter().peekable();
+    // `learnchain export anki` is shorthand for `--format anki`.
+    let mut format = args
+        .next_if(|arg| !arg.starts_with("--"))
+        .map(|arg| arg.to_ascii_lowercase());
+    let mut filter = ExportFilter::default();
+    let mut output = None;
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| eyre!("missing value for {}. {}", flag, EXPORT_USAGE))?;
+        match flag.as_str() {
+            "--format" => format = Some(value.to_ascii_lowercase()),
+            "--since" => filter.since = Some(parse_date(value)?),
+            "--until" => filter.until = Some(parse_date(value)?),
+            "--group" => filter.knowledge_type_group = Some(value.clone()),
+            "--language" => filter.knowledge_type_language = Some(value.clone()),
+            "--output" => output = Some(PathBuf::from(value)),
+            _ => return Err(eyre!("unknown export option '{}'. {}", flag, EXPORT_USAGE)),
+        }
+    }
+    let format = format.ok_or_else(|| eyre!("choose an export format. {}", EXPORT_USAGE))?;
+    Ok(ExportOptions {
+        format,
+        filter,
+        output,
+    })
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| eyre!("'{}' is not a date in YYYY-MM-DD form", value))
+}
+
+/// Handle `learnchain mastery`: print every knowledge group ranked by mastery.
+pub fn run_mastery_command() -> Result<()> {
+    let report = knowledge_store::mastery_report()
+        .map_err(|err| eyre!("failed to build mastery report: {}", err))?;
+    if report.entries.is_empty() {
+        println!("No quiz attempts recorded yet. Answer some questions first.");
+        return Ok(());
+    }
+    println!(
+        "{:>4}  {:>7}  {:>8}  {:<12}  {:<10}  Knowledge group",
+        "Rank", "Mastery", "Attempts", "Last", "Language"
+    );
+    for (index, entry) in report.entries.iter().enumerate() {
+        println!(
+            "{:>4}  {:>6.0}%  {:>8}  {:<12}  {:<10}  {}",
+            index + 1,
+            entry.score * 100.0,
+            entry.attempts,
+            entry
+                .last_attempt
+                .map(|date| date.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            if entry.knowledge_type_language.is_empty() {
+                "-"
+            } else {
+                entry.knowledge_type_language.as_str()
+            },
+            entry.knowledge_type_group
+        );
+    }
+    Ok(())
+}
+
+/// Handle `learnchain prompt <subcommand>`.
+pub fn run_prompt_command(args: &[String]) -> Result<()> {
+    match args.first().map(String::as_str) {
+        Some("preview") => preview_prompt(),
+        _ => {
+            eprintln!("Usage: learnchain prompt preview");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Render the system and user prompts for today's session exactly as they would be sent.
+fn preview_prompt() -> Result<()> {
+    if let Err(err) = config::initialize() {
+        eprintln!(
+            "Warning: configuration load failed, using defaults: {}",
+            err
+        );
+    }
+    let config_snapshot = config::current();
+
+    let load = SessionManager::from_source(config_snapshot.session_source).load_today_events();
+    if let Some(error) = load.error.as_deref() {
+        eprintln!("Warning: {}", error);
+    }
+    let artifact = OutputManager::new().write_markdown_summary(
+        &load.events,
+        &load.session_date,
+        load.latest_file.as_deref(),
+        false,
+    );
+
+    let prompt = prompt_templates::render_prompt(
+        &config_snapshot,
+        &artifact.content,
+        JSON_SCHEMA,
+        &LearnerHistory::load(),
+    )
+    .map_err(|err| eyre!("failed to render prompt: {}", err))?;
+
+    for kind in [TemplateKind::System, TemplateKind::User] {
+        let path = prompt_templates::template_path(kind);
+        let origin = if path.exists() {
+            format!("override at {}", path.display())
+        } else {
+            format!("built-in default; create {} to override", path.display())
+        };
+        let (title, body) = match kind {
+            TemplateKind::System => ("System prompt", &prompt.system),
+            TemplateKind::User => ("User prompt", &prompt.user),
+        };
+        println!("===== {} ({}) =====\n{}\n", title, origin, body);
+    }
+    Ok(())
+}
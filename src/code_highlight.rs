@@ -0,0 +1,330 @@
+use std::sync::OnceLock;
+
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span, Text},
+};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{self, Theme, ThemeSet},
+    parsing::{SyntaxReference, SyntaxSet},
+};
+
+/// Bundled syntect theme used for code in the Learning view.
+const THEME_NAME: &str = "base16-ocean.dark";
+const FENCE: &str = "```";
+const TAB_WIDTH: usize = 4;
+
+struct Highlighter {
+    syntaxes: SyntaxSet,
+    theme: Theme,
+}
+
+fn highlighter() -> &'static Highlighter {
+    static HIGHLIGHTER: OnceLock<Highlighter> = OnceLock::new();
+    HIGHLIGHTER.get_or_init(|| {
+        let mut themes = ThemeSet::load_defaults();
+        Highlighter {
+            syntaxes: SyntaxSet::load_defaults_newlines(),
+            theme: themes.themes.remove(THEME_NAME).unwrap_or_default(),
+        }
+    })
+}
+
+/// Turn one field that may contain Markdown code into styled lines wrapped to `width` columns.
+/// Fenced blocks are highlighted and continue each wrapped line at its indentation; inline
+/// `code` is highlighted in place. Fences without a language tag and inline code use
+/// `language`. A fence left open ends with the field, so callers highlight each field on its
+/// own and keep the text around it plain.
+pub fn highlight_text(text: &str, language: &str, width: u16) -> Text<'static> {
+    let highlighter = highlighter();
+    let width = usize::from(width);
+    let mut lines = Vec::new();
+    let mut fence: Option<HighlightLines> = None;
+
+    for raw in text.split('\n') {
+        if let Some(block) = fence.as_mut() {
+            if let Some(after) = raw.trim_start().strip_prefix(FENCE) {
+                fence = None;
+                if !after.trim().is_empty() {
+                    lines.extend(wrap_prose(prose_line(after, language), width));
+                }
+            } else {
+                lines.extend(code_lines(block, raw, width));
+            }
+            continue;
+        }
+
+        // An unmatched fence opens a block; a matched pair on one line is inline code.
+        match raw.find(FENCE) {
+            Some(start) if raw.matches(FENCE).count() % 2 == 1 => {
+                let (before, opening) = raw.split_at(start);
+                if !before.trim().is_empty() {
+                    lines.extend(wrap_prose(prose_line(before, language), width));
+                }
+                let tag = opening[FENCE.len()..]
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or(language);
+                fence = Some(HighlightLines::new(
+                    syntax_for(tag, language),
+                    &highlighter.theme,
+                ));
+            }
+            _ => lines.extend(wrap_prose(prose_line(raw, language), width)),
+        }
+    }
+    Text::from(lines)
+}
+
+/// [`highlight_text`] for a field shown after a plain `label` on its first line, such as an
+/// answer option after its marker. Later lines are indented to line up with the field.
+pub fn highlight_labelled(label: &str, text: &str, language: &str, width: u16) -> Text<'static> {
+    let indent = label.chars().count();
+    let field_width = width.saturating_sub(indent as u16);
+    let mut lines = highlight_text(text, language, field_width).lines;
+    for (index, line) in lines.iter_mut().enumerate() {
+        let lead = if index == 0 {
+            label.to_string()
+        } else {
+            " ".repeat(indent)
+        };
+        line.spans.insert(0, Span::raw(lead));
+    }
+    Text::from(lines)
+}
+
+/// Plain text wrapped to `width` columns the same way as the prose in [`highlight_text`].
+pub fn plain_text(text: &str, width: u16) -> Text<'static> {
+    let width = usize::from(width);
+    Text::from(
+        text.split('\n')
+            .flat_map(|raw| wrap_prose(Line::from(raw.to_string()), width))
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn syntax_for(tag: &str, language: &str) -> &'static SyntaxReference {
+    let syntaxes = &highlighter().syntaxes;
+    [tag, language]
+        .into_iter()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .find_map(|name| {
+            syntaxes
+                .find_syntax_by_token(name)
+                .or_else(|| syntaxes.find_syntax_by_token(&name.to_lowercase()))
+        })
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text())
+}
+
+/// A line of prose with each backtick-quoted span highlighted as code.
+fn prose_line(text: &str, language: &str) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('`') {
+        let ticks = rest[start..].chars().take_while(|&ch| ch == '`').count();
+        let delimiter = &rest[start..start + ticks];
+        let code_start = start + ticks;
+        let Some(length) = rest[code_start..].find(delimiter) else {
+            break;
+        };
+        if start > 0 {
+            spans.push(Span::raw(rest[..start].to_string()));
+        }
+        let mut snippet = HighlightLines::new(syntax_for(language, ""), &highlighter().theme);
+        spans.extend(
+            highlight_line(&mut snippet, &rest[code_start..code_start + length])
+                .into_iter()
+                .map(|(style, text)| Span::styled(text, style)),
+        );
+        rest = &rest[code_start + length + ticks..];
+    }
+    if !rest.is_empty() {
+        spans.push(Span::raw(rest.to_string()));
+    }
+    Line::from(spans)
+}
+
+/// Wrap a line of prose at spaces, splitting words longer than a whole line.
+fn wrap_prose(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let length: usize = line
+        .spans
+        .iter()
+        .map(|span| span.content.chars().count())
+        .sum();
+    if width == 0 || length <= width {
+        return vec![line];
+    }
+
+    let mut lines = Vec::new();
+    let mut current: Vec<Span<'static>> = Vec::new();
+    let mut used = 0;
+    for span in line.spans {
+        for word in span.content.split_inclusive(' ') {
+            if used > 0 && used + word.trim_end().chars().count() > width {
+                lines.push(Line::from(std::mem::take(&mut current)));
+                used = 0;
+            }
+            // Wrapped lines start at the next word rather than the space before it.
+            let mut word: Vec<char> = if used == 0 && !lines.is_empty() {
+                word.trim_start().chars().collect()
+            } else {
+                word.chars().collect()
+            };
+            while used == 0 && word.len() > width {
+                let rest = word.split_off(width);
+                current.push(Span::styled(
+                    word.into_iter().collect::<String>(),
+                    span.style,
+                ));
+                lines.push(Line::from(std::mem::take(&mut current)));
+                word = rest;
+            }
+            if !word.is_empty() {
+                used += word.len();
+                current.push(Span::styled(
+                    word.into_iter().collect::<String>(),
+                    span.style,
+                ));
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push(Line::from(current));
+    }
+    lines
+}
+
+/// Highlight one line of a fenced block, wrapping it to `width` columns. Wrapped parts start
+/// at the line's indentation, capped at half the width so deep nesting still shows code.
+fn code_lines(block: &mut HighlightLines, raw: &str, width: usize) -> Vec<Line<'static>> {
+    let expanded = raw.replace('\t', &" ".repeat(TAB_WIDTH));
+    let pieces = highlight_line(block, &expanded);
+    let length = expanded.chars().count();
+    if width == 0 || length <= width {
+        return vec![Line::from(
+            pieces
+                .into_iter()
+                .map(|(style, text)| Span::styled(text, style))
+                .collect::<Vec<_>>(),
+        )];
+    }
+
+    let indent = expanded.chars().take_while(|ch| *ch == ' ').count();
+    let continuation = indent.min(width / 2);
+    let indent_style = pieces.first().map(|(style, _)| *style).unwrap_or_default();
+    let mut lines = Vec::new();
+    let mut current: Vec<Span<'static>> = Vec::new();
+    let mut used = 0;
+    for (style, text) in pieces {
+        let mut buffer = String::new();
+        for ch in text.chars() {
+            if used == width {
+                if !buffer.is_empty() {
+                    current.push(Span::styled(std::mem::take(&mut buffer), style));
+                }
+                lines.push(Line::from(std::mem::take(&mut current)));
+                current.push(Span::styled(" ".repeat(continuation), indent_style));
+                used = continuation;
+            }
+            buffer.push(ch);
+            used += 1;
+        }
+        if !buffer.is_empty() {
+            current.push(Span::styled(buffer, style));
+        }
+    }
+    if !current.is_empty() {
+        lines.push(Line::from(current));
+    }
+    lines
+}
+
+/// Styled pieces of one line of code. Syntaxes expect a trailing newline, which is dropped
+/// from the result again.
+fn highlight_line(highlighter_state: &mut HighlightLines, line: &str) -> Vec<(Style, String)> {
+    let with_newline = format!("{}\n", line);
+    match highlighter_state.highlight_line(&with_newline, &highlighter().syntaxes) {
+        Ok(ranges) => ranges
+            .into_iter()
+            .map(|(style, text)| (to_ratatui(style), text.trim_end_matches('\n').to_string()))
+            .filter(|(_, text)| !text.is_empty())
+            .collect(),
+        Err(_) => vec![(Style::default(), line.to_string())],
+    }
+}
+
+fn to_ratatui(style: highlighting::Style) -> Style {
+    let rgb = |color: highlighting::Color| Color::Rgb(color.r, color.g, color.b);
+    Style::default()
+        .fg(rgb(style.foreground))
+        .bg(rgb(style.background))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(line: &Line) -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn fenced_and_inline_code_are_highlighted_and_wrapped_at_their_indentation() {
+        let text = "Does `let x = 5;` move?\n\
+            Summary: ```rust\n\
+            fn main() {\n    println!(\"{}\", \"a long string that will not fit\");\n}\n\
+            ```\n\
+            Done";
+        let highlighted = highlight_text(text, "Rust", 24);
+        let lines: Vec<String> = highlighted.lines.iter().map(plain).collect();
+
+        assert_eq!(lines[0], "Does let x = 5; move?");
+        assert_eq!(lines[1], "Summary: ");
+        assert_eq!(lines[2], "fn main() {");
+        assert_eq!(lines[3], "    println!(\"{}\", \"a lo");
+        assert_eq!(lines[4], "    ng string that will ");
+        assert_eq!(lines[5], "    not fit\");");
+        assert_eq!(lines[6], "}");
+        assert_eq!(lines[7], "Done");
+        assert_eq!(lines.len(), 8);
+
+        assert_eq!(highlighted.lines[0].spans[0].style, Style::default());
+        let inline_code = &highlighted.lines[0].spans[1..highlighted.lines[0].spans.len() - 1];
+        assert!(inline_code.len() > 1, "inline code is split into tokens");
+        assert!(inline_code.iter().all(|span| span.style.fg.is_some()));
+        assert!(highlighted.lines[2].spans.len() > 1);
+    }
+
+    #[test]
+    fn prose_wraps_at_spaces_and_an_open_fence_ends_with_its_field() {
+        let wrapped: Vec<String> = plain_text("Pick the option that compiles\nabcdefghijklm", 12)
+            .lines
+            .iter()
+            .map(plain)
+            .collect();
+        assert_eq!(
+            wrapped,
+            vec!["Pick the ", "option that ", "compiles", "abcdefghijkl", "m"]
+        );
+
+        let mut panel = highlight_text("Fix it:\n```rust\nlet x = 1;", "Rust", 40);
+        panel.extend(plain_text("A. let x = 2;", 40));
+        let lines: Vec<String> = panel.lines.iter().map(plain).collect();
+        assert_eq!(lines, vec!["Fix it:", "let x = 1;", "A. let x = 2;"]);
+        assert!(panel.lines[1].spans[0].style.bg.is_some());
+        assert_eq!(panel.lines[2].spans[0].style, Style::default());
+    }
+
+    #[test]
+    fn unknown_or_missing_languages_fall_back_to_plain_code() {
+        let highlighted = highlight_text("```\n\tindented\n", "", 80);
+        let lines: Vec<String> = highlighted.lines.iter().map(plain).collect();
+        assert_eq!(lines, vec!["    indented", ""]);
+        assert!(highlighted.lines[0].spans[0].style.bg.is_some());
+    }
+}
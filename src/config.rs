@@ -1,6 +1,7 @@
 use color_eyre::eyre::{Context, Result, eyre};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     fs, io,
     path::PathBuf,
     sync::{OnceLock, RwLock},
@@ -21,6 +22,27 @@ pub struct AppConfig {
     pub openai_model: OpenAiModelKind,
     #[serde(default)]
     pub openai_api_key: String,
+    /// Monthly spending cap in USD for OpenAI requests; zero disables the cap.
+    #[serde(default)]
+    pub monthly_budget_usd: f64,
+    /// Self-reported proficiency per language, used to pitch question difficulty.
+    #[serde(default)]
+    pub language_proficiency: BTreeMap<String, ProficiencyLevel>,
+    /// Languages the learner wants questions to concentrate on.
+    #[serde(default)]
+    pub focus_languages: Vec<String>,
+    /// Languages that should never be quizzed.
+    #[serde(default)]
+    pub ignored_languages: Vec<String>,
+    /// Topics the learner already knows or does not care about.
+    #[serde(default)]
+    pub skip_topics: Vec<String>,
+    /// Directory of published lessons shared with a team, e.g. a git-synced checkout.
+    #[serde(default)]
+    pub shared_bank_path: String,
+    /// Name recorded on questions published to the shared bank.
+    #[serde(default)]
+    pub author_name: String,
 }
 
 impl AppConfig {
@@ -31,11 +53,42 @@ impl AppConfig {
         if self.min_quiz_questions == 0 {
             self.min_quiz_questions = DEFAULT_MIN_QUIZ_QUESTIONS;
         }
+        if !self.monthly_budget_usd.is_finite() || self.monthly_budget_usd < 0.0 {
+            self.monthly_budget_usd = 0.0;
+        }
+        self.language_proficiency = std::mem::take(&mut self.language_proficiency)
+            .into_iter()
+            .map(|(language, level)| (language.trim().to_string(), level))
+            .filter(|(language, _)| !language.is_empty())
+            .collect();
+        normalize_list(&mut self.focus_languages);
+        normalize_list(&mut self.ignored_languages);
+        normalize_list(&mut self.skip_topics);
+        self.shared_bank_path = self.shared_bank_path.trim().to_string();
+        self.author_name = self.author_name.trim().to_string();
+        // Ignoring a language wins over focusing on it.
+        let ignored = self.ignored_languages.clone();
+        self.focus_languages
+            .retain(|language| !contains_ignore_case(&ignored, language));
     }
+}
 
-    pub fn system_prompt(&self) -> String {
-        SYSTEM_PROMPT_TEMPLATE.replace("{MIN_QUIZ_QUESTIONS}", &self.min_quiz_questions.to_string())
+/// Trim entries, drop empty ones, and remove case-insensitive duplicates while keeping order.
+fn normalize_list(values: &mut Vec<String>) {
+    let mut normalized: Vec<String> = Vec::with_capacity(values.len());
+    for value in values.drain(..) {
+        let trimmed = value.trim();
+        if !trimmed.is_empty() && !contains_ignore_case(&normalized, trimmed) {
+            normalized.push(trimmed.to_string());
+        }
     }
+    *values = normalized;
+}
+
+fn contains_ignore_case(values: &[String], needle: &str) -> bool {
+    values
+        .iter()
+        .any(|value| value.eq_ignore_ascii_case(needle))
 }
 
 impl Default for AppConfig {
@@ -47,6 +100,13 @@ impl Default for AppConfig {
             write_output_artifacts: default_write_output_artifacts_value(),
             openai_model: default_openai_model_kind(),
             openai_api_key: String::new(),
+            monthly_budget_usd: 0.0,
+            language_proficiency: BTreeMap::new(),
+            focus_languages: Vec::new(),
+            ignored_languages: Vec::new(),
+            skip_topics: Vec::new(),
+            shared_bank_path: String::new(),
+            author_name: String::new(),
         }
     }
 }
@@ -62,40 +122,23 @@ const fn default_write_output_artifacts_value() -> bool {
 const fn default_openai_model_kind() -> OpenAiModelKind {
     OpenAiModelKind::Gpt5Mini
 }
-const SYSTEM_PROMPT_TEMPLATE: &str = r#"You are a precise curriculum planner that helps the student learn about coding concepts.
-You will produce a quiz that will teach the user about a coding concept based on the provided context.
-You should base each quiz item on the provided context to help the student learn new language features or concepts.
-All context examples include bash scripts. The contents of the bash updates are what should be considered for quiz updates.
-Example full bash script json:
-```
-{'command':['bash','-lc','apply_patch <<'PATCH'
-*** Begin Patch
-*** Update File: src/ai_manager.rs
-@@
--    #[serde(default)]
--    pub knowledge_type_language: Option<String>,
-+    #[serde(default)]
-+    pub knowledge_type_language: String,
- }
-*** End Patch
-PATCH
-'],'workdir':'/Users/davidnorman/learnchain'}
-```
-Example subset of what should actually be considered for learning content:
-```
-Update File: src/ai_manager.rs
-@@
--    #[serde(default)]
--    pub knowledge_type_language: Option<String>,
-+    #[serde(default)]
-+    pub knowledge_type_language: String,
- }
-```
-All questions should be language specific and should not quiz based on implementation of the specific program.
-You should return a minimum of {MIN_QUIZ_QUESTIONS} quiz questions.
-Return JSON that strictly matches the provided schema."#;
-
 const CONFIG_FILE_PATH: &str = "config/app_config.toml";
+/// Languages offered in the Config view's learner profile rows; configured extras are appended.
+const KNOWN_LANGUAGES: [&str; 13] = [
+    "Rust",
+    "Python",
+    "TypeScript",
+    "JavaScript",
+    "Go",
+    "Java",
+    "C#",
+    "C++",
+    "Swift",
+    "Kotlin",
+    "Ruby",
+    "Shell",
+    "SQL",
+];
 
 static APP_CONFIG: OnceLock<RwLock<AppConfig>> = OnceLock::new();
 
@@ -133,14 +176,6 @@ pub fn default_max_events() -> usize {
         .default_max_events
 }
 
-/// Convenience accessor for the configured system prompt.
-pub fn system_prompt() -> String {
-    config_lock()
-        .read()
-        .expect("config lock poisoned")
-        .system_prompt()
-}
-
 /// Apply the provided mutation to the in-memory configuration and persist the result to disk.
 pub fn update<F>(mutator: F) -> Result<AppConfig>
 where
@@ -231,6 +266,72 @@ impl SessionSourceKind {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProficiencyLevel {
+    Beginner,
+    Intermediate,
+    Advanced,
+}
+
+impl ProficiencyLevel {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Beginner => "beginner",
+            Self::Intermediate => "intermediate",
+            Self::Advanced => "advanced",
+        }
+    }
+
+    /// Cycle through the levels with an extra "not set" stop between advanced and beginner.
+    fn cycle(level: Option<Self>, forward: bool) -> Option<Self> {
+        match (level, forward) {
+            (None, true) => Some(Self::Beginner),
+            (Some(Self::Beginner), true) => Some(Self::Intermediate),
+            (Some(Self::Intermediate), true) => Some(Self::Advanced),
+            (Some(Self::Advanced), true) => None,
+            (None, false) => Some(Self::Advanced),
+            (Some(Self::Advanced), false) => Some(Self::Intermediate),
+            (Some(Self::Intermediate), false) => Some(Self::Beginner),
+            (Some(Self::Beginner), false) => None,
+        }
+    }
+}
+
+/// Whether a language is prioritised, excluded, or left to the session content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LanguageFocus {
+    Neutral,
+    Focus,
+    Ignore,
+}
+
+impl LanguageFocus {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Neutral => "neutral",
+            Self::Focus => "focus",
+            Self::Ignore => "ignore",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Neutral => Self::Focus,
+            Self::Focus => Self::Ignore,
+            Self::Ignore => Self::Neutral,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            Self::Neutral => Self::Ignore,
+            Self::Focus => Self::Neutral,
+            Self::Ignore => Self::Focus,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ConfigField {
     MaxEvents,
@@ -239,6 +340,13 @@ enum ConfigField {
     OutputArtifacts,
     OpenAiModel,
     OpenAiKey,
+    MonthlyBudget,
+    ProfileLanguage,
+    LanguageLevel,
+    LanguageFocus,
+    SkipTopics,
+    SharedBankPath,
+    AuthorName,
 }
 
 #[derive(Debug, Clone)]
@@ -249,8 +357,17 @@ pub struct ConfigForm {
     pub(crate) write_output_artifacts: bool,
     pub(crate) openai_model: OpenAiModelKind,
     pub(crate) openai_api_key: String,
-    editing_openai_key: bool,
-    openai_key_buffer: String,
+    pub(crate) monthly_budget_usd: f64,
+    pub(crate) language_proficiency: BTreeMap<String, ProficiencyLevel>,
+    pub(crate) focus_languages: Vec<String>,
+    pub(crate) ignored_languages: Vec<String>,
+    pub(crate) skip_topics: Vec<String>,
+    pub(crate) shared_bank_path: String,
+    pub(crate) author_name: String,
+    profile_languages: Vec<String>,
+    profile_language_index: usize,
+    editing_field: Option<ConfigField>,
+    edit_buffer: String,
     field: ConfigField,
     pub(crate) dirty: bool,
     pub(crate) status: Option<String>,
@@ -258,6 +375,7 @@ pub struct ConfigForm {
 
 impl ConfigForm {
     pub(crate) fn from_config(config: AppConfig) -> Self {
+        let profile_languages = profile_languages(&config);
         Self {
             max_events: config.default_max_events,
             min_quiz_questions: config.min_quiz_questions,
@@ -265,8 +383,17 @@ impl ConfigForm {
             write_output_artifacts: config.write_output_artifacts,
             openai_model: config.openai_model,
             openai_api_key: config.openai_api_key,
-            editing_openai_key: false,
-            openai_key_buffer: String::new(),
+            monthly_budget_usd: config.monthly_budget_usd,
+            profile_languages,
+            language_proficiency: config.language_proficiency,
+            focus_languages: config.focus_languages,
+            ignored_languages: config.ignored_languages,
+            skip_topics: config.skip_topics,
+            shared_bank_path: config.shared_bank_path,
+            author_name: config.author_name,
+            profile_language_index: 0,
+            editing_field: None,
+            edit_buffer: String::new(),
             field: ConfigField::MaxEvents,
             dirty: false,
             status: None,
@@ -328,7 +455,54 @@ impl ConfigForm {
             return;
         }
 
-        if matches!(self.field, ConfigField::OpenAiKey) {
+        if self.is_text_field_selected() {
+            return;
+        }
+
+        if matches!(self.field, ConfigField::ProfileLanguage) {
+            let count = self.profile_languages.len() as isize;
+            if count > 0 {
+                self.profile_language_index = (self.profile_language_index as isize
+                    + delta.signum())
+                .rem_euclid(count) as usize;
+            }
+            return;
+        }
+
+        if matches!(self.field, ConfigField::LanguageLevel) {
+            let language = self.selected_language().to_string();
+            let updated = ProficiencyLevel::cycle(self.proficiency_for(&language), delta > 0);
+            self.language_proficiency
+                .retain(|name, _| !name.eq_ignore_ascii_case(&language));
+            if let Some(level) = updated {
+                self.language_proficiency.insert(language, level);
+            }
+            self.dirty = true;
+            self.status = None;
+            return;
+        }
+
+        if matches!(self.field, ConfigField::LanguageFocus) {
+            let language = self.selected_language().to_string();
+            let current = self.focus_for(&language);
+            let updated = if delta > 0 {
+                current.next()
+            } else {
+                current.previous()
+            };
+            self.set_focus(&language, updated);
+            self.dirty = true;
+            self.status = None;
+            return;
+        }
+
+        if matches!(self.field, ConfigField::MonthlyBudget) {
+            let updated = (self.monthly_budget_usd + delta as f64).max(0.0);
+            if updated != self.monthly_budget_usd {
+                self.monthly_budget_usd = updated;
+                self.dirty = true;
+                self.status = None;
+            }
             return;
         }
 
@@ -338,7 +512,14 @@ impl ConfigForm {
             ConfigField::SessionSource
             | ConfigField::OutputArtifacts
             | ConfigField::OpenAiModel
-            | ConfigField::OpenAiKey => {
+            | ConfigField::OpenAiKey
+            | ConfigField::MonthlyBudget
+            | ConfigField::ProfileLanguage
+            | ConfigField::LanguageLevel
+            | ConfigField::LanguageFocus
+            | ConfigField::SkipTopics
+            | ConfigField::SharedBankPath
+            | ConfigField::AuthorName => {
                 unreachable!()
             }
         };
@@ -361,8 +542,15 @@ impl ConfigForm {
         self.write_output_artifacts = config.write_output_artifacts;
         self.openai_model = config.openai_model;
         self.openai_api_key = config.openai_api_key;
-        self.editing_openai_key = false;
-        self.openai_key_buffer.clear();
+        self.monthly_budget_usd = config.monthly_budget_usd;
+        self.language_proficiency = config.language_proficiency;
+        self.focus_languages = config.focus_languages;
+        self.ignored_languages = config.ignored_languages;
+        self.skip_topics = config.skip_topics;
+        self.shared_bank_path = config.shared_bank_path;
+        self.author_name = config.author_name;
+        self.editing_field = None;
+        self.edit_buffer.clear();
         self.dirty = false;
         self.status = None;
     }
@@ -371,47 +559,156 @@ impl ConfigForm {
         self.status = Some(status.into());
     }
 
-    pub(crate) fn is_openai_key_selected(&self) -> bool {
-        matches!(self.field, ConfigField::OpenAiKey)
+    /// Whether the selected row is edited by typing rather than with ←/→.
+    pub(crate) fn is_text_field_selected(&self) -> bool {
+        matches!(
+            self.field,
+            ConfigField::OpenAiKey
+                | ConfigField::SkipTopics
+                | ConfigField::SharedBankPath
+                | ConfigField::AuthorName
+        )
+    }
+
+    pub(crate) fn is_editing_text(&self) -> bool {
+        self.editing_field.is_some()
     }
 
     pub(crate) fn is_editing_openai_key(&self) -> bool {
-        self.editing_openai_key
+        self.editing_field == Some(ConfigField::OpenAiKey)
     }
 
-    pub(crate) fn start_editing_openai_key(&mut self) {
-        self.editing_openai_key = true;
-        self.openai_key_buffer = self.openai_api_key.clone();
-        self.status = Some("Editing OpenAI API key (Enter to save, Esc to cancel)".to_string());
+    pub(crate) fn is_editing_skip_topics(&self) -> bool {
+        self.editing_field == Some(ConfigField::SkipTopics)
     }
 
-    pub(crate) fn cancel_openai_key_edit(&mut self) {
-        self.editing_openai_key = false;
-        self.openai_key_buffer.clear();
-        self.status = Some("Cancelled OpenAI API key edit.".to_string());
+    pub(crate) fn is_editing_shared_bank_path(&self) -> bool {
+        self.editing_field == Some(ConfigField::SharedBankPath)
     }
 
-    pub(crate) fn apply_openai_key_edit(&mut self) {
-        let new_value = self.openai_key_buffer.trim().to_string();
-        if new_value != self.openai_api_key {
-            self.openai_api_key = new_value;
+    pub(crate) fn is_editing_author_name(&self) -> bool {
+        self.editing_field == Some(ConfigField::AuthorName)
+    }
+
+    pub(crate) fn start_text_edit(&mut self) {
+        let (buffer, label) = match self.field {
+            ConfigField::OpenAiKey => (self.openai_api_key.clone(), "OpenAI API key"),
+            ConfigField::SkipTopics => {
+                (self.skip_topics.join(", "), "skip topics (comma separated)")
+            }
+            ConfigField::SharedBankPath => (self.shared_bank_path.clone(), "shared bank path"),
+            ConfigField::AuthorName => (self.author_name.clone(), "author name"),
+            _ => return,
+        };
+        self.editing_field = Some(self.field);
+        self.edit_buffer = buffer;
+        self.status = Some(format!("Editing {} (Enter to save, Esc to cancel)", label));
+    }
+
+    pub(crate) fn cancel_text_edit(&mut self) {
+        if let Some(field) = self.editing_field.take() {
+            self.status = Some(format!("Cancelled {} edit.", field.text_label()));
+        }
+        self.edit_buffer.clear();
+    }
+
+    pub(crate) fn apply_text_edit(&mut self) {
+        let Some(field) = self.editing_field.take() else {
+            return;
+        };
+        let changed = match field {
+            ConfigField::OpenAiKey => {
+                let new_value = self.edit_buffer.trim().to_string();
+                let changed = new_value != self.openai_api_key;
+                self.openai_api_key = new_value;
+                changed
+            }
+            ConfigField::SkipTopics => {
+                let mut topics: Vec<String> =
+                    self.edit_buffer.split(',').map(str::to_string).collect();
+                normalize_list(&mut topics);
+                let changed = topics != self.skip_topics;
+                self.skip_topics = topics;
+                changed
+            }
+            ConfigField::SharedBankPath => {
+                let new_value = self.edit_buffer.trim().to_string();
+                let changed = new_value != self.shared_bank_path;
+                self.shared_bank_path = new_value;
+                changed
+            }
+            ConfigField::AuthorName => {
+                let new_value = self.edit_buffer.trim().to_string();
+                let changed = new_value != self.author_name;
+                self.author_name = new_value;
+                changed
+            }
+            _ => false,
+        };
+        if changed {
             self.dirty = true;
-            self.status = Some("Updated OpenAI API key.".to_string());
+            self.status = Some(format!("Updated {}.", field.text_label()));
         } else {
-            self.status = Some("OpenAI API key unchanged.".to_string());
+            self.status = Some(format!("{} unchanged.", field.text_label()));
         }
-        self.editing_openai_key = false;
-        self.openai_key_buffer.clear();
+        self.edit_buffer.clear();
     }
 
-    pub(crate) fn backspace_openai_key(&mut self) {
-        self.openai_key_buffer.pop();
-        self.status = Some("Editing OpenAI API key...".to_string());
+    pub(crate) fn backspace_text(&mut self) {
+        self.edit_buffer.pop();
+        self.set_editing_status();
     }
 
-    pub(crate) fn push_openai_key_char(&mut self, ch: char) {
-        self.openai_key_buffer.push(ch);
-        self.status = Some("Editing OpenAI API key...".to_string());
+    pub(crate) fn push_text_char(&mut self, ch: char) {
+        self.edit_buffer.push(ch);
+        self.set_editing_status();
+    }
+
+    fn set_editing_status(&mut self) {
+        if let Some(field) = self.editing_field {
+            self.status = Some(format!("Editing {}...", field.text_label()));
+        }
+    }
+
+    pub(crate) fn edit_buffer(&self) -> &str {
+        &self.edit_buffer
+    }
+
+    /// Language whose proficiency and focus rows are currently shown.
+    pub(crate) fn selected_language(&self) -> &str {
+        self.profile_languages
+            .get(self.profile_language_index)
+            .map(String::as_str)
+            .unwrap_or("")
+    }
+
+    pub(crate) fn proficiency_for(&self, language: &str) -> Option<ProficiencyLevel> {
+        self.language_proficiency
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(language))
+            .map(|(_, level)| *level)
+    }
+
+    pub(crate) fn focus_for(&self, language: &str) -> LanguageFocus {
+        if contains_ignore_case(&self.ignored_languages, language) {
+            LanguageFocus::Ignore
+        } else if contains_ignore_case(&self.focus_languages, language) {
+            LanguageFocus::Focus
+        } else {
+            LanguageFocus::Neutral
+        }
+    }
+
+    fn set_focus(&mut self, language: &str, focus: LanguageFocus) {
+        self.focus_languages
+            .retain(|name| !name.eq_ignore_ascii_case(language));
+        self.ignored_languages
+            .retain(|name| !name.eq_ignore_ascii_case(language));
+        match focus {
+            LanguageFocus::Neutral => {}
+            LanguageFocus::Focus => self.focus_languages.push(language.to_string()),
+            LanguageFocus::Ignore => self.ignored_languages.push(language.to_string()),
+        }
     }
 
     pub(crate) fn masked_openai_key(&self) -> String {
@@ -419,10 +716,29 @@ impl ConfigForm {
     }
 
     pub(crate) fn masked_openai_key_buffer(&self) -> String {
-        mask_secret(&self.openai_key_buffer)
+        mask_secret(&self.edit_buffer)
     }
 }
 
+/// Known languages followed by any extra languages mentioned in the saved profile.
+fn profile_languages(config: &AppConfig) -> Vec<String> {
+    let mut languages: Vec<String> = KNOWN_LANGUAGES
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    let configured = config
+        .language_proficiency
+        .keys()
+        .chain(&config.focus_languages)
+        .chain(&config.ignored_languages);
+    for language in configured {
+        if !contains_ignore_case(&languages, language) {
+            languages.push(language.clone());
+        }
+    }
+    languages
+}
+
 fn mask_secret(value: &str) -> String {
     if value.is_empty() {
         return "<not set>".to_string();
@@ -452,6 +768,23 @@ impl ConfigField {
             Self::OutputArtifacts => 3,
             Self::OpenAiModel => 4,
             Self::OpenAiKey => 5,
+            Self::MonthlyBudget => 6,
+            Self::ProfileLanguage => 7,
+            Self::LanguageLevel => 8,
+            Self::LanguageFocus => 9,
+            Self::SkipTopics => 10,
+            Self::SharedBankPath => 11,
+            Self::AuthorName => 12,
+        }
+    }
+
+    fn text_label(self) -> &'static str {
+        match self {
+            Self::OpenAiKey => "OpenAI API key",
+            Self::SkipTopics => "skip topics",
+            Self::SharedBankPath => "shared bank path",
+            Self::AuthorName => "author name",
+            _ => "value",
         }
     }
 
@@ -462,18 +795,32 @@ impl ConfigField {
             Self::SessionSource => Self::OutputArtifacts,
             Self::OutputArtifacts => Self::OpenAiModel,
             Self::OpenAiModel => Self::OpenAiKey,
-            Self::OpenAiKey => Self::MaxEvents,
+            Self::OpenAiKey => Self::MonthlyBudget,
+            Self::MonthlyBudget => Self::ProfileLanguage,
+            Self::ProfileLanguage => Self::LanguageLevel,
+            Self::LanguageLevel => Self::LanguageFocus,
+            Self::LanguageFocus => Self::SkipTopics,
+            Self::SkipTopics => Self::SharedBankPath,
+            Self::SharedBankPath => Self::AuthorName,
+            Self::AuthorName => Self::MaxEvents,
         }
     }
 
     fn previous(self) -> Self {
         match self {
-            Self::MaxEvents => Self::OpenAiKey,
+            Self::MaxEvents => Self::AuthorName,
             Self::MinQuiz => Self::MaxEvents,
             Self::SessionSource => Self::MinQuiz,
             Self::OutputArtifacts => Self::SessionSource,
             Self::OpenAiModel => Self::OutputArtifacts,
             Self::OpenAiKey => Self::OpenAiModel,
+            Self::MonthlyBudget => Self::OpenAiKey,
+            Self::ProfileLanguage => Self::MonthlyBudget,
+            Self::LanguageLevel => Self::ProfileLanguage,
+            Self::LanguageFocus => Self::LanguageLevel,
+            Self::SkipTopics => Self::LanguageFocus,
+            Self::SharedBankPath => Self::SkipTopics,
+            Self::AuthorName => Self::SharedBankPath,
         }
     }
 }
@@ -497,6 +844,22 @@ impl OpenAiModelKind {
         self.as_model_name()
     }
 
+    pub fn from_model_name(name: &str) -> Option<Self> {
+        match name {
+            "gpt-5-mini" => Some(Self::Gpt5Mini),
+            "gpt-5" => Some(Self::Gpt5),
+            _ => None,
+        }
+    }
+
+    /// Published (input, output) prices in USD per million tokens.
+    pub fn price_per_million_tokens(self) -> (f64, f64) {
+        match self {
+            Self::Gpt5Mini => (0.25, 2.0),
+            Self::Gpt5 => (1.25, 10.0),
+        }
+    }
+
     pub fn next(self) -> Self {
         match self {
             Self::Gpt5Mini => Self::Gpt5,
@@ -3,15 +3,132 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use chrono::{Duration, NaiveDate, Utc};
+use chrono::{Datelike, Duration, NaiveDate, Utc};
 use color_eyre::eyre::{Context, Result, eyre};
-use rusqlite::{Connection, params};
-use std::collections::{BTreeSet, HashMap};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
-use crate::{ai_manager::StructuredLearningResponse, output_manager::OutputManager};
+use crate::{
+    ai_manager::{
+        ChatMessage, ChatRole, GenerationUsage, KnowledgeResponse, QuizDifficulty, QuizItem,
+        StructuredLearningResponse,
+    },
+    output_manager::OutputManager,
+    question_history::{self, normalize_text, similarity},
+};
 
 const DATABASE_FILENAME: &str = "learning_history.sqlite";
 
+/// Days after which an attempt counts half as much towards mastery as one made today.
+const MASTERY_HALF_LIFE_DAYS: f64 = 14.0;
+/// Weight of the neutral 50% prior, so a single attempt cannot produce full or zero mastery.
+const MASTERY_PRIOR_WEIGHT: f64 = 1.0;
+
+/// Name similarity at or above which a new knowledge group joins an existing topic.
+const TOPIC_MATCH_SIMILARITY: f64 = 0.6;
+
+/// `quiz_attempts` filter that leaves out questions the learner flagged as incorrect.
+const NOT_FLAGGED: &str = "(question_id IS NULL OR question_id NOT IN (SELECT question_id FROM question_marks WHERE kind = 'flag'))";
+
+/// Schema changes applied in order on top of the original tables. The number of applied
+/// migrations is tracked with `PRAGMA user_version`, so entries must never be reordered.
+const MIGRATIONS: &[&str] = &[
+    "ALTER TABLE quiz_attempts ADD COLUMN answer_text TEXT;
+     ALTER TABLE quiz_attempts ADD COLUMN score INTEGER;",
+    "ALTER TABLE quiz_attempts ADD COLUMN difficulty TEXT;",
+    "CREATE TABLE answer_attempts (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_date TEXT NOT NULL,
+        recorded_at TEXT NOT NULL,
+        knowledge_type_group TEXT NOT NULL,
+        knowledge_type_language TEXT,
+        question TEXT NOT NULL,
+        attempt_number INTEGER NOT NULL,
+        selected_answer TEXT,
+        correct INTEGER NOT NULL,
+        elapsed_ms INTEGER,
+        score INTEGER,
+        difficulty TEXT,
+        UNIQUE(session_date, knowledge_type_group, question, attempt_number)
+     );
+     INSERT INTO answer_attempts (
+        session_date, recorded_at, knowledge_type_group, knowledge_type_language, question,
+        attempt_number, selected_answer, correct, score, difficulty
+     )
+     SELECT session_date, recorded_at, knowledge_type_group, knowledge_type_language, question,
+        1, answer_text, first_try_correct, score, difficulty
+     FROM quiz_attempts;
+     DROP TABLE quiz_attempts;
+     CREATE INDEX idx_answer_attempts_session_date ON answer_attempts(session_date);
+     CREATE VIEW quiz_attempts AS
+        SELECT id, session_date, recorded_at, knowledge_type_group, knowledge_type_language,
+            question, correct AS first_try_correct, selected_answer AS answer_text, score,
+            difficulty
+        FROM answer_attempts
+        WHERE attempt_number = 1;",
+    "CREATE TABLE questions (
+        id TEXT PRIMARY KEY,
+        first_seen_at TEXT NOT NULL,
+        knowledge_type_group TEXT NOT NULL,
+        knowledge_type_language TEXT NOT NULL,
+        question TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        difficulty TEXT NOT NULL,
+        rubric TEXT NOT NULL
+     );
+     CREATE TABLE options (
+        question_id TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        selection TEXT NOT NULL,
+        is_correct_answer INTEGER NOT NULL,
+        explanation TEXT NOT NULL,
+        PRIMARY KEY(question_id, position)
+     );
+     ALTER TABLE answer_attempts ADD COLUMN question_id TEXT;
+     CREATE INDEX idx_answer_attempts_question_id ON answer_attempts(question_id);
+     DROP VIEW quiz_attempts;
+     CREATE VIEW quiz_attempts AS
+        SELECT id, session_date, recorded_at, knowledge_type_group, knowledge_type_language,
+            question, question_id, correct AS first_try_correct,
+            selected_answer AS answer_text, score, difficulty
+        FROM answer_attempts
+        WHERE attempt_number = 1;",
+    "CREATE TABLE question_marks (
+        question_id TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        marked_at TEXT NOT NULL,
+        knowledge_type_group TEXT NOT NULL,
+        knowledge_type_language TEXT NOT NULL,
+        summary TEXT NOT NULL,
+        question_json TEXT NOT NULL,
+        note TEXT NOT NULL,
+        PRIMARY KEY(question_id, kind)
+     );",
+    "CREATE TABLE challenge_runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_date TEXT NOT NULL,
+        started_at TEXT NOT NULL,
+        finished_at TEXT NOT NULL,
+        knowledge_groups TEXT NOT NULL,
+        question_count INTEGER NOT NULL,
+        correct_count INTEGER NOT NULL,
+        timed_out_count INTEGER NOT NULL,
+        score INTEGER NOT NULL,
+        seconds_per_question INTEGER NOT NULL,
+        duration_ms INTEGER NOT NULL
+     );
+     CREATE INDEX idx_challenge_runs_score ON challenge_runs(score DESC);",
+];
+
+/// Migration that replaced the `quiz_attempts` table with the `answer_attempts` log and a
+/// first-try view of it under the old name.
+const ANSWER_LOG_MIGRATION: i64 = 3;
+/// Migration that added the `questions` and `options` tables. Stored quizzes and attempts are
+/// backfilled in Rust once it has run, since question ids are content hashes.
+const QUESTIONS_MIGRATION: i64 = 4;
+
 #[derive(Debug, Clone, Default)]
 pub struct DailyAnalytics {
     pub date: NaiveDate,
@@ -28,6 +145,219 @@ pub struct KnowledgeAnalytics {
     pub total_first_try_correct: u32,
     pub total_attempts: u32,
     pub knowledge_groups: Vec<String>,
+    pub usage_today: UsageTotals,
+    pub usage_this_month: UsageTotals,
+    pub mastery: MasteryReport,
+}
+
+/// Recency-weighted mastery of one knowledge group in one language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MasteryEntry {
+    pub knowledge_type_group: String,
+    /// Empty when the attempts did not record a language.
+    pub knowledge_type_language: String,
+    /// Between 0.0 and 1.0.
+    pub score: f64,
+    pub attempts: usize,
+    pub last_attempt: Option<NaiveDate>,
+}
+
+/// Mastery per knowledge group and language, strongest first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MasteryReport {
+    pub entries: Vec<MasteryEntry>,
+}
+
+impl MasteryReport {
+    pub fn strongest(&self, count: usize) -> &[MasteryEntry] {
+        &self.entries[..count.min(self.entries.len())]
+    }
+
+    /// The weakest entries, weakest first.
+    pub fn weakest(&self, count: usize) -> Vec<&MasteryEntry> {
+        self.entries.iter().rev().take(count).collect()
+    }
+}
+
+/// Aggregated OpenAI token usage and estimated spend over a period.
+#[derive(Debug, Clone, Default)]
+pub struct UsageTotals {
+    pub requests: u32,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub estimated_cost_usd: f64,
+}
+
+/// Details stored with an answer attempt: the option(s) picked or the text typed, the score
+/// the grader gave a free-text answer, how difficult the question was rated, and how long
+/// the learner took since the question was shown.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttemptAnswer {
+    pub answer_text: Option<String>,
+    pub score: Option<u8>,
+    pub difficulty: Option<QuizDifficulty>,
+    pub elapsed_ms: Option<u64>,
+    /// Answered while retrying missed questions, so it is never logged as a first try.
+    pub retry: bool,
+}
+
+/// A canonical knowledge topic and the group names the model has used for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Topic {
+    pub id: i64,
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub question_count: usize,
+}
+
+/// First-try results for one knowledge group across every recorded session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupAccuracy {
+    pub knowledge_type_group: String,
+    pub attempts: usize,
+    pub first_try_correct: usize,
+}
+
+impl GroupAccuracy {
+    pub fn accuracy(&self) -> f64 {
+        if self.attempts == 0 {
+            0.0
+        } else {
+            self.first_try_correct as f64 / self.attempts as f64
+        }
+    }
+}
+
+/// Which stored knowledge responses to export. Empty fields match everything; group and
+/// language compare case-insensitively.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportFilter {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    pub knowledge_type_group: Option<String>,
+    pub knowledge_type_language: Option<String>,
+}
+
+/// A knowledge response as stored, with the session it was generated for.
+#[derive(Debug, Clone)]
+pub struct StoredResponse {
+    pub session_date: String,
+    pub response: KnowledgeResponse,
+}
+
+/// Version of the portable export format written by [`export_portable_store`].
+pub const PORTABLE_FORMAT_VERSION: u32 = 1;
+
+/// Knowledge responses and answer attempts in a form that can move between machines.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PortableStore {
+    pub version: u32,
+    pub exported_at: String,
+    pub responses: Vec<PortableResponse>,
+    pub attempts: Vec<PortableAttempt>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PortableResponse {
+    pub session_date: String,
+    pub recorded_at: String,
+    pub knowledge_type_group: String,
+    pub knowledge_type_language: String,
+    pub summary: String,
+    pub quiz: Vec<QuizItem>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PortableAttempt {
+    pub session_date: String,
+    pub recorded_at: String,
+    pub knowledge_type_group: String,
+    pub knowledge_type_language: Option<String>,
+    pub question: String,
+    pub question_id: Option<String>,
+    pub attempt_number: u32,
+    pub selected_answer: Option<String>,
+    pub correct: bool,
+    pub elapsed_ms: Option<u64>,
+    pub score: Option<u8>,
+    pub difficulty: Option<QuizDifficulty>,
+}
+
+/// What an import added and what it recognised as already stored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub responses_added: usize,
+    pub questions_added: usize,
+    pub questions_skipped: usize,
+    pub attempts_added: usize,
+    pub attempts_skipped: usize,
+}
+
+/// Whether a question was saved for later or reported as incorrect.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum QuestionMarkKind {
+    #[default]
+    Bookmark,
+    /// Flagged questions are left out of reviews and statistics.
+    Flag,
+}
+
+impl QuestionMarkKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Bookmark => "bookmark",
+            Self::Flag => "flag",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "bookmark" => Some(Self::Bookmark),
+            "flag" => Some(Self::Flag),
+            _ => None,
+        }
+    }
+}
+
+/// A bookmarked or flagged question, stored whole so it can be shown again later.
+#[derive(Debug, Clone)]
+pub struct QuestionMark {
+    pub question_id: String,
+    pub kind: QuestionMarkKind,
+    pub marked_at: String,
+    pub knowledge_type_group: String,
+    pub knowledge_type_language: String,
+    pub summary: String,
+    pub question: QuizItem,
+    pub note: String,
+}
+
+/// A finished timed challenge run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChallengeRun {
+    pub session_date: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub knowledge_groups: Vec<String>,
+    pub question_count: usize,
+    pub correct_count: usize,
+    /// Questions left unanswered when their countdown ran out; also counted as wrong.
+    pub timed_out_count: usize,
+    pub score: u32,
+    pub seconds_per_question: u64,
+    pub duration_ms: u64,
+}
+
+/// A previously generated lesson: every knowledge group recorded from one response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PastLesson {
+    pub session_date: String,
+    pub recorded_at: String,
+    pub groups: Vec<String>,
+    pub question_count: usize,
+    /// Questions answered at least once during the lesson's session.
+    pub answered: usize,
+    pub first_try_correct: usize,
 }
 
 /// Persist AI knowledge responses in a lightweight SQLite database for later analysis.
@@ -53,32 +383,36 @@ pub(crate) fn record_learning_response_at_path(
     persist_learning_entries(&mut connection, session_date, response)
 }
 
-/// Record the user's first attempt for a quiz question. Subsequent attempts are ignored.
-pub fn record_quiz_first_attempt(
+/// Append an answer attempt for a quiz question to the answer log. Attempts are numbered per
+/// question and session; first-try statistics come from attempt number 1.
+pub fn record_quiz_attempt(
     session_date: &str,
     knowledge_type_group: &str,
     knowledge_type_language: Option<&str>,
-    question: &str,
-    first_try_correct: bool,
+    question: &QuizItem,
+    correct: bool,
+    answer: &AttemptAnswer,
 ) -> Result<()> {
     let db_path = database_path()?;
-    record_quiz_first_attempt_at_path(
+    record_quiz_attempt_at_path(
         &db_path,
         session_date,
         knowledge_type_group,
         knowledge_type_language,
         question,
-        first_try_correct,
+        correct,
+        answer,
     )
 }
 
-pub(crate) fn record_quiz_first_attempt_at_path(
+pub(crate) fn record_quiz_attempt_at_path(
     db_path: &Path,
     session_date: &str,
     knowledge_type_group: &str,
     knowledge_type_language: Option<&str>,
-    question: &str,
-    first_try_correct: bool,
+    question: &QuizItem,
+    correct: bool,
+    answer: &AttemptAnswer,
 ) -> Result<()> {
     let mut connection = connection_for_path(db_path)?;
     initialize_schema(&mut connection)?;
@@ -88,159 +422,506 @@ pub(crate) fn record_quiz_first_attempt_at_path(
         knowledge_type_group,
         knowledge_type_language,
         question,
-        first_try_correct,
+        correct,
+        answer,
     )
 }
 
-pub fn load_analytics_snapshot() -> Result<KnowledgeAnalytics> {
+/// Append one message of a follow-up conversation about a quiz question.
+pub fn record_followup_message(
+    session_date: &str,
+    knowledge_type_group: &str,
+    question: &str,
+    message: &ChatMessage,
+) -> Result<()> {
     let db_path = database_path()?;
-    load_analytics_snapshot_from_path(&db_path, 30)
+    record_followup_message_at_path(
+        &db_path,
+        session_date,
+        knowledge_type_group,
+        question,
+        message,
+    )
 }
 
-pub(crate) fn load_analytics_snapshot_from_path(
+pub(crate) fn record_followup_message_at_path(
     db_path: &Path,
-    days: usize,
-) -> Result<KnowledgeAnalytics> {
+    session_date: &str,
+    knowledge_type_group: &str,
+    question: &str,
+    message: &ChatMessage,
+) -> Result<()> {
     let mut connection = connection_for_path(db_path)?;
     initialize_schema(&mut connection)?;
+    connection
+        .execute(
+            "INSERT INTO followup_messages (
+                session_date,
+                recorded_at,
+                knowledge_type_group,
+                question,
+                role,
+                content
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                session_date,
+                Utc::now().to_rfc3339(),
+                knowledge_type_group,
+                question,
+                message.role.as_str(),
+                &message.content,
+            ],
+        )
+        .wrap_err("failed to insert follow-up message into store")?;
+    Ok(())
+}
 
-    let today = Utc::now().date_naive();
-    let start = today - Duration::days(days.saturating_sub(1) as i64);
+/// Load an earlier follow-up conversation about a quiz question, oldest message first.
+pub fn load_followup_messages(
+    session_date: &str,
+    knowledge_type_group: &str,
+    question: &str,
+) -> Result<Vec<ChatMessage>> {
+    let db_path = database_path()?;
+    load_followup_messages_at_path(&db_path, session_date, knowledge_type_group, question)
+}
 
-    let mut daily_map: HashMap<NaiveDate, DailyAnalytics> = HashMap::new();
-    let mut daily_groups: HashMap<NaiveDate, BTreeSet<String>> = HashMap::new();
+pub(crate) fn load_followup_messages_at_path(
+    db_path: &Path,
+    session_date: &str,
+    knowledge_type_group: &str,
+    question: &str,
+) -> Result<Vec<ChatMessage>> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let mut statement = connection
+        .prepare(
+            "SELECT role, content FROM followup_messages
+            WHERE session_date = ?1 AND knowledge_type_group = ?2 AND question = ?3
+            ORDER BY id",
+        )
+        .wrap_err("failed to prepare follow-up message query")?;
+    let rows = statement
+        .query_map(
+            params![session_date, knowledge_type_group, question],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        )
+        .wrap_err("failed to query follow-up messages")?;
 
-    let mut total_questions: u32 = 0;
+    let mut messages = Vec::new();
+    for row in rows {
+        let (role, content) = row.wrap_err("failed to read follow-up message row")?;
+        if let Some(role) = ChatRole::parse(&role) {
+            messages.push(ChatMessage { role, content });
+        }
+    }
+    Ok(messages)
+}
 
-    {
-        let mut stmt = connection.prepare(
-            "SELECT session_date, SUM(quiz_question_count) FROM knowledge_responses
-            WHERE session_date >= ?1 GROUP BY session_date",
-        )?;
-        let rows = stmt.query_map([start.format("%Y-%m-%d").to_string()], |row| {
-            let date_str: String = row.get(0)?;
-            let count: i64 = row.get(1)?;
-            Ok((date_str, count))
-        })?;
+/// Every question text stored with earlier learning responses, oldest first and without repeats.
+pub fn load_past_questions() -> Result<Vec<String>> {
+    let db_path = database_path()?;
+    load_past_questions_at_path(&db_path)
+}
 
-        for result in rows {
-            let (date_str, count) = result?;
-            if let Ok(date) = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
-                let entry = daily_map.entry(date).or_default();
-                entry.date = date;
-                entry.total_questions = entry.total_questions.saturating_add(count as u32);
-                total_questions = total_questions.saturating_add(count as u32);
+pub(crate) fn load_past_questions_at_path(db_path: &Path) -> Result<Vec<String>> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let mut statement = connection
+        .prepare("SELECT quiz_json FROM knowledge_responses ORDER BY id")
+        .wrap_err("failed to prepare past question query")?;
+    let rows = statement
+        .query_map([], |row| row.get::<_, String>(0))
+        .wrap_err("failed to query past questions")?;
+
+    let mut seen = BTreeSet::new();
+    let mut questions = Vec::new();
+    for row in rows {
+        let quiz_json = row.wrap_err("failed to read past question row")?;
+        let Ok(quiz) = serde_json::from_str::<Vec<QuizItem>>(&quiz_json) else {
+            continue;
+        };
+        for item in quiz {
+            if seen.insert(item.question.clone()) {
+                questions.push(item.question);
             }
         }
     }
+    Ok(questions)
+}
 
-    let mut total_first_try_correct: u32 = 0;
-    let mut total_attempts: u32 = 0;
+/// First-try accuracy for every knowledge group with recorded attempts, by group name.
+pub fn load_group_accuracy() -> Result<Vec<GroupAccuracy>> {
+    let db_path = database_path()?;
+    load_group_accuracy_at_path(&db_path)
+}
 
-    {
-        let mut stmt = connection.prepare(
-            "SELECT session_date, SUM(first_try_correct), COUNT(*) FROM quiz_attempts
-            WHERE session_date >= ?1 GROUP BY session_date",
-        )?;
+pub(crate) fn load_group_accuracy_at_path(db_path: &Path) -> Result<Vec<GroupAccuracy>> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT knowledge_type_group, COUNT(*), SUM(first_try_correct) FROM quiz_attempts
+            WHERE {NOT_FLAGGED}
+            GROUP BY knowledge_type_group
+            ORDER BY knowledge_type_group"
+        ))
+        .wrap_err("failed to prepare group accuracy query")?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok(GroupAccuracy {
+                knowledge_type_group: row.get(0)?,
+                attempts: row.get::<_, i64>(1)?.max(0) as usize,
+                first_try_correct: row.get::<_, i64>(2)?.max(0) as usize,
+            })
+        })
+        .wrap_err("failed to query group accuracy")?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .wrap_err("failed to read group accuracy row")
+}
 
-        let rows = stmt.query_map([start.format("%Y-%m-%d").to_string()], |row| {
-            let date_str: String = row.get(0)?;
-            let correct: i64 = row.get(1)?;
-            let attempts: i64 = row.get(2)?;
-            Ok((date_str, correct, attempts))
-        })?;
+/// Knowledge groups the learner consistently answers correctly on the first try.
+pub fn load_mastered_concepts() -> Result<Vec<String>> {
+    let db_path = database_path()?;
+    load_mastered_concepts_at_path(&db_path)
+}
 
-        for result in rows {
-            let (date_str, correct, attempts) = result?;
-            if let Ok(date) = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
-                let entry = daily_map.entry(date).or_default();
-                entry.date = date;
-                entry.first_try_correct = entry.first_try_correct.saturating_add(correct as u32);
-                entry.total_attempts = entry.total_attempts.saturating_add(attempts as u32);
-                total_first_try_correct = total_first_try_correct.saturating_add(correct as u32);
-                total_attempts = total_attempts.saturating_add(attempts as u32);
-            }
-        }
-    }
+pub(crate) fn load_mastered_concepts_at_path(db_path: &Path) -> Result<Vec<String>> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT knowledge_type_group FROM quiz_attempts
+            WHERE {NOT_FLAGGED}
+            GROUP BY knowledge_type_group
+            HAVING COUNT(*) >= ?1 AND AVG(first_try_correct) >= ?2
+            ORDER BY knowledge_type_group"
+        ))
+        .wrap_err("failed to prepare mastered concept query")?;
+    let rows = statement
+        .query_map(
+            params![
+                question_history::MASTERY_MIN_ATTEMPTS as i64,
+                question_history::MASTERY_MIN_ACCURACY
+            ],
+            |row| row.get::<_, String>(0),
+        )
+        .wrap_err("failed to query mastered concepts")?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .wrap_err("failed to read mastered concept row")
+}
 
-    {
-        let mut stmt = connection.prepare(
-            "SELECT session_date, knowledge_type_group FROM knowledge_responses
-            WHERE session_date >= ?1",
-        )?;
-        let rows = stmt.query_map([start.format("%Y-%m-%d").to_string()], |row| {
-            let date_str: String = row.get(0)?;
-            let group: String = row.get(1)?;
-            Ok((date_str, group))
-        })?;
+/// Every stored lesson, newest first, with first-try results from the session it was made for.
+pub fn list_past_lessons() -> Result<Vec<PastLesson>> {
+    let db_path = database_path()?;
+    list_past_lessons_at_path(&db_path)
+}
 
-        for result in rows {
-            let (date_str, group) = result?;
-            if let Ok(date) = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
-                daily_groups.entry(date).or_default().insert(group);
-            }
-        }
-    }
+pub(crate) fn list_past_lessons_at_path(db_path: &Path) -> Result<Vec<PastLesson>> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
 
-    let mut groups: BTreeSet<String> = BTreeSet::new();
+    let mut results: HashMap<(String, String), bool> = HashMap::new();
     {
-        let mut stmt =
-            connection.prepare("SELECT DISTINCT knowledge_type_group FROM knowledge_responses")?;
-        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
-        for result in rows {
-            if let Ok(group) = result {
-                groups.insert(group);
-            }
+        let mut statement = connection
+            .prepare(
+                "SELECT session_date, question_id, first_try_correct FROM quiz_attempts
+                WHERE question_id IS NOT NULL",
+            )
+            .wrap_err("failed to prepare lesson result query")?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, bool>(2)?,
+                ))
+            })
+            .wrap_err("failed to query lesson results")?;
+        for row in rows {
+            let (session_date, question_id, correct) =
+                row.wrap_err("failed to read lesson result row")?;
+            results.insert((session_date, question_id), correct);
         }
     }
 
-    let mut daily: Vec<DailyAnalytics> = Vec::with_capacity(days);
-    let mut cumulative_groups: BTreeSet<String> = BTreeSet::new();
-    for offset in 0..days {
-        let date = start + Duration::days(offset as i64);
-        let mut summary = daily_map.remove(&date).unwrap_or_else(|| DailyAnalytics {
-            date,
-            total_questions: 0,
-            first_try_correct: 0,
-            total_attempts: 0,
-            cumulative_groups: 0,
-        });
-        summary.date = date;
-        if let Some(groups_for_day) = daily_groups.remove(&date) {
-            for group in groups_for_day {
-                cumulative_groups.insert(group);
+    let mut statement = connection
+        .prepare(
+            "SELECT session_date, recorded_at, knowledge_type_group, quiz_json
+            FROM knowledge_responses
+            ORDER BY id DESC",
+        )
+        .wrap_err("failed to prepare past lesson query")?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })
+        .wrap_err("failed to query past lessons")?;
+
+    let mut lessons: Vec<PastLesson> = Vec::new();
+    for row in rows {
+        let (session_date, recorded_at, group, quiz_json) =
+            row.wrap_err("failed to read past lesson row")?;
+        let quiz = serde_json::from_str::<Vec<QuizItem>>(&quiz_json).unwrap_or_default();
+        let lesson = match lessons.last_mut() {
+            Some(lesson)
+                if lesson.session_date == session_date && lesson.recorded_at == recorded_at =>
+            {
+                lesson
+            }
+            _ => {
+                lessons.push(PastLesson {
+                    session_date,
+                    recorded_at,
+                    groups: Vec::new(),
+                    question_count: 0,
+                    answered: 0,
+                    first_try_correct: 0,
+                });
+                lessons.last_mut().expect("lesson was just pushed")
+            }
+        };
+        // Rows come newest first, so insert groups at the front to keep generation order.
+        lesson.groups.insert(0, group);
+        lesson.question_count += quiz.len();
+        for item in &quiz {
+            let key = (lesson.session_date.clone(), question_id(item));
+            if let Some(&correct) = results.get(&key) {
+                lesson.answered += 1;
+                lesson.first_try_correct += usize::from(correct);
             }
         }
-        summary.cumulative_groups = cumulative_groups.len() as u32;
-        daily.push(summary);
     }
+    Ok(lessons)
+}
 
-    Ok(KnowledgeAnalytics {
-        daily,
-        total_questions,
-        total_first_try_correct,
-        total_attempts,
-        knowledge_groups: groups.into_iter().collect(),
-    })
+/// Stored knowledge responses matching `filter`, oldest first.
+pub fn load_stored_responses(filter: &ExportFilter) -> Result<Vec<StoredResponse>> {
+    let db_path = database_path()?;
+    load_stored_responses_at_path(&db_path, filter)
 }
 
-fn persist_learning_entries(
-    connection: &mut Connection,
-    session_date: &str,
-    response: &StructuredLearningResponse,
-) -> Result<()> {
-    let now = Utc::now().to_rfc3339();
-    let transaction = connection
-        .transaction()
-        .wrap_err("failed to start transaction for knowledge store")?;
+pub(crate) fn load_stored_responses_at_path(
+    db_path: &Path,
+    filter: &ExportFilter,
+) -> Result<Vec<StoredResponse>> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let mut statement = connection
+        .prepare(
+            "SELECT session_date, knowledge_type_group, summary, knowledge_type_language, quiz_json
+            FROM knowledge_responses
+            WHERE (?1 IS NULL OR session_date >= ?1)
+                AND (?2 IS NULL OR session_date <= ?2)
+                AND (?3 IS NULL OR knowledge_type_group = ?3 COLLATE NOCASE)
+                AND (?4 IS NULL OR knowledge_type_language = ?4 COLLATE NOCASE)
+            ORDER BY id",
+        )
+        .wrap_err("failed to prepare stored response query")?;
+    let date_param = |date: Option<NaiveDate>| date.map(|date| date.format("%Y-%m-%d").to_string());
+    let rows = statement
+        .query_map(
+            params![
+                date_param(filter.since),
+                date_param(filter.until),
+                filter.knowledge_type_group.as_deref(),
+                filter.knowledge_type_language.as_deref(),
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            },
+        )
+        .wrap_err("failed to query stored responses")?;
 
-    for entry in &response.response {
-        let quiz_json = serde_json::to_string(&entry.quiz)
-            .wrap_err("failed to serialise quiz payload for knowledge store")?;
-        let question_count = entry.quiz.len() as i64;
-        transaction
-            .execute(
-                "INSERT INTO knowledge_responses (
+    let mut responses = Vec::new();
+    for row in rows {
+        let (session_date, knowledge_type_group, summary, knowledge_type_language, quiz_json) =
+            row.wrap_err("failed to read stored response row")?;
+        let Ok(quiz) = serde_json::from_str(&quiz_json) else {
+            continue;
+        };
+        responses.push(StoredResponse {
+            session_date,
+            response: KnowledgeResponse {
+                knowledge_type_group,
+                summary,
+                knowledge_type_language,
+                quiz,
+            },
+        });
+    }
+    Ok(responses)
+}
+
+/// Knowledge responses and answer attempts matching `filter`, ready to write to a file.
+pub fn export_portable_store(filter: &ExportFilter) -> Result<PortableStore> {
+    let db_path = database_path()?;
+    export_portable_store_at_path(&db_path, filter)
+}
+
+pub(crate) fn export_portable_store_at_path(
+    db_path: &Path,
+    filter: &ExportFilter,
+) -> Result<PortableStore> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let date_param = |date: Option<NaiveDate>| date.map(|date| date.format("%Y-%m-%d").to_string());
+    let filter_params = params![
+        date_param(filter.since),
+        date_param(filter.until),
+        filter.knowledge_type_group.as_deref(),
+        filter.knowledge_type_language.as_deref(),
+    ];
+
+    let mut responses = Vec::new();
+    {
+        let mut statement = connection
+            .prepare(
+                "SELECT session_date, recorded_at, knowledge_type_group, knowledge_type_language,
+                    summary, quiz_json
+                FROM knowledge_responses
+                WHERE (?1 IS NULL OR session_date >= ?1)
+                    AND (?2 IS NULL OR session_date <= ?2)
+                    AND (?3 IS NULL OR knowledge_type_group = ?3 COLLATE NOCASE)
+                    AND (?4 IS NULL OR knowledge_type_language = ?4 COLLATE NOCASE)
+                ORDER BY id",
+            )
+            .wrap_err("failed to prepare response export query")?;
+        let rows = statement
+            .query_map(filter_params, |row| {
+                Ok((
+                    PortableResponse {
+                        session_date: row.get(0)?,
+                        recorded_at: row.get(1)?,
+                        knowledge_type_group: row.get(2)?,
+                        knowledge_type_language: row.get(3)?,
+                        summary: row.get(4)?,
+                        quiz: Vec::new(),
+                    },
+                    row.get::<_, String>(5)?,
+                ))
+            })
+            .wrap_err("failed to query responses for export")?;
+        for row in rows {
+            let (mut response, quiz_json) = row.wrap_err("failed to read response for export")?;
+            response.quiz = serde_json::from_str(&quiz_json).wrap_err_with(|| {
+                format!(
+                    "failed to parse stored quiz for '{}'",
+                    response.knowledge_type_group
+                )
+            })?;
+            responses.push(response);
+        }
+    }
+
+    let mut statement = connection
+        .prepare(
+            "SELECT session_date, recorded_at, knowledge_type_group, knowledge_type_language,
+                question, question_id, attempt_number, selected_answer, correct, elapsed_ms,
+                score, difficulty
+            FROM answer_attempts
+            WHERE (?1 IS NULL OR session_date >= ?1)
+                AND (?2 IS NULL OR session_date <= ?2)
+                AND (?3 IS NULL OR knowledge_type_group = ?3 COLLATE NOCASE)
+                AND (?4 IS NULL OR knowledge_type_language = ?4 COLLATE NOCASE)
+            ORDER BY id",
+        )
+        .wrap_err("failed to prepare attempt export query")?;
+    let rows = statement
+        .query_map(filter_params, |row| {
+            Ok(PortableAttempt {
+                session_date: row.get(0)?,
+                recorded_at: row.get(1)?,
+                knowledge_type_group: row.get(2)?,
+                knowledge_type_language: row.get(3)?,
+                question: row.get(4)?,
+                question_id: row.get(5)?,
+                attempt_number: row.get(6)?,
+                selected_answer: row.get(7)?,
+                correct: row.get(8)?,
+                elapsed_ms: row.get::<_, Option<i64>>(9)?.map(|ms| ms.max(0) as u64),
+                score: row.get(10)?,
+                difficulty: row
+                    .get::<_, Option<String>>(11)?
+                    .as_deref()
+                    .and_then(QuizDifficulty::parse),
+            })
+        })
+        .wrap_err("failed to query attempts for export")?;
+    let attempts = rows
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .wrap_err("failed to read attempt for export")?;
+
+    Ok(PortableStore {
+        version: PORTABLE_FORMAT_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        responses,
+        attempts,
+    })
+}
+
+/// Merge an exported store into this one. Questions already stored, matched by question id,
+/// are skipped, as are attempts with the same session, question and timestamp, so importing
+/// the same file twice changes nothing. Attempts are renumbered per question in time order.
+pub fn import_portable_store(store: &PortableStore) -> Result<ImportSummary> {
+    let db_path = database_path()?;
+    import_portable_store_at_path(&db_path, store)
+}
+
+pub(crate) fn import_portable_store_at_path(
+    db_path: &Path,
+    store: &PortableStore,
+) -> Result<ImportSummary> {
+    if store.version > PORTABLE_FORMAT_VERSION {
+        return Err(eyre!(
+            "export format version {} is newer than this learnchain supports ({})",
+            store.version,
+            PORTABLE_FORMAT_VERSION
+        ));
+    }
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let transaction = connection
+        .transaction()
+        .wrap_err("failed to start knowledge store import")?;
+    let mut summary = ImportSummary::default();
+
+    for response in &store.responses {
+        let mut seen = BTreeSet::new();
+        let mut new_items = Vec::new();
+        for item in &response.quiz {
+            let id = question_id(item);
+            let stored = transaction
+                .query_row("SELECT 1 FROM questions WHERE id = ?1", [&id], |_| Ok(()))
+                .optional()
+                .wrap_err("failed to look up imported question")?
+                .is_some();
+            if stored || !seen.insert(id) {
+                summary.questions_skipped += 1;
+            } else {
+                new_items.push(item.clone());
+            }
+        }
+        if new_items.is_empty() {
+            continue;
+        }
+
+        let quiz_json = serde_json::to_string(&new_items)
+            .wrap_err("failed to serialise imported quiz for knowledge store")?;
+        transaction
+            .execute(
+                "INSERT INTO knowledge_responses (
                     session_date,
                     recorded_at,
                     knowledge_type_group,
@@ -250,146 +931,1766 @@ fn persist_learning_entries(
                     quiz_question_count
                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
                 params![
-                    session_date,
-                    &now,
-                    &entry.knowledge_type_group,
-                    &entry.summary,
-                    &entry.knowledge_type_language,
+                    &response.session_date,
+                    &response.recorded_at,
+                    &response.knowledge_type_group,
+                    &response.summary,
+                    &response.knowledge_type_language,
                     &quiz_json,
-                    question_count,
+                    new_items.len() as i64,
                 ],
             )
-            .wrap_err("failed to insert knowledge response into store")?;
+            .wrap_err("failed to insert imported knowledge response")?;
+        for item in &new_items {
+            insert_question(
+                &transaction,
+                &response.recorded_at,
+                &response.knowledge_type_group,
+                &response.knowledge_type_language,
+                item,
+            )?;
+        }
+        summary.responses_added += 1;
+        summary.questions_added += new_items.len();
+    }
+
+    // Imported attempt ids and whether they were retries where they came from.
+    let mut imported: HashMap<i64, bool> = HashMap::new();
+    for attempt in &store.attempts {
+        let duplicate = transaction
+            .query_row(
+                "SELECT 1 FROM answer_attempts
+                WHERE session_date = ?1 AND recorded_at = ?2
+                    AND (question = ?3 OR question_id = ?4)",
+                params![
+                    &attempt.session_date,
+                    &attempt.recorded_at,
+                    &attempt.question,
+                    attempt.question_id.as_deref(),
+                ],
+                |_| Ok(()),
+            )
+            .optional()
+            .wrap_err("failed to look up imported attempt")?
+            .is_some();
+        if duplicate {
+            summary.attempts_skipped += 1;
+            continue;
+        }
+        // Numbered after existing attempts for now; renumbered by time below.
+        transaction
+            .execute(
+                "INSERT INTO answer_attempts (
+                    session_date,
+                    recorded_at,
+                    knowledge_type_group,
+                    knowledge_type_language,
+                    question,
+                    question_id,
+                    attempt_number,
+                    selected_answer,
+                    correct,
+                    elapsed_ms,
+                    score,
+                    difficulty
+                ) VALUES (
+                    ?1, ?2, ?3, ?4, ?5, ?6,
+                    (SELECT COALESCE(MAX(attempt_number), 0) + 1 FROM answer_attempts
+                        WHERE session_date = ?1 AND knowledge_type_group = ?3 AND question = ?5),
+                    ?7, ?8, ?9, ?10, ?11
+                )",
+                params![
+                    &attempt.session_date,
+                    &attempt.recorded_at,
+                    &attempt.knowledge_type_group,
+                    attempt.knowledge_type_language.as_deref(),
+                    &attempt.question,
+                    attempt.question_id.as_deref(),
+                    attempt.selected_answer.as_deref(),
+                    attempt.correct,
+                    attempt.elapsed_ms.map(|ms| ms as i64),
+                    attempt.score,
+                    attempt.difficulty.map(QuizDifficulty::as_str),
+                ],
+            )
+            .wrap_err("failed to insert imported attempt")?;
+        imported.insert(transaction.last_insert_rowid(), attempt.attempt_number >= 2);
+        summary.attempts_added += 1;
     }
 
+    if summary.attempts_added > 0 {
+        link_attempts_to_questions(&transaction)?;
+        renumber_attempts(&transaction, &imported)?;
+    }
     transaction
         .commit()
-        .wrap_err("failed to commit knowledge store transaction")?;
+        .wrap_err("failed to commit knowledge store import")?;
+    Ok(summary)
+}
+
+/// Number every question's attempts within a session by the time they were recorded, so the
+/// earliest attempt from any machine counts as the first try. Retries never do: when the
+/// earliest attempt is a retry, that question's attempts are numbered from 2. `imported` maps
+/// the ids of newly imported attempts to whether they were retries in the store they came
+/// from; every other attempt numbered 2 or later is treated as a retry.
+fn renumber_attempts(connection: &Connection, imported: &HashMap<i64, bool>) -> Result<()> {
+    let mut retries: HashSet<i64> = imported
+        .iter()
+        .filter(|(_, retry)| **retry)
+        .map(|(id, _)| *id)
+        .collect();
+    {
+        let mut statement = connection
+            .prepare("SELECT id FROM answer_attempts WHERE attempt_number >= 2")
+            .wrap_err("failed to prepare retried attempt query")?;
+        let rows = statement
+            .query_map([], |row| row.get::<_, i64>(0))
+            .wrap_err("failed to query retried attempts")?;
+        for id in rows {
+            let id = id.wrap_err("failed to read retried attempt row")?;
+            if !imported.contains_key(&id) {
+                retries.insert(id);
+            }
+        }
+    }
+
+    for statement in [
+        // Move existing numbers out of the way of the unique constraint first.
+        "UPDATE answer_attempts SET attempt_number = -attempt_number",
+        "UPDATE answer_attempts SET attempt_number = (
+            SELECT COUNT(*) FROM answer_attempts AS earlier
+            WHERE earlier.session_date = answer_attempts.session_date
+                AND earlier.knowledge_type_group = answer_attempts.knowledge_type_group
+                AND earlier.question = answer_attempts.question
+                AND (earlier.recorded_at < answer_attempts.recorded_at
+                    OR (earlier.recorded_at = answer_attempts.recorded_at
+                        AND earlier.id <= answer_attempts.id))
+        )",
+    ] {
+        connection
+            .execute(statement, [])
+            .wrap_err("failed to renumber answer attempts")?;
+    }
 
+    let retried_first = {
+        let mut statement = connection
+            .prepare("SELECT id FROM answer_attempts WHERE attempt_number = 1")
+            .wrap_err("failed to prepare first attempt query")?;
+        let rows = statement
+            .query_map([], |row| row.get::<_, i64>(0))
+            .wrap_err("failed to query first attempts")?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .wrap_err("failed to read first attempt row")?
+            .into_iter()
+            .filter(|id| retries.contains(id))
+            .collect::<Vec<_>>()
+    };
+    for id in retried_first {
+        connection
+            .execute(
+                "UPDATE answer_attempts SET attempt_number = -(attempt_number + 1)
+                WHERE EXISTS (
+                    SELECT 1 FROM answer_attempts AS retried
+                    WHERE retried.id = ?1
+                        AND retried.session_date = answer_attempts.session_date
+                        AND retried.knowledge_type_group = answer_attempts.knowledge_type_group
+                        AND retried.question = answer_attempts.question
+                )",
+                [id],
+            )
+            .and_then(|_| {
+                connection.execute(
+                    "UPDATE answer_attempts SET attempt_number = -attempt_number
+                    WHERE attempt_number < 0",
+                    [],
+                )
+            })
+            .wrap_err("failed to renumber retried attempts")?;
+    }
     Ok(())
 }
 
-fn insert_quiz_attempt(
-    connection: &mut Connection,
+/// Rebuild a stored lesson as a learning response, with groups in their original order.
+pub fn load_past_lesson(
     session_date: &str,
-    knowledge_type_group: &str,
-    knowledge_type_language: Option<&str>,
-    question: &str,
-    first_try_correct: bool,
+    recorded_at: &str,
+) -> Result<StructuredLearningResponse> {
+    let db_path = database_path()?;
+    load_past_lesson_at_path(&db_path, session_date, recorded_at)
+}
+
+pub(crate) fn load_past_lesson_at_path(
+    db_path: &Path,
+    session_date: &str,
+    recorded_at: &str,
+) -> Result<StructuredLearningResponse> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let mut statement = connection
+        .prepare(
+            "SELECT knowledge_type_group, summary, knowledge_type_language, quiz_json
+            FROM knowledge_responses
+            WHERE session_date = ?1 AND recorded_at = ?2
+            ORDER BY id",
+        )
+        .wrap_err("failed to prepare lesson query")?;
+    let rows = statement
+        .query_map(params![session_date, recorded_at], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })
+        .wrap_err("failed to query lesson")?;
+
+    let mut response = Vec::new();
+    for row in rows {
+        let (knowledge_type_group, summary, knowledge_type_language, quiz_json) =
+            row.wrap_err("failed to read lesson row")?;
+        let quiz = serde_json::from_str(&quiz_json).wrap_err_with(|| {
+            format!("failed to parse stored quiz for '{}'", knowledge_type_group)
+        })?;
+        response.push(KnowledgeResponse {
+            knowledge_type_group,
+            summary,
+            knowledge_type_language,
+            quiz,
+        });
+    }
+    if response.is_empty() {
+        return Err(eyre!(
+            "no lesson recorded at {} for session {}",
+            recorded_at,
+            session_date
+        ));
+    }
+    Ok(StructuredLearningResponse { response })
+}
+
+/// Build a review lesson of at most `limit` questions from the personal store and `shared`
+/// bank responses. Questions whose latest attempt was wrong come first, then shared questions
+/// never answered, then personal ones never answered; questions last answered correctly are
+/// left out. Newer questions come first within each tier and duplicates are kept once.
+pub fn load_review_queue(
+    shared: &[KnowledgeResponse],
+    limit: usize,
+) -> Result<StructuredLearningResponse> {
+    let db_path = database_path()?;
+    load_review_queue_at_path(&db_path, shared, limit)
+}
+
+pub(crate) fn load_review_queue_at_path(
+    db_path: &Path,
+    shared: &[KnowledgeResponse],
+    limit: usize,
+) -> Result<StructuredLearningResponse> {
+    let personal: Vec<KnowledgeResponse> =
+        load_stored_responses_at_path(db_path, &ExportFilter::default())?
+            .into_iter()
+            .rev()
+            .map(|stored| stored.response)
+            .collect();
+
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let flagged = question_ids_marked(&connection, QuestionMarkKind::Flag)?;
+    let mut latest_correct: HashMap<String, bool> = HashMap::new();
+    {
+        let mut statement = connection
+            .prepare(
+                "SELECT question_id, correct FROM answer_attempts
+                WHERE question_id IS NOT NULL
+                ORDER BY recorded_at, attempt_number",
+            )
+            .wrap_err("failed to prepare review attempt query")?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?))
+            })
+            .wrap_err("failed to query review attempts")?;
+        for row in rows {
+            let (question_id, correct) = row.wrap_err("failed to read review attempt row")?;
+            latest_correct.insert(question_id, correct);
+        }
+    }
+
+    let mut missed = Vec::new();
+    let mut unanswered_shared = Vec::new();
+    let mut unanswered_personal = Vec::new();
+    let mut seen = BTreeSet::new();
+    let sources = personal
+        .iter()
+        .map(|group| (group, false))
+        .chain(shared.iter().rev().map(|group| (group, true)));
+    for (group, is_shared) in sources {
+        for item in &group.quiz {
+            let id = question_id(item);
+            if flagged.contains(&id) || !seen.insert(id.clone()) {
+                continue;
+            }
+            let tier = match latest_correct.get(&id) {
+                Some(true) => continue,
+                Some(false) => &mut missed,
+                None if is_shared => &mut unanswered_shared,
+                None => &mut unanswered_personal,
+            };
+            tier.push((group, item));
+        }
+    }
+
+    let mut response: Vec<KnowledgeResponse> = Vec::new();
+    for (group, item) in missed
+        .into_iter()
+        .chain(unanswered_shared)
+        .chain(unanswered_personal)
+        .take(limit)
+    {
+        let existing = response.iter_mut().find(|entry| {
+            entry.knowledge_type_group == group.knowledge_type_group
+                && entry.knowledge_type_language == group.knowledge_type_language
+        });
+        match existing {
+            Some(entry) => entry.quiz.push(item.clone()),
+            None => response.push(KnowledgeResponse {
+                knowledge_type_group: group.knowledge_type_group.clone(),
+                summary: group.summary.clone(),
+                knowledge_type_language: group.knowledge_type_language.clone(),
+                quiz: vec![item.clone()],
+            }),
+        }
+    }
+    Ok(StructuredLearningResponse { response })
+}
+
+/// Bookmark or flag a question from `group`, replacing any earlier mark of the same kind.
+pub fn set_question_mark(
+    kind: QuestionMarkKind,
+    group: &KnowledgeResponse,
+    question: &QuizItem,
+    note: &str,
 ) -> Result<()> {
-    let now = Utc::now().to_rfc3339();
+    let db_path = database_path()?;
+    set_question_mark_at_path(&db_path, kind, group, question, note)
+}
+
+pub(crate) fn set_question_mark_at_path(
+    db_path: &Path,
+    kind: QuestionMarkKind,
+    group: &KnowledgeResponse,
+    question: &QuizItem,
+    note: &str,
+) -> Result<()> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let question_json =
+        serde_json::to_string(question).wrap_err("failed to serialize marked question")?;
     connection
         .execute(
-            "INSERT OR IGNORE INTO quiz_attempts (
-                session_date,
-                recorded_at,
+            "INSERT OR REPLACE INTO question_marks (
+                question_id,
+                kind,
+                marked_at,
                 knowledge_type_group,
                 knowledge_type_language,
-                question,
-                first_try_correct
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                summary,
+                question_json,
+                note
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
             params![
-                session_date,
-                &now,
-                knowledge_type_group,
-                knowledge_type_language,
-                question,
-                if first_try_correct { 1 } else { 0 },
+                question_id(question),
+                kind.as_str(),
+                Utc::now().to_rfc3339(),
+                &group.knowledge_type_group,
+                &group.knowledge_type_language,
+                &group.summary,
+                question_json,
+                note.trim(),
             ],
         )
-        .wrap_err("failed to insert quiz attempt into store")?;
-
+        .wrap_err("failed to store question mark")?;
     Ok(())
 }
 
-fn initialize_schema(connection: &mut Connection) -> Result<()> {
-    connection
-        .execute(
-            "CREATE TABLE IF NOT EXISTS knowledge_responses (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                session_date TEXT NOT NULL,
-                recorded_at TEXT NOT NULL,
-                knowledge_type_group TEXT NOT NULL,
-                summary TEXT NOT NULL,
-                knowledge_type_language TEXT NOT NULL,
-                quiz_json TEXT NOT NULL,
-                quiz_question_count INTEGER NOT NULL
-            )",
-            [],
-        )
-        .wrap_err("failed to create knowledge_responses table")?;
+pub fn clear_question_mark(kind: QuestionMarkKind, question_id: &str) -> Result<()> {
+    let db_path = database_path()?;
+    clear_question_mark_at_path(&db_path, kind, question_id)
+}
 
+pub(crate) fn clear_question_mark_at_path(
+    db_path: &Path,
+    kind: QuestionMarkKind,
+    question_id: &str,
+) -> Result<()> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
     connection
         .execute(
-            "CREATE INDEX IF NOT EXISTS idx_knowledge_responses_session_date
-            ON knowledge_responses(session_date)",
-            [],
+            "DELETE FROM question_marks WHERE question_id = ?1 AND kind = ?2",
+            params![question_id, kind.as_str()],
         )
-        .wrap_err("failed to create knowledge_responses indexes")?;
+        .wrap_err("failed to remove question mark")?;
+    Ok(())
+}
 
-    connection
-        .execute(
-            "CREATE TABLE IF NOT EXISTS quiz_attempts (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                session_date TEXT NOT NULL,
-                recorded_at TEXT NOT NULL,
-                knowledge_type_group TEXT NOT NULL,
-                knowledge_type_language TEXT,
-                question TEXT NOT NULL,
-                first_try_correct INTEGER NOT NULL,
-                UNIQUE(session_date, knowledge_type_group, question)
-            )",
-            [],
+/// Stored marks, newest first, optionally limited to one kind.
+pub fn load_question_marks(kind: Option<QuestionMarkKind>) -> Result<Vec<QuestionMark>> {
+    let db_path = database_path()?;
+    load_question_marks_at_path(&db_path, kind)
+}
+
+pub(crate) fn load_question_marks_at_path(
+    db_path: &Path,
+    kind: Option<QuestionMarkKind>,
+) -> Result<Vec<QuestionMark>> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let mut statement = connection
+        .prepare(
+            "SELECT question_id, kind, marked_at, knowledge_type_group, knowledge_type_language,
+                summary, question_json, note
+            FROM question_marks
+            WHERE ?1 IS NULL OR kind = ?1
+            ORDER BY marked_at DESC",
         )
-        .wrap_err("failed to create quiz_attempts table")?;
+        .wrap_err("failed to prepare question mark query")?;
+    let rows = statement
+        .query_map(params![kind.map(QuestionMarkKind::as_str)], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
+            ))
+        })
+        .wrap_err("failed to query question marks")?;
+
+    let mut marks = Vec::new();
+    for row in rows {
+        let (question_id, kind, marked_at, group, language, summary, question_json, note) =
+            row.wrap_err("failed to read question mark row")?;
+        let (Some(kind), Ok(question)) = (
+            QuestionMarkKind::parse(&kind),
+            serde_json::from_str(&question_json),
+        ) else {
+            continue;
+        };
+        marks.push(QuestionMark {
+            question_id,
+            kind,
+            marked_at,
+            knowledge_type_group: group,
+            knowledge_type_language: language,
+            summary,
+            question,
+            note,
+        });
+    }
+    Ok(marks)
+}
+
+fn question_ids_marked(connection: &Connection, kind: QuestionMarkKind) -> Result<HashSet<String>> {
+    let mut statement = connection
+        .prepare("SELECT question_id FROM question_marks WHERE kind = ?1")
+        .wrap_err("failed to prepare marked question query")?;
+    let rows = statement
+        .query_map([kind.as_str()], |row| row.get::<_, String>(0))
+        .wrap_err("failed to query marked questions")?;
+    rows.collect::<rusqlite::Result<HashSet<_>>>()
+        .wrap_err("failed to read marked question row")
+}
+
+/// Store a finished challenge run. Returns whether it beat every earlier run's score.
+pub fn record_challenge_run(run: &ChallengeRun) -> Result<bool> {
+    let db_path = database_path()?;
+    record_challenge_run_at_path(&db_path, run)
+}
 
+pub(crate) fn record_challenge_run_at_path(db_path: &Path, run: &ChallengeRun) -> Result<bool> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let previous_best: Option<i64> = connection
+        .query_row("SELECT MAX(score) FROM challenge_runs", [], |row| {
+            row.get(0)
+        })
+        .wrap_err("failed to read best challenge score")?;
+    let knowledge_groups = serde_json::to_string(&run.knowledge_groups)
+        .wrap_err("failed to serialize challenge knowledge groups")?;
     connection
         .execute(
-            "CREATE INDEX IF NOT EXISTS idx_quiz_attempts_session_date
-            ON quiz_attempts(session_date)",
-            [],
+            "INSERT INTO challenge_runs (
+                session_date,
+                started_at,
+                finished_at,
+                knowledge_groups,
+                question_count,
+                correct_count,
+                timed_out_count,
+                score,
+                seconds_per_question,
+                duration_ms
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                &run.session_date,
+                &run.started_at,
+                &run.finished_at,
+                knowledge_groups,
+                run.question_count as i64,
+                run.correct_count as i64,
+                run.timed_out_count as i64,
+                run.score as i64,
+                run.seconds_per_question as i64,
+                run.duration_ms as i64,
+            ],
         )
-        .wrap_err("failed to create quiz_attempts indexes")?;
+        .wrap_err("failed to store challenge run")?;
+    Ok(previous_best.is_none_or(|best| run.score as i64 > best))
+}
 
-    Ok(())
+/// The best challenge runs, highest score first; ties go to the faster run.
+pub fn load_challenge_leaderboard(limit: usize) -> Result<Vec<ChallengeRun>> {
+    let db_path = database_path()?;
+    load_challenge_leaderboard_at_path(&db_path, limit)
 }
 
-fn database_path() -> Result<PathBuf> {
-    let manager = OutputManager::new();
-    let mut output_dir = manager.output_directory().map_err(|err| eyre!(err))?;
-    fs::create_dir_all(&output_dir).wrap_err_with(|| {
-        format!(
-            "failed to create output directory at {}",
-            output_dir.display()
+pub(crate) fn load_challenge_leaderboard_at_path(
+    db_path: &Path,
+    limit: usize,
+) -> Result<Vec<ChallengeRun>> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let mut statement = connection
+        .prepare(
+            "SELECT session_date, started_at, finished_at, knowledge_groups, question_count,
+                correct_count, timed_out_count, score, seconds_per_question, duration_ms
+            FROM challenge_runs
+            ORDER BY score DESC, duration_ms ASC, id ASC
+            LIMIT ?1",
         )
-    })?;
-    output_dir.push(DATABASE_FILENAME);
-    Ok(output_dir)
+        .wrap_err("failed to prepare challenge leaderboard query")?;
+    let rows = statement
+        .query_map([limit as i64], |row| {
+            let knowledge_groups: String = row.get(3)?;
+            Ok(ChallengeRun {
+                session_date: row.get(0)?,
+                started_at: row.get(1)?,
+                finished_at: row.get(2)?,
+                knowledge_groups: serde_json::from_str(&knowledge_groups).unwrap_or_default(),
+                question_count: row.get::<_, i64>(4)?.max(0) as usize,
+                correct_count: row.get::<_, i64>(5)?.max(0) as usize,
+                timed_out_count: row.get::<_, i64>(6)?.max(0) as usize,
+                score: row.get::<_, i64>(7)?.max(0) as u32,
+                seconds_per_question: row.get::<_, i64>(8)?.max(0) as u64,
+                duration_ms: row.get::<_, i64>(9)?.max(0) as u64,
+            })
+        })
+        .wrap_err("failed to query challenge leaderboard")?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .wrap_err("failed to read challenge run row")
 }
 
-fn connection_for_path(db_path: &Path) -> Result<Connection> {
-    if let Some(parent) = db_path.parent() {
-        fs::create_dir_all(parent).wrap_err_with(|| {
-            format!(
-                "failed to create directory for knowledge store at {}",
-                parent.display()
-            )
-        })?;
-    }
+/// Look up a previously generated response stored under `cache_key`.
+pub fn load_cached_response(cache_key: &str) -> Result<Option<StructuredLearningResponse>> {
+    let db_path = database_path()?;
+    load_cached_response_at_path(&db_path, cache_key)
+}
 
-    Connection::open(db_path)
-        .wrap_err_with(|| format!("failed to open knowledge store at {}", db_path.display()))
+pub(crate) fn load_cached_response_at_path(
+    db_path: &Path,
+    cache_key: &str,
+) -> Result<Option<StructuredLearningResponse>> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let payload: Option<String> = connection
+        .query_row(
+            "SELECT response_json FROM response_cache WHERE cache_key = ?1",
+            [cache_key],
+            |row| row.get(0),
+        )
+        .optional()
+        .wrap_err("failed to query response cache")?;
+
+    payload
+        .map(|json| {
+            serde_json::from_str(&json).wrap_err("failed to deserialise cached learning response")
+        })
+        .transpose()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::ai_manager::{KnowledgeResponse, QuizItem, QuizOption, StructuredLearningResponse};
-    use chrono::{Duration, Utc};
-    use std::{fs, time::SystemTime};
+/// Store a generated response so the same summary can be replayed without another request.
+pub fn store_cached_response(
+    cache_key: &str,
+    model_name: &str,
+    response: &StructuredLearningResponse,
+) -> Result<()> {
+    let db_path = database_path()?;
+    store_cached_response_at_path(&db_path, cache_key, model_name, response)
+}
 
-    fn sample_response() -> StructuredLearningResponse {
-        StructuredLearningResponse {
+pub(crate) fn store_cached_response_at_path(
+    db_path: &Path,
+    cache_key: &str,
+    model_name: &str,
+    response: &StructuredLearningResponse,
+) -> Result<()> {
+    if response.response.is_empty() {
+        return Ok(());
+    }
+
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let response_json = serde_json::to_string(response)
+        .wrap_err("failed to serialise learning response for cache")?;
+    connection
+        .execute(
+            "INSERT OR REPLACE INTO response_cache (
+                cache_key,
+                model_name,
+                created_at,
+                response_json
+            ) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                cache_key,
+                model_name,
+                Utc::now().to_rfc3339(),
+                response_json
+            ],
+        )
+        .wrap_err("failed to write learning response to cache")?;
+    Ok(())
+}
+
+/// Record token usage and estimated cost for a single OpenAI request.
+pub fn record_generation_usage(usage: &GenerationUsage, purpose: &str) -> Result<()> {
+    let db_path = database_path()?;
+    record_generation_usage_at_path(&db_path, usage, purpose)
+}
+
+pub(crate) fn record_generation_usage_at_path(
+    db_path: &Path,
+    usage: &GenerationUsage,
+    purpose: &str,
+) -> Result<()> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    connection
+        .execute(
+            "INSERT INTO generation_usage (
+                recorded_at,
+                model_name,
+                purpose,
+                prompt_tokens,
+                completion_tokens,
+                latency_ms,
+                estimated_cost_usd
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                Utc::now().to_rfc3339(),
+                &usage.model_name,
+                purpose,
+                usage.prompt_tokens as i64,
+                usage.completion_tokens as i64,
+                usage.latency_ms as i64,
+                usage.estimated_cost_usd(),
+            ],
+        )
+        .wrap_err("failed to insert generation usage into store")?;
+    Ok(())
+}
+
+/// Estimated spend since the first day of the current (UTC) month.
+pub fn month_to_date_cost() -> Result<f64> {
+    let db_path = database_path()?;
+    month_to_date_cost_at_path(&db_path)
+}
+
+pub(crate) fn month_to_date_cost_at_path(db_path: &Path) -> Result<f64> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    Ok(usage_totals_since(&connection, month_start(Utc::now().date_naive()))?.estimated_cost_usd)
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+fn usage_totals_since(connection: &Connection, start: NaiveDate) -> Result<UsageTotals> {
+    connection
+        .query_row(
+            "SELECT COUNT(*),
+                COALESCE(SUM(prompt_tokens), 0),
+                COALESCE(SUM(completion_tokens), 0),
+                COALESCE(SUM(estimated_cost_usd), 0.0)
+            FROM generation_usage
+            WHERE substr(recorded_at, 1, 10) >= ?1",
+            [start.format("%Y-%m-%d").to_string()],
+            |row| {
+                Ok(UsageTotals {
+                    requests: row.get::<_, i64>(0)? as u32,
+                    prompt_tokens: row.get::<_, i64>(1)? as u64,
+                    completion_tokens: row.get::<_, i64>(2)? as u64,
+                    estimated_cost_usd: row.get(3)?,
+                })
+            },
+        )
+        .wrap_err("failed to aggregate generation usage")
+}
+
+/// Stable id of a quiz question: a hash of its kind, text and options in their original order,
+/// so the same question gets the same id however often it is generated or shuffled.
+pub fn question_id(item: &QuizItem) -> String {
+    let mut options: Vec<(usize, &str)> = item
+        .options
+        .iter()
+        .enumerate()
+        .map(|(index, option)| (option.position.unwrap_or(index), option.selection.trim()))
+        .collect();
+    options.sort_by_key(|(position, _)| *position);
+
+    let mut parts = vec![item.kind.as_str(), item.question.trim()];
+    parts.extend(options.iter().map(|(_, selection)| *selection));
+    content_hash(&parts)[..16].to_string()
+}
+
+/// Stable hex digest of the supplied parts, used for cache keys and content identifiers.
+pub(crate) fn content_hash(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        // Length-prefix each part so ("ab", "c") and ("a", "bc") hash differently.
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub fn load_analytics_snapshot() -> Result<KnowledgeAnalytics> {
+    let db_path = database_path()?;
+    load_analytics_snapshot_from_path(&db_path, 30)
+}
+
+pub(crate) fn load_analytics_snapshot_from_path(
+    db_path: &Path,
+    days: usize,
+) -> Result<KnowledgeAnalytics> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+
+    let today = Utc::now().date_naive();
+    let start = today - Duration::days(days.saturating_sub(1) as i64);
+
+    let mut daily_map: HashMap<NaiveDate, DailyAnalytics> = HashMap::new();
+    let mut daily_groups: HashMap<NaiveDate, BTreeSet<String>> = HashMap::new();
+
+    let mut total_questions: u32 = 0;
+    let flagged = question_ids_marked(&connection, QuestionMarkKind::Flag)?;
+
+    {
+        let mut stmt = connection.prepare(
+            "SELECT session_date, quiz_question_count, quiz_json FROM knowledge_responses
+            WHERE session_date >= ?1",
+        )?;
+        let rows = stmt.query_map([start.format("%Y-%m-%d").to_string()], |row| {
+            let date_str: String = row.get(0)?;
+            let count: i64 = row.get(1)?;
+            let quiz_json: String = row.get(2)?;
+            Ok((date_str, count, quiz_json))
+        })?;
+
+        for result in rows {
+            let (date_str, mut count, quiz_json) = result?;
+            if !flagged.is_empty() {
+                let quiz = serde_json::from_str::<Vec<QuizItem>>(&quiz_json).unwrap_or_default();
+                let flagged_here = quiz
+                    .iter()
+                    .filter(|item| flagged.contains(&question_id(item)))
+                    .count();
+                count = (count - flagged_here as i64).max(0);
+            }
+            if let Ok(date) = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
+                let entry = daily_map.entry(date).or_default();
+                entry.date = date;
+                entry.total_questions = entry.total_questions.saturating_add(count as u32);
+                total_questions = total_questions.saturating_add(count as u32);
+            }
+        }
+    }
+
+    let mut total_first_try_correct: u32 = 0;
+    let mut total_attempts: u32 = 0;
+
+    {
+        let mut stmt = connection.prepare(&format!(
+            "SELECT session_date, SUM(first_try_correct), COUNT(*) FROM quiz_attempts
+            WHERE session_date >= ?1
+                AND {NOT_FLAGGED}
+            GROUP BY session_date"
+        ))?;
+
+        let rows = stmt.query_map([start.format("%Y-%m-%d").to_string()], |row| {
+            let date_str: String = row.get(0)?;
+            let correct: i64 = row.get(1)?;
+            let attempts: i64 = row.get(2)?;
+            Ok((date_str, correct, attempts))
+        })?;
+
+        for result in rows {
+            let (date_str, correct, attempts) = result?;
+            if let Ok(date) = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
+                let entry = daily_map.entry(date).or_default();
+                entry.date = date;
+                entry.first_try_correct = entry.first_try_correct.saturating_add(correct as u32);
+                entry.total_attempts = entry.total_attempts.saturating_add(attempts as u32);
+                total_first_try_correct = total_first_try_correct.saturating_add(correct as u32);
+                total_attempts = total_attempts.saturating_add(attempts as u32);
+            }
+        }
+    }
+
+    {
+        let mut stmt = connection.prepare(
+            "SELECT session_date, knowledge_type_group FROM knowledge_responses
+            WHERE session_date >= ?1",
+        )?;
+        let rows = stmt.query_map([start.format("%Y-%m-%d").to_string()], |row| {
+            let date_str: String = row.get(0)?;
+            let group: String = row.get(1)?;
+            Ok((date_str, group))
+        })?;
+
+        for result in rows {
+            let (date_str, group) = result?;
+            if let Ok(date) = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
+                daily_groups.entry(date).or_default().insert(group);
+            }
+        }
+    }
+
+    let mut groups: BTreeSet<String> = BTreeSet::new();
+    {
+        let mut stmt =
+            connection.prepare("SELECT DISTINCT knowledge_type_group FROM knowledge_responses")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        for result in rows {
+            if let Ok(group) = result {
+                groups.insert(group);
+            }
+        }
+    }
+
+    let mut daily: Vec<DailyAnalytics> = Vec::with_capacity(days);
+    let mut cumulative_groups: BTreeSet<String> = BTreeSet::new();
+    for offset in 0..days {
+        let date = start + Duration::days(offset as i64);
+        let mut summary = daily_map.remove(&date).unwrap_or_else(|| DailyAnalytics {
+            date,
+            total_questions: 0,
+            first_try_correct: 0,
+            total_attempts: 0,
+            cumulative_groups: 0,
+        });
+        summary.date = date;
+        if let Some(groups_for_day) = daily_groups.remove(&date) {
+            for group in groups_for_day {
+                cumulative_groups.insert(group);
+            }
+        }
+        summary.cumulative_groups = cumulative_groups.len() as u32;
+        daily.push(summary);
+    }
+
+    let usage_today = usage_totals_since(&connection, today)?;
+    let usage_this_month = usage_totals_since(&connection, month_start(today))?;
+    let mastery = mastery_report_for(&connection, today)?;
+
+    Ok(KnowledgeAnalytics {
+        daily,
+        total_questions,
+        total_first_try_correct,
+        total_attempts,
+        knowledge_groups: groups.into_iter().collect(),
+        usage_today,
+        usage_this_month,
+        mastery,
+    })
+}
+
+/// Replace each generated knowledge group name with its canonical topic, creating topics for
+/// new names. Groups that land on the same topic in the same language are combined.
+pub fn canonicalize_knowledge_groups(response: &mut StructuredLearningResponse) -> Result<()> {
+    let db_path = database_path()?;
+    canonicalize_knowledge_groups_at_path(&db_path, response)
+}
+
+pub(crate) fn canonicalize_knowledge_groups_at_path(
+    db_path: &Path,
+    response: &mut StructuredLearningResponse,
+) -> Result<()> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+
+    let mut combined: Vec<KnowledgeResponse> = Vec::with_capacity(response.response.len());
+    for mut group in response.response.drain(..) {
+        group.knowledge_type_group = resolve_topic(&connection, &group.knowledge_type_group)?;
+        match combined.iter_mut().find(|existing| {
+            existing.knowledge_type_group == group.knowledge_type_group
+                && existing.knowledge_type_language == group.knowledge_type_language
+        }) {
+            Some(existing) => existing.quiz.append(&mut group.quiz),
+            None => combined.push(group),
+        }
+    }
+    response.response = combined;
+    Ok(())
+}
+
+/// Find the topic for a group name by alias, then by name similarity, creating one if needed.
+fn resolve_topic(connection: &Connection, group_name: &str) -> Result<String> {
+    let name = group_name.trim();
+    let alias_key = normalize_text(name);
+    if alias_key.is_empty() {
+        return Ok(name.to_string());
+    }
+
+    let known: Option<String> = connection
+        .query_row(
+            "SELECT topics.name FROM topic_aliases
+            JOIN topics ON topics.id = topic_aliases.topic_id
+            WHERE topic_aliases.alias_key = ?1",
+            [&alias_key],
+            |row| row.get(0),
+        )
+        .optional()
+        .wrap_err("failed to look up topic alias")?;
+    if let Some(topic) = known {
+        return Ok(topic);
+    }
+
+    let mut stmt = connection
+        .prepare(
+            "SELECT topic_aliases.alias, topics.id, topics.name FROM topic_aliases
+            JOIN topics ON topics.id = topic_aliases.topic_id",
+        )
+        .wrap_err("failed to prepare topic alias query")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .wrap_err("failed to query topic aliases")?;
+    let mut best: Option<(f64, i64, String)> = None;
+    for row in rows {
+        let (alias, topic_id, topic_name) = row.wrap_err("failed to read topic alias row")?;
+        let score = similarity(name, &alias);
+        if score >= TOPIC_MATCH_SIMILARITY
+            && best
+                .as_ref()
+                .is_none_or(|(best_score, _, _)| score > *best_score)
+        {
+            best = Some((score, topic_id, topic_name));
+        }
+    }
+
+    let (topic_id, topic_name) = match best {
+        Some((_, topic_id, topic_name)) => (topic_id, topic_name),
+        None => {
+            connection
+                .execute(
+                    "INSERT INTO topics (name, created_at) VALUES (?1, ?2)",
+                    params![name, Utc::now().to_rfc3339()],
+                )
+                .wrap_err("failed to create topic")?;
+            (connection.last_insert_rowid(), name.to_string())
+        }
+    };
+    connection
+        .execute(
+            "INSERT OR IGNORE INTO topic_aliases (alias_key, alias, topic_id) VALUES (?1, ?2, ?3)",
+            params![alias_key, name, topic_id],
+        )
+        .wrap_err("failed to record topic alias")?;
+    Ok(topic_name)
+}
+
+/// Every topic with its aliases and stored question count, sorted by name. Group names in the
+/// history that predate topics are added as topics of their own first.
+pub fn list_topics() -> Result<Vec<Topic>> {
+    let db_path = database_path()?;
+    list_topics_at_path(&db_path)
+}
+
+pub(crate) fn list_topics_at_path(db_path: &Path) -> Result<Vec<Topic>> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    sync_history_topics(&connection)?;
+
+    let mut aliases: HashMap<i64, Vec<String>> = HashMap::new();
+    {
+        let mut stmt = connection
+            .prepare("SELECT topic_id, alias FROM topic_aliases ORDER BY alias COLLATE NOCASE")
+            .wrap_err("failed to prepare topic alias query")?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .wrap_err("failed to query topic aliases")?;
+        for row in rows {
+            let (topic_id, alias) = row.wrap_err("failed to read topic alias row")?;
+            aliases.entry(topic_id).or_default().push(alias);
+        }
+    }
+
+    let mut stmt = connection
+        .prepare(
+            "SELECT topics.id, topics.name, COALESCE(SUM(knowledge_responses.quiz_question_count), 0)
+            FROM topics
+            LEFT JOIN knowledge_responses
+                ON knowledge_responses.knowledge_type_group = topics.name
+            GROUP BY topics.id
+            ORDER BY topics.name COLLATE NOCASE",
+        )
+        .wrap_err("failed to prepare topic query")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })
+        .wrap_err("failed to query topics")?;
+    let mut topics = Vec::new();
+    for row in rows {
+        let (id, name, question_count) = row.wrap_err("failed to read topic row")?;
+        topics.push(Topic {
+            id,
+            aliases: aliases
+                .remove(&id)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|alias| alias != &name)
+                .collect(),
+            name,
+            question_count: question_count.max(0) as usize,
+        });
+    }
+    Ok(topics)
+}
+
+/// Give every group name already stored in the history a topic, without fuzzy matching.
+fn sync_history_topics(connection: &Connection) -> Result<()> {
+    let mut stmt = connection
+        .prepare(
+            "SELECT knowledge_type_group FROM knowledge_responses
+            UNION SELECT knowledge_type_group FROM quiz_attempts",
+        )
+        .wrap_err("failed to prepare history group query")?;
+    let names = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .wrap_err("failed to query history groups")?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .wrap_err("failed to read history group row")?;
+    for name in names {
+        let alias_key = normalize_text(&name);
+        if alias_key.is_empty() {
+            continue;
+        }
+        connection
+            .execute(
+                "INSERT OR IGNORE INTO topics (name, created_at) VALUES (?1, ?2)",
+                params![name, Utc::now().to_rfc3339()],
+            )
+            .wrap_err("failed to create topic")?;
+        connection
+            .execute(
+                "INSERT OR IGNORE INTO topic_aliases (alias_key, alias, topic_id)
+                SELECT ?1, ?2, id FROM topics WHERE name = ?2",
+                params![alias_key, name],
+            )
+            .wrap_err("failed to record topic alias")?;
+    }
+    Ok(())
+}
+
+/// Rename a topic, rewriting the stored history to use the new name.
+pub fn rename_topic(topic_id: i64, new_name: &str) -> Result<()> {
+    let db_path = database_path()?;
+    rename_topic_at_path(&db_path, topic_id, new_name)
+}
+
+pub(crate) fn rename_topic_at_path(db_path: &Path, topic_id: i64, new_name: &str) -> Result<()> {
+    let new_name = new_name.trim();
+    if normalize_text(new_name).is_empty() {
+        return Err(eyre!("topic names cannot be empty"));
+    }
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let old_name = topic_name(&connection, topic_id)?;
+    let clash: Option<i64> = connection
+        .query_row(
+            "SELECT id FROM topics WHERE name = ?1 COLLATE NOCASE AND id != ?2",
+            params![new_name, topic_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .wrap_err("failed to check topic names")?;
+    if clash.is_some() {
+        return Err(eyre!(
+            "a topic named '{}' already exists; merge the topics instead",
+            new_name
+        ));
+    }
+
+    let transaction = connection
+        .transaction()
+        .wrap_err("failed to start topic rename")?;
+    transaction
+        .execute(
+            "UPDATE topics SET name = ?1 WHERE id = ?2",
+            params![new_name, topic_id],
+        )
+        .wrap_err("failed to rename topic")?;
+    transaction
+        .execute(
+            "INSERT OR REPLACE INTO topic_aliases (alias_key, alias, topic_id) VALUES (?1, ?2, ?3)",
+            params![normalize_text(new_name), new_name, topic_id],
+        )
+        .wrap_err("failed to record topic alias")?;
+    rewrite_group_name(&transaction, &old_name, new_name)?;
+    transaction
+        .commit()
+        .wrap_err("failed to commit topic rename")
+}
+
+/// Fold `source_id` into `target_id`: aliases and history move over and the source is removed.
+pub fn merge_topics(source_id: i64, target_id: i64) -> Result<()> {
+    let db_path = database_path()?;
+    merge_topics_at_path(&db_path, source_id, target_id)
+}
+
+pub(crate) fn merge_topics_at_path(db_path: &Path, source_id: i64, target_id: i64) -> Result<()> {
+    if source_id == target_id {
+        return Err(eyre!("cannot merge a topic into itself"));
+    }
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    let source_name = topic_name(&connection, source_id)?;
+    let target_name = topic_name(&connection, target_id)?;
+
+    let transaction = connection
+        .transaction()
+        .wrap_err("failed to start topic merge")?;
+    transaction
+        .execute(
+            "UPDATE topic_aliases SET topic_id = ?1 WHERE topic_id = ?2",
+            params![target_id, source_id],
+        )
+        .wrap_err("failed to move topic aliases")?;
+    rewrite_group_name(&transaction, &source_name, &target_name)?;
+    transaction
+        .execute("DELETE FROM topics WHERE id = ?1", [source_id])
+        .wrap_err("failed to remove merged topic")?;
+    transaction
+        .commit()
+        .wrap_err("failed to commit topic merge")
+}
+
+fn topic_name(connection: &Connection, topic_id: i64) -> Result<String> {
+    connection
+        .query_row("SELECT name FROM topics WHERE id = ?1", [topic_id], |row| {
+            row.get(0)
+        })
+        .optional()
+        .wrap_err("failed to look up topic")?
+        .ok_or_else(|| eyre!("topic {} no longer exists", topic_id))
+}
+
+/// Point every stored row for `old_name` at `new_name`. Attempts on a question that already
+/// has attempts under the new name in the same session are numbered after those, so the
+/// attempts already under the new name keep their first try.
+fn rewrite_group_name(connection: &Connection, old_name: &str, new_name: &str) -> Result<()> {
+    for statement in [
+        "UPDATE knowledge_responses SET knowledge_type_group = ?2 WHERE knowledge_type_group = ?1",
+        // Move colliding numbers out of the way of the unique constraint first.
+        "UPDATE answer_attempts SET attempt_number = -attempt_number
+            WHERE knowledge_type_group = ?1 AND ?1 != ?2
+            AND EXISTS (
+                SELECT 1 FROM answer_attempts AS kept
+                WHERE kept.knowledge_type_group = ?2
+                    AND kept.session_date = answer_attempts.session_date
+                    AND kept.question = answer_attempts.question
+            )",
+        "UPDATE answer_attempts SET knowledge_type_group = ?2 WHERE knowledge_type_group = ?1",
+        "UPDATE questions SET knowledge_type_group = ?2 WHERE knowledge_type_group = ?1",
+        "UPDATE followup_messages SET knowledge_type_group = ?2 WHERE knowledge_type_group = ?1",
+        "UPDATE question_marks SET knowledge_type_group = ?2 WHERE knowledge_type_group = ?1",
+    ] {
+        connection
+            .execute(statement, params![old_name, new_name])
+            .wrap_err("failed to rewrite knowledge group history")?;
+    }
+
+    let moved = {
+        let mut statement = connection
+            .prepare(
+                "SELECT id FROM answer_attempts
+                WHERE knowledge_type_group = ?1 AND attempt_number < 0
+                ORDER BY recorded_at, id",
+            )
+            .wrap_err("failed to prepare merged attempt query")?;
+        let rows = statement
+            .query_map([new_name], |row| row.get::<_, i64>(0))
+            .wrap_err("failed to query merged attempts")?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .wrap_err("failed to read merged attempt row")?
+    };
+    for id in moved {
+        connection
+            .execute(
+                "UPDATE answer_attempts SET attempt_number = (
+                    SELECT MAX(attempt_number) + 1 FROM answer_attempts AS kept
+                    WHERE kept.session_date = answer_attempts.session_date
+                        AND kept.knowledge_type_group = answer_attempts.knowledge_type_group
+                        AND kept.question = answer_attempts.question
+                )
+                WHERE id = ?1",
+                [id],
+            )
+            .wrap_err("failed to renumber merged attempt")?;
+    }
+    Ok(())
+}
+
+/// Mastery per knowledge group and language from every recorded first attempt.
+pub fn mastery_report() -> Result<MasteryReport> {
+    let db_path = database_path()?;
+    mastery_report_at_path(&db_path, Utc::now().date_naive())
+}
+
+/// Each first attempt scores 1 or 0 (free-text answers use their grade) and is weighted by
+/// how recent it is, halving every [`MASTERY_HALF_LIFE_DAYS`]. A neutral prior keeps groups
+/// with little or only old evidence near 50%.
+pub(crate) fn mastery_report_at_path(db_path: &Path, today: NaiveDate) -> Result<MasteryReport> {
+    let mut connection = connection_for_path(db_path)?;
+    initialize_schema(&mut connection)?;
+    mastery_report_for(&connection, today)
+}
+
+fn mastery_report_for(connection: &Connection, today: NaiveDate) -> Result<MasteryReport> {
+    struct Tally {
+        weighted_correct: f64,
+        weight: f64,
+        attempts: usize,
+        last_attempt: Option<NaiveDate>,
+    }
+
+    let mut stmt = connection
+        .prepare(&format!(
+            "SELECT knowledge_type_group, COALESCE(knowledge_type_language, ''), session_date,
+                first_try_correct, score
+            FROM quiz_attempts
+            WHERE {NOT_FLAGGED}"
+        ))
+        .wrap_err("failed to prepare mastery query")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, Option<i64>>(4)?,
+            ))
+        })
+        .wrap_err("failed to query attempts for mastery")?;
+
+    let mut tallies: HashMap<(String, String), Tally> = HashMap::new();
+    for row in rows {
+        let (group, language, date_str, correct, score) =
+            row.wrap_err("failed to read mastery row")?;
+        let date = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d").ok();
+        let age_days = date
+            .map(|date| (today - date).num_days().max(0) as f64)
+            .unwrap_or(0.0);
+        let weight = 0.5f64.powf(age_days / MASTERY_HALF_LIFE_DAYS);
+        let outcome = match score {
+            Some(score) => (score as f64 / 100.0).clamp(0.0, 1.0),
+            None if correct != 0 => 1.0,
+            None => 0.0,
+        };
+
+        let tally = tallies
+            .entry((group, language.trim().to_string()))
+            .or_insert(Tally {
+                weighted_correct: 0.0,
+                weight: 0.0,
+                attempts: 0,
+                last_attempt: None,
+            });
+        tally.weighted_correct += weight * outcome;
+        tally.weight += weight;
+        tally.attempts += 1;
+        tally.last_attempt = tally.last_attempt.max(date);
+    }
+
+    let mut entries: Vec<MasteryEntry> = tallies
+        .into_iter()
+        .map(|((group, language), tally)| MasteryEntry {
+            knowledge_type_group: group,
+            knowledge_type_language: language,
+            score: (tally.weighted_correct + MASTERY_PRIOR_WEIGHT * 0.5)
+                / (tally.weight + MASTERY_PRIOR_WEIGHT),
+            attempts: tally.attempts,
+            last_attempt: tally.last_attempt,
+        })
+        .collect();
+    entries.sort_by(|left, right| {
+        right
+            .score
+            .total_cmp(&left.score)
+            .then_with(|| right.attempts.cmp(&left.attempts))
+            .then_with(|| left.knowledge_type_group.cmp(&right.knowledge_type_group))
+    });
+    Ok(MasteryReport { entries })
+}
+
+fn persist_learning_entries(
+    connection: &mut Connection,
+    session_date: &str,
+    response: &StructuredLearningResponse,
+) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    let transaction = connection
+        .transaction()
+        .wrap_err("failed to start transaction for knowledge store")?;
+
+    for entry in &response.response {
+        let quiz_json = serde_json::to_string(&entry.quiz)
+            .wrap_err("failed to serialise quiz payload for knowledge store")?;
+        let question_count = entry.quiz.len() as i64;
+        transaction
+            .execute(
+                "INSERT INTO knowledge_responses (
+                    session_date,
+                    recorded_at,
+                    knowledge_type_group,
+                    summary,
+                    knowledge_type_language,
+                    quiz_json,
+                    quiz_question_count
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    session_date,
+                    &now,
+                    &entry.knowledge_type_group,
+                    &entry.summary,
+                    &entry.knowledge_type_language,
+                    &quiz_json,
+                    question_count,
+                ],
+            )
+            .wrap_err("failed to insert knowledge response into store")?;
+        for item in &entry.quiz {
+            insert_question(
+                &transaction,
+                &now,
+                &entry.knowledge_type_group,
+                &entry.knowledge_type_language,
+                item,
+            )?;
+        }
+    }
+
+    transaction
+        .commit()
+        .wrap_err("failed to commit knowledge store transaction")?;
+
+    Ok(())
+}
+
+/// Store a question and its options under their content-hash id. Questions that were stored
+/// before keep their original row.
+fn insert_question(
+    connection: &Connection,
+    recorded_at: &str,
+    knowledge_type_group: &str,
+    knowledge_type_language: &str,
+    item: &QuizItem,
+) -> Result<()> {
+    let id = question_id(item);
+    let inserted = connection
+        .execute(
+            "INSERT OR IGNORE INTO questions (
+                id,
+                first_seen_at,
+                knowledge_type_group,
+                knowledge_type_language,
+                question,
+                kind,
+                difficulty,
+                rubric
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                &id,
+                recorded_at,
+                knowledge_type_group,
+                knowledge_type_language,
+                &item.question,
+                item.kind.as_str(),
+                item.difficulty.as_str(),
+                &item.rubric,
+            ],
+        )
+        .wrap_err("failed to insert question into store")?;
+    if inserted == 0 {
+        return Ok(());
+    }
+
+    for (index, option) in item.options.iter().enumerate() {
+        connection
+            .execute(
+                "INSERT OR IGNORE INTO options (
+                    question_id,
+                    position,
+                    selection,
+                    is_correct_answer,
+                    explanation
+                ) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    &id,
+                    option.position.unwrap_or(index) as i64,
+                    &option.selection,
+                    option.is_correct_answer,
+                    &option.explanation,
+                ],
+            )
+            .wrap_err("failed to insert question option into store")?;
+    }
+    Ok(())
+}
+
+/// Give every stored quiz a `questions` row and point existing attempts at them by text,
+/// preferring a question from the same knowledge group.
+fn backfill_questions(connection: &Connection) -> Result<()> {
+    let mut statement = connection
+        .prepare(
+            "SELECT recorded_at, knowledge_type_group, knowledge_type_language, quiz_json
+            FROM knowledge_responses ORDER BY id",
+        )
+        .wrap_err("failed to prepare question backfill query")?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })
+        .wrap_err("failed to query stored quizzes")?;
+    for row in rows {
+        let (recorded_at, group, language, quiz_json) =
+            row.wrap_err("failed to read stored quiz row")?;
+        let Ok(quiz) = serde_json::from_str::<Vec<QuizItem>>(&quiz_json) else {
+            continue;
+        };
+        for item in &quiz {
+            insert_question(connection, &recorded_at, &group, &language, item)?;
+        }
+    }
+    link_attempts_to_questions(connection)
+}
+
+/// Fill in missing attempt question ids by matching question text, preferring a question from
+/// the same knowledge group.
+fn link_attempts_to_questions(connection: &Connection) -> Result<()> {
+    for statement in [
+        "UPDATE answer_attempts SET question_id = (
+            SELECT questions.id FROM questions
+            WHERE questions.question = answer_attempts.question
+                AND questions.knowledge_type_group = answer_attempts.knowledge_type_group
+            ORDER BY questions.first_seen_at
+            LIMIT 1
+        )
+        WHERE question_id IS NULL",
+        "UPDATE answer_attempts SET question_id = (
+            SELECT questions.id FROM questions
+            WHERE questions.question = answer_attempts.question
+            ORDER BY questions.first_seen_at
+            LIMIT 1
+        )
+        WHERE question_id IS NULL",
+    ] {
+        connection
+            .execute(statement, [])
+            .wrap_err("failed to link attempts to questions")?;
+    }
+    Ok(())
+}
+
+fn insert_quiz_attempt(
+    connection: &mut Connection,
+    session_date: &str,
+    knowledge_type_group: &str,
+    knowledge_type_language: Option<&str>,
+    question: &QuizItem,
+    correct: bool,
+    answer: &AttemptAnswer,
+) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    connection
+        .execute(
+            "INSERT INTO answer_attempts (
+                session_date,
+                recorded_at,
+                knowledge_type_group,
+                knowledge_type_language,
+                question,
+                question_id,
+                attempt_number,
+                selected_answer,
+                correct,
+                elapsed_ms,
+                score,
+                difficulty
+            ) VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6,
+                (SELECT MAX(COALESCE(MAX(attempt_number), 0) + 1, ?12) FROM answer_attempts
+                    WHERE session_date = ?1 AND knowledge_type_group = ?3 AND question = ?5),
+                ?7, ?8, ?9, ?10, ?11
+            )",
+            params![
+                session_date,
+                &now,
+                knowledge_type_group,
+                knowledge_type_language,
+                &question.question,
+                question_id(question),
+                answer.answer_text.as_deref(),
+                if correct { 1 } else { 0 },
+                answer.elapsed_ms.map(|ms| ms as i64),
+                answer.score,
+                answer.difficulty.map(QuizDifficulty::as_str),
+                if answer.retry { 2 } else { 1 },
+            ],
+        )
+        .wrap_err("failed to insert quiz attempt into store")?;
+
+    Ok(())
+}
+
+fn initialize_schema(connection: &mut Connection) -> Result<()> {
+    connection
+        .execute(
+            "CREATE TABLE IF NOT EXISTS knowledge_responses (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_date TEXT NOT NULL,
+                recorded_at TEXT NOT NULL,
+                knowledge_type_group TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                knowledge_type_language TEXT NOT NULL,
+                quiz_json TEXT NOT NULL,
+                quiz_question_count INTEGER NOT NULL
+            )",
+            [],
+        )
+        .wrap_err("failed to create knowledge_responses table")?;
+
+    connection
+        .execute(
+            "CREATE INDEX IF NOT EXISTS idx_knowledge_responses_session_date
+            ON knowledge_responses(session_date)",
+            [],
+        )
+        .wrap_err("failed to create knowledge_responses indexes")?;
+
+    // Stores older than the answer log start from the original first-attempt table, which
+    // the migrations then turn into the log.
+    if schema_version(connection)? < ANSWER_LOG_MIGRATION {
+        create_legacy_quiz_attempts(connection)?;
+    }
+
+    connection
+        .execute(
+            "CREATE TABLE IF NOT EXISTS response_cache (
+                cache_key TEXT PRIMARY KEY,
+                model_name TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                response_json TEXT NOT NULL
+            )",
+            [],
+        )
+        .wrap_err("failed to create response_cache table")?;
+
+    connection
+        .execute(
+            "CREATE TABLE IF NOT EXISTS generation_usage (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                recorded_at TEXT NOT NULL,
+                model_name TEXT NOT NULL,
+                purpose TEXT NOT NULL,
+                prompt_tokens INTEGER NOT NULL,
+                completion_tokens INTEGER NOT NULL,
+                latency_ms INTEGER NOT NULL,
+                estimated_cost_usd REAL NOT NULL
+            )",
+            [],
+        )
+        .wrap_err("failed to create generation_usage table")?;
+
+    connection
+        .execute(
+            "CREATE TABLE IF NOT EXISTS followup_messages (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_date TEXT NOT NULL,
+                recorded_at TEXT NOT NULL,
+                knowledge_type_group TEXT NOT NULL,
+                question TEXT NOT NULL,
+                role TEXT NOT NULL,
+                content TEXT NOT NULL
+            )",
+            [],
+        )
+        .wrap_err("failed to create followup_messages table")?;
+
+    connection
+        .execute(
+            "CREATE TABLE IF NOT EXISTS topics (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )
+        .wrap_err("failed to create topics table")?;
+
+    connection
+        .execute(
+            "CREATE TABLE IF NOT EXISTS topic_aliases (
+                alias_key TEXT PRIMARY KEY,
+                alias TEXT NOT NULL,
+                topic_id INTEGER NOT NULL
+            )",
+            [],
+        )
+        .wrap_err("failed to create topic_aliases table")?;
+
+    apply_migrations(connection)
+}
+
+/// The first-attempt table that migrations up to [`ANSWER_LOG_MIGRATION`] expect to exist.
+fn create_legacy_quiz_attempts(connection: &Connection) -> Result<()> {
+    connection
+        .execute(
+            "CREATE TABLE IF NOT EXISTS quiz_attempts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_date TEXT NOT NULL,
+                recorded_at TEXT NOT NULL,
+                knowledge_type_group TEXT NOT NULL,
+                knowledge_type_language TEXT,
+                question TEXT NOT NULL,
+                first_try_correct INTEGER NOT NULL,
+                UNIQUE(session_date, knowledge_type_group, question)
+            )",
+            [],
+        )
+        .wrap_err("failed to create quiz_attempts table")?;
+
+    connection
+        .execute(
+            "CREATE INDEX IF NOT EXISTS idx_quiz_attempts_session_date
+            ON quiz_attempts(session_date)",
+            [],
+        )
+        .wrap_err("failed to create quiz_attempts indexes")?;
+
+    Ok(())
+}
+
+fn schema_version(connection: &Connection) -> Result<i64> {
+    connection
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .wrap_err("failed to read knowledge store schema version")
+}
+
+fn apply_migrations(connection: &mut Connection) -> Result<()> {
+    let applied = schema_version(connection)?;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(applied.max(0) as usize) {
+        let transaction = connection
+            .transaction()
+            .wrap_err("failed to start knowledge store migration")?;
+        transaction
+            .execute_batch(migration)
+            .wrap_err_with(|| format!("failed to apply knowledge store migration {}", index + 1))?;
+        if (index + 1) as i64 == QUESTIONS_MIGRATION {
+            backfill_questions(&transaction)?;
+        }
+        transaction
+            .pragma_update(None, "user_version", (index + 1) as i64)
+            .wrap_err("failed to update knowledge store schema version")?;
+        transaction
+            .commit()
+            .wrap_err("failed to commit knowledge store migration")?;
+    }
+
+    Ok(())
+}
+
+fn database_path() -> Result<PathBuf> {
+    let manager = OutputManager::new();
+    let mut output_dir = manager.output_directory().map_err(|err| eyre!(err))?;
+    fs::create_dir_all(&output_dir).wrap_err_with(|| {
+        format!(
+            "failed to create output directory at {}",
+            output_dir.display()
+        )
+    })?;
+    output_dir.push(DATABASE_FILENAME);
+    Ok(output_dir)
+}
+
+fn connection_for_path(db_path: &Path) -> Result<Connection> {
+    if let Some(parent) = db_path.parent() {
+        fs::create_dir_all(parent).wrap_err_with(|| {
+            format!(
+                "failed to create directory for knowledge store at {}",
+                parent.display()
+            )
+        })?;
+    }
+
+    Connection::open(db_path)
+        .wrap_err_with(|| format!("failed to open knowledge store at {}", db_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_manager::{KnowledgeResponse, QuizItem, QuizOption, StructuredLearningResponse};
+    use chrono::{Duration, Utc};
+    use std::{fs, time::SystemTime};
+
+    fn quiz_item(question: &str) -> QuizItem {
+        QuizItem {
+            question: question.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn sample_response() -> StructuredLearningResponse {
+        StructuredLearningResponse {
             response: vec![KnowledgeResponse {
                 knowledge_type_group: "Rust Fundamentals".to_string(),
                 summary: "Borrow checker overview".to_string(),
@@ -398,8 +2699,10 @@ mod tests {
                     options: vec![QuizOption {
                         selection: "The borrow checker".to_string(),
                         is_correct_answer: true,
+                        ..Default::default()
                     }],
                     resources: vec!["https://doc.rust-lang.org/".to_string()],
+                    ..Default::default()
                 }],
                 knowledge_type_language: "Rust".to_string(),
             }],
@@ -407,145 +2710,1106 @@ mod tests {
     }
 
     #[test]
-    fn record_learning_response_at_custom_path_persists_rows() {
+    fn record_learning_response_at_custom_path_persists_rows() {
+        let mut temp_dir = std::env::temp_dir();
+        let unique = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        temp_dir.push(format!("learnchain-knowledge-store-{unique}"));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+
+        let response = sample_response();
+        record_learning_response_at_path(&db_path, "2024-05-01", &response).unwrap();
+
+        {
+            let connection = Connection::open(&db_path).unwrap();
+            let row_count: i64 = connection
+                .query_row("SELECT COUNT(*) FROM knowledge_responses", [], |row| {
+                    row.get(0)
+                })
+                .unwrap();
+            assert_eq!(row_count, response.response.len() as i64);
+
+            let question_count: i64 = connection
+                .query_row(
+                    "SELECT quiz_question_count FROM knowledge_responses LIMIT 1",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(question_count, 1);
+        }
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn record_learning_response_ignores_empty_payloads() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-knowledge-store-empty-{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+
+        let empty = StructuredLearningResponse {
+            response: Vec::new(),
+        };
+        record_learning_response_at_path(&db_path, "2024-05-01", &empty).unwrap();
+
+        // The database file should not exist because there was nothing to persist.
+        assert!(!db_path.exists());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn record_quiz_attempt_at_path_logs_every_attempt_and_derives_first_try() {
         let mut temp_dir = std::env::temp_dir();
         let unique = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap()
             .as_nanos();
-        temp_dir.push(format!("learnchain-knowledge-store-{unique}"));
+        temp_dir.push(format!("learnchain-quiz-attempts-{unique}"));
         fs::create_dir_all(&temp_dir).unwrap();
         let db_path = temp_dir.join("test.sqlite");
 
-        let response = sample_response();
-        record_learning_response_at_path(&db_path, "2024-05-01", &response).unwrap();
+        record_quiz_attempt_at_path(
+            &db_path,
+            "2024-05-01",
+            "Rust Fundamentals",
+            Some("Rust"),
+            &quiz_item("What guarantees memory safety?"),
+            false,
+            &AttemptAnswer {
+                answer_text: Some("Garbage collection".to_string()),
+                elapsed_ms: Some(4_200),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // A second attempt is logged too, but first-try stats keep the first result.
+        record_quiz_attempt_at_path(
+            &db_path,
+            "2024-05-01",
+            "Rust Fundamentals",
+            Some("Rust"),
+            &quiz_item("What guarantees memory safety?"),
+            true,
+            &AttemptAnswer {
+                answer_text: Some("The borrow checker".to_string()),
+                elapsed_ms: Some(9_000),
+                ..Default::default()
+            },
+        )
+        .unwrap();
 
         {
             let connection = Connection::open(&db_path).unwrap();
+            let mut statement = connection
+                .prepare(
+                    "SELECT attempt_number, selected_answer, correct, elapsed_ms
+                    FROM answer_attempts ORDER BY attempt_number",
+                )
+                .unwrap();
+            let attempts: Vec<(i64, String, i64, i64)> = statement
+                .query_map([], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })
+                .unwrap()
+                .collect::<rusqlite::Result<_>>()
+                .unwrap();
+            assert_eq!(
+                attempts,
+                vec![
+                    (1, "Garbage collection".to_string(), 0, 4_200),
+                    (2, "The borrow checker".to_string(), 1, 9_000),
+                ]
+            );
+
             let row_count: i64 = connection
-                .query_row("SELECT COUNT(*) FROM knowledge_responses", [], |row| {
-                    row.get(0)
+                .query_row("SELECT COUNT(*) FROM quiz_attempts", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(row_count, 1);
+
+            let first_try_correct: i64 = connection
+                .query_row(
+                    "SELECT first_try_correct FROM quiz_attempts LIMIT 1",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(first_try_correct, 0);
+        }
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn retry_attempts_never_count_as_first_tries() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-retry-attempts-{}",
+            Utc::now().timestamp_nanos_opt().unwrap()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+        let question = quiz_item("What does `git stash` do?");
+        let retry = AttemptAnswer {
+            retry: true,
+            ..Default::default()
+        };
+
+        // Retrying the day after the first try starts a fresh session date.
+        for (session_date, answer) in [
+            ("2024-05-01", AttemptAnswer::default()),
+            ("2024-05-02", retry.clone()),
+            ("2024-05-02", retry),
+        ] {
+            record_quiz_attempt_at_path(
+                &db_path,
+                session_date,
+                "Git",
+                None,
+                &question,
+                session_date == "2024-05-02",
+                &answer,
+            )
+            .unwrap();
+        }
+
+        let connection = Connection::open(&db_path).unwrap();
+        let mut statement = connection
+            .prepare(
+                "SELECT session_date, attempt_number FROM answer_attempts
+                ORDER BY session_date, attempt_number",
+            )
+            .unwrap();
+        let attempts: Vec<(String, i64)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            attempts,
+            vec![
+                ("2024-05-01".to_string(), 1),
+                ("2024-05-02".to_string(), 2),
+                ("2024-05-02".to_string(), 3),
+            ]
+        );
+        let first_tries: i64 = connection
+            .query_row("SELECT COUNT(*) FROM quiz_attempts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(first_tries, 1);
+
+        // Importing only the retries elsewhere must not turn one into a first try.
+        let retries_only = export_portable_store_at_path(
+            &db_path,
+            &ExportFilter {
+                since: NaiveDate::from_ymd_opt(2024, 5, 2),
+                until: None,
+                knowledge_type_group: None,
+                knowledge_type_language: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(retries_only.attempts.len(), 2);
+        let other_path = temp_dir.join("other.sqlite");
+        import_portable_store_at_path(&other_path, &retries_only).unwrap();
+        let other = Connection::open(&other_path).unwrap();
+        let mut statement = other
+            .prepare("SELECT attempt_number FROM answer_attempts ORDER BY attempt_number")
+            .unwrap();
+        let numbers: Vec<i64> = statement
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(numbers, vec![2, 3]);
+        let first_tries: i64 = other
+            .query_row("SELECT COUNT(*) FROM quiz_attempts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(first_tries, 0);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn migration_backfills_questions_and_links_existing_attempts() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-questions-backfill-{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+
+        let response = sample_response();
+        let item = response.response[0].quiz[0].clone();
+
+        // Simulate a store written before questions had their own table.
+        {
+            let connection = Connection::open(&db_path).unwrap();
+            connection
+                .execute_batch(
+                    "CREATE TABLE knowledge_responses (
+                        id INTEGER PRIMARY KEY AUTOINCREMENT,
+                        session_date TEXT NOT NULL,
+                        recorded_at TEXT NOT NULL,
+                        knowledge_type_group TEXT NOT NULL,
+                        summary TEXT NOT NULL,
+                        knowledge_type_language TEXT NOT NULL,
+                        quiz_json TEXT NOT NULL,
+                        quiz_question_count INTEGER NOT NULL
+                    );
+                    CREATE TABLE quiz_attempts (
+                        id INTEGER PRIMARY KEY AUTOINCREMENT,
+                        session_date TEXT NOT NULL,
+                        recorded_at TEXT NOT NULL,
+                        knowledge_type_group TEXT NOT NULL,
+                        knowledge_type_language TEXT,
+                        question TEXT NOT NULL,
+                        first_try_correct INTEGER NOT NULL,
+                        UNIQUE(session_date, knowledge_type_group, question)
+                    );",
+                )
+                .unwrap();
+            connection
+                .execute(
+                    "INSERT INTO knowledge_responses VALUES
+                        (1, '2024-05-01', '2024-05-01T10:00:00Z', 'Rust Fundamentals', '', 'Rust', ?1, 1)",
+                    [serde_json::to_string(&response.response[0].quiz).unwrap()],
+                )
+                .unwrap();
+            connection
+                .execute(
+                    "INSERT INTO quiz_attempts VALUES
+                        (1, '2024-05-01', '2024-05-01T10:05:00Z', 'Rust Fundamentals', 'Rust', ?1, 1)",
+                    [&item.question],
+                )
+                .unwrap();
+        }
+
+        // Any access migrates the store; the new attempt is linked directly.
+        record_quiz_attempt_at_path(
+            &db_path,
+            "2024-05-01",
+            "Rust Fundamentals",
+            Some("Rust"),
+            &item,
+            true,
+            &AttemptAnswer::default(),
+        )
+        .unwrap();
+
+        let connection = Connection::open(&db_path).unwrap();
+        let id = question_id(&item);
+        let (question, kind): (String, String) = connection
+            .query_row(
+                "SELECT question, kind FROM questions WHERE id = ?1",
+                [&id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(question, item.question);
+        assert_eq!(kind, "multiple_choice");
+        let option_count: i64 = connection
+            .query_row(
+                "SELECT COUNT(*) FROM options WHERE question_id = ?1",
+                [&id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(option_count, item.options.len() as i64);
+        let linked: Vec<Option<String>> = connection
+            .prepare("SELECT question_id FROM answer_attempts ORDER BY attempt_number")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(linked, vec![Some(id.clone()), Some(id)]);
+        drop(connection);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn past_lessons_list_newest_first_with_scores_and_reload() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-past-lessons-{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+
+        let first = sample_response();
+        record_learning_response_at_path(&db_path, "2024-05-01", &first).unwrap();
+        let mut second = sample_response();
+        second.response[0].knowledge_type_group = "Shell".to_string();
+        second.response.push(KnowledgeResponse {
+            knowledge_type_group: "Git".to_string(),
+            summary: "Branching".to_string(),
+            quiz: vec![quiz_item("What does git switch do?")],
+            knowledge_type_language: String::new(),
+        });
+        record_learning_response_at_path(&db_path, "2024-05-02", &second).unwrap();
+
+        let item = &first.response[0].quiz[0];
+        record_quiz_attempt_at_path(
+            &db_path,
+            "2024-05-01",
+            "Rust Fundamentals",
+            Some("Rust"),
+            item,
+            false,
+            &AttemptAnswer::default(),
+        )
+        .unwrap();
+        record_quiz_attempt_at_path(
+            &db_path,
+            "2024-05-01",
+            "Rust Fundamentals",
+            Some("Rust"),
+            item,
+            true,
+            &AttemptAnswer::default(),
+        )
+        .unwrap();
+
+        let lessons = list_past_lessons_at_path(&db_path).unwrap();
+        assert_eq!(lessons.len(), 2);
+        assert_eq!(lessons[0].session_date, "2024-05-02");
+        assert_eq!(lessons[0].groups, vec!["Shell", "Git"]);
+        assert_eq!(lessons[0].question_count, 2);
+        assert_eq!(lessons[0].answered, 0);
+        assert_eq!(lessons[1].groups, vec!["Rust Fundamentals"]);
+        assert_eq!((lessons[1].answered, lessons[1].first_try_correct), (1, 0));
+
+        let reloaded =
+            load_past_lesson_at_path(&db_path, "2024-05-02", &lessons[0].recorded_at).unwrap();
+        let groups: Vec<&str> = reloaded
+            .response
+            .iter()
+            .map(|group| group.knowledge_type_group.as_str())
+            .collect();
+        assert_eq!(groups, vec!["Shell", "Git"]);
+        assert_eq!(
+            reloaded.response[1].quiz[0].question,
+            "What does git switch do?"
+        );
+        assert!(load_past_lesson_at_path(&db_path, "2024-05-03", "missing").is_err());
+
+        let filtered = load_stored_responses_at_path(
+            &db_path,
+            &ExportFilter {
+                since: NaiveDate::from_ymd_opt(2024, 5, 2),
+                knowledge_type_group: Some("shell".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].response.knowledge_type_group, "Shell");
+        assert_eq!(filtered[0].session_date, "2024-05-02");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn importing_an_export_merges_questions_and_attempts_by_hash() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-portable-{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let laptop_a = temp_dir.join("a.sqlite");
+        let laptop_b = temp_dir.join("b.sqlite");
+
+        let shared = sample_response();
+        let item = shared.response[0].quiz[0].clone();
+        let record = |db_path: &Path, correct: bool| {
+            record_quiz_attempt_at_path(
+                db_path,
+                "2024-05-01",
+                "Rust Fundamentals",
+                Some("Rust"),
+                &item,
+                correct,
+                &AttemptAnswer::default(),
+            )
+            .unwrap();
+        };
+
+        // Laptop A answers first and wrongly; laptop B answers later and also has its own lesson.
+        record_learning_response_at_path(&laptop_a, "2024-05-01", &shared).unwrap();
+        record(&laptop_a, false);
+        record_learning_response_at_path(&laptop_b, "2024-05-01", &shared).unwrap();
+        let mut extra = sample_response();
+        extra.response[0].quiz[0] = quiz_item("What does `cargo check` skip?");
+        record_learning_response_at_path(&laptop_b, "2024-05-02", &extra).unwrap();
+        record(&laptop_b, true);
+
+        let export = export_portable_store_at_path(&laptop_a, &ExportFilter::default()).unwrap();
+        assert_eq!(export.responses.len(), 1);
+        assert_eq!(export.attempts.len(), 1);
+        assert_eq!(export.attempts[0].question_id, Some(question_id(&item)));
+
+        let summary = import_portable_store_at_path(&laptop_b, &export).unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary {
+                responses_added: 0,
+                questions_added: 0,
+                questions_skipped: 1,
+                attempts_added: 1,
+                attempts_skipped: 0,
+            }
+        );
+
+        // The earliest attempt from either laptop is now the first try.
+        let merged = export_portable_store_at_path(&laptop_b, &ExportFilter::default()).unwrap();
+        assert_eq!(merged.responses.len(), 2);
+        let mut attempts: Vec<(u32, bool)> = merged
+            .attempts
+            .iter()
+            .map(|attempt| (attempt.attempt_number, attempt.correct))
+            .collect();
+        attempts.sort();
+        assert_eq!(attempts, vec![(1, false), (2, true)]);
+
+        // Importing the same file again changes nothing.
+        let again = import_portable_store_at_path(&laptop_b, &export).unwrap();
+        assert_eq!((again.attempts_added, again.attempts_skipped), (0, 1));
+
+        // Into an empty store everything is new.
+        let fresh = temp_dir.join("fresh.sqlite");
+        let summary = import_portable_store_at_path(&fresh, &merged).unwrap();
+        assert_eq!((summary.responses_added, summary.questions_added), (2, 2));
+        assert_eq!(summary.attempts_added, 2);
+        assert_eq!(list_past_lessons_at_path(&fresh).unwrap().len(), 2);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn question_id_ignores_shuffling_but_not_content() {
+        let mut item = QuizItem {
+            question: "Which command lists files?".to_string(),
+            options: ["ls", "cd", "pwd"]
+                .iter()
+                .map(|selection| QuizOption {
+                    selection: selection.to_string(),
+                    ..Default::default()
                 })
+                .collect(),
+            ..Default::default()
+        };
+        let original = question_id(&item);
+
+        for (index, option) in item.options.iter_mut().enumerate() {
+            option.position = Some(index);
+        }
+        item.options.reverse();
+        assert_eq!(question_id(&item), original);
+
+        item.options[0].selection = "mkdir".to_string();
+        assert_ne!(question_id(&item), original);
+    }
+
+    #[test]
+    fn free_text_attempts_store_answer_and_score_after_migration() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-quiz-attempts-free-text-{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+
+        // Simulate a store created before migrations existed.
+        {
+            let connection = Connection::open(&db_path).unwrap();
+            connection
+                .execute(
+                    "CREATE TABLE quiz_attempts (
+                        id INTEGER PRIMARY KEY AUTOINCREMENT,
+                        session_date TEXT NOT NULL,
+                        recorded_at TEXT NOT NULL,
+                        knowledge_type_group TEXT NOT NULL,
+                        knowledge_type_language TEXT,
+                        question TEXT NOT NULL,
+                        first_try_correct INTEGER NOT NULL,
+                        UNIQUE(session_date, knowledge_type_group, question)
+                    )",
+                    [],
+                )
                 .unwrap();
-            assert_eq!(row_count, response.response.len() as i64);
+        }
 
-            let question_count: i64 = connection
+        let answer = AttemptAnswer {
+            answer_text: Some("Ownership moves the value".to_string()),
+            score: Some(72),
+            ..Default::default()
+        };
+        record_quiz_attempt_at_path(
+            &db_path,
+            "2024-05-01",
+            "Ownership",
+            Some("Rust"),
+            &quiz_item("Explain moves"),
+            true,
+            &answer,
+        )
+        .unwrap();
+        // Re-opening must not re-run migrations.
+        record_learning_response_at_path(&db_path, "2024-05-01", &sample_response()).unwrap();
+
+        let connection = Connection::open(&db_path).unwrap();
+        let (text, score): (Option<String>, Option<i64>) = connection
+            .query_row(
+                "SELECT answer_text, score FROM quiz_attempts LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(text.as_deref(), Some("Ownership moves the value"));
+        assert_eq!(score, Some(72));
+        let version: i64 = connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len() as i64);
+        drop(connection);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn record_quiz_attempt_handles_missing_language() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-quiz-attempts-lang-{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+
+        record_quiz_attempt_at_path(
+            &db_path,
+            "2024-05-01",
+            "Rust Fundamentals",
+            None,
+            &quiz_item("What guarantees memory safety?"),
+            true,
+            &AttemptAnswer::default(),
+        )
+        .unwrap();
+
+        {
+            let connection = Connection::open(&db_path).unwrap();
+            let language: Option<String> = connection
                 .query_row(
-                    "SELECT quiz_question_count FROM knowledge_responses LIMIT 1",
+                    "SELECT knowledge_type_language FROM quiz_attempts LIMIT 1",
                     [],
                     |row| row.get(0),
                 )
                 .unwrap();
-            assert_eq!(question_count, 1);
+            assert!(language.is_none());
         }
 
         fs::remove_dir_all(&temp_dir).unwrap();
     }
 
     #[test]
-    fn record_learning_response_ignores_empty_payloads() {
+    fn followup_messages_round_trip_per_question() {
         let mut temp_dir = std::env::temp_dir();
         temp_dir.push(format!(
-            "learnchain-knowledge-store-empty-{}",
+            "learnchain-followup-{}",
             Utc::now().timestamp_nanos_opt().unwrap_or_default()
         ));
         fs::create_dir_all(&temp_dir).unwrap();
         let db_path = temp_dir.join("test.sqlite");
 
-        let empty = StructuredLearningResponse {
-            response: Vec::new(),
+        let question = ChatMessage {
+            role: ChatRole::User,
+            content: "Why can't I borrow twice?".to_string(),
         };
-        record_learning_response_at_path(&db_path, "2024-05-01", &empty).unwrap();
+        let reply = ChatMessage {
+            role: ChatRole::Assistant,
+            content: "Only one mutable borrow may exist.".to_string(),
+        };
+        for message in [&question, &reply] {
+            record_followup_message_at_path(&db_path, "2024-05-01", "Borrowing", "Q1", message)
+                .unwrap();
+        }
+        record_followup_message_at_path(&db_path, "2024-05-01", "Borrowing", "Q2", &question)
+            .unwrap();
 
-        // The database file should not exist because there was nothing to persist.
-        assert!(!db_path.exists());
+        let loaded =
+            load_followup_messages_at_path(&db_path, "2024-05-01", "Borrowing", "Q1").unwrap();
+        assert_eq!(loaded, vec![question, reply]);
+        assert!(
+            load_followup_messages_at_path(&db_path, "2024-05-02", "Borrowing", "Q1")
+                .unwrap()
+                .is_empty()
+        );
 
         fs::remove_dir_all(&temp_dir).unwrap();
     }
 
     #[test]
-    fn record_quiz_first_attempt_at_path_persists_only_first_result() {
+    fn past_questions_and_mastered_concepts_come_from_history() {
         let mut temp_dir = std::env::temp_dir();
-        let unique = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        temp_dir.push(format!("learnchain-quiz-attempts-{unique}"));
+        temp_dir.push(format!(
+            "learnchain-history-{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
         fs::create_dir_all(&temp_dir).unwrap();
         let db_path = temp_dir.join("test.sqlite");
 
-        record_quiz_first_attempt_at_path(
+        let response = |questions: &[&str]| StructuredLearningResponse {
+            response: vec![KnowledgeResponse {
+                knowledge_type_group: "Shell".to_string(),
+                quiz: questions
+                    .iter()
+                    .map(|question| QuizItem {
+                        question: question.to_string(),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }],
+        };
+        record_learning_response_at_path(&db_path, "2024-05-01", &response(&["Q1", "Q2"])).unwrap();
+        record_learning_response_at_path(&db_path, "2024-05-02", &response(&["Q2", "Q3"])).unwrap();
+        assert_eq!(
+            load_past_questions_at_path(&db_path).unwrap(),
+            vec!["Q1", "Q2", "Q3"]
+        );
+
+        let answer = AttemptAnswer::default();
+        for (group, question, correct) in [
+            ("Shell", "Q1", true),
+            ("Shell", "Q2", true),
+            ("Shell", "Q3", true),
+            ("Shell", "Q10", true),
+            ("Shell", "Q11", true),
+            ("Git", "Q12", true),
+            ("Git", "Q13", true),
+            ("Rust", "Q4", true),
+            ("Rust", "Q5", false),
+            ("Rust", "Q6", false),
+            ("Git", "Q7", true),
+        ] {
+            record_quiz_attempt_at_path(
+                &db_path,
+                "2024-05-02",
+                group,
+                None,
+                &quiz_item(question),
+                correct,
+                &answer,
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            load_mastered_concepts_at_path(&db_path).unwrap(),
+            vec!["Shell"]
+        );
+        let accuracy = load_group_accuracy_at_path(&db_path).unwrap();
+        assert_eq!(
+            accuracy
+                .iter()
+                .map(|group| (
+                    group.knowledge_type_group.as_str(),
+                    group.attempts,
+                    group.first_try_correct
+                ))
+                .collect::<Vec<_>>(),
+            vec![("Git", 3, 3), ("Rust", 3, 1), ("Shell", 5, 5)]
+        );
+
+        record_quiz_attempt_at_path(
             &db_path,
-            "2024-05-01",
-            "Rust Fundamentals",
-            Some("Rust"),
-            "What guarantees memory safety?",
+            "2024-05-03",
+            "Git",
+            None,
+            &quiz_item("Q8"),
             false,
+            &AttemptAnswer {
+                difficulty: Some(QuizDifficulty::Hard),
+                ..Default::default()
+            },
         )
         .unwrap();
+        let connection = connection_for_path(&db_path).unwrap();
+        let stored: Option<String> = connection
+            .query_row(
+                "SELECT difficulty FROM quiz_attempts WHERE question = 'Q8'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(stored.as_deref(), Some("hard"));
 
-        // A second attempt should be ignored.
-        record_quiz_first_attempt_at_path(
-            &db_path,
-            "2024-05-01",
-            "Rust Fundamentals",
-            Some("Rust"),
-            "What guarantees memory safety?",
-            true,
-        )
-        .unwrap();
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
 
-        {
-            let connection = Connection::open(&db_path).unwrap();
-            let row_count: i64 = connection
-                .query_row("SELECT COUNT(*) FROM quiz_attempts", [], |row| row.get(0))
-                .unwrap();
-            assert_eq!(row_count, 1);
+    #[test]
+    fn mastery_report_ranks_groups_with_recency_decay() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-mastery-{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+        let today = NaiveDate::from_ymd_opt(2024, 6, 30).unwrap();
 
-            let first_try_correct: i64 = connection
-                .query_row(
-                    "SELECT first_try_correct FROM quiz_attempts LIMIT 1",
-                    [],
-                    |row| row.get(0),
-                )
-                .unwrap();
-            assert_eq!(first_try_correct, 0);
+        let attempts = [
+            // Recent successes outweigh an old miss.
+            ("2024-06-30", "Traits", Some("Rust"), "Q1", true, None),
+            ("2024-06-29", "Traits", Some("Rust"), "Q2", true, None),
+            ("2024-04-01", "Traits", Some("Rust"), "Q3", false, None),
+            // The same group in another language is tracked separately.
+            ("2024-06-30", "Traits", Some("Python"), "Q4", false, None),
+            // Old successes fade towards the neutral prior.
+            ("2024-01-01", "Pipes", None, "Q5", true, None),
+            // Free-text answers count by their grade.
+            (
+                "2024-06-30",
+                "Ownership",
+                Some("Rust"),
+                "Q6",
+                true,
+                Some(60),
+            ),
+        ];
+        for (date, group, language, question, correct, score) in attempts {
+            record_quiz_attempt_at_path(
+                &db_path,
+                date,
+                group,
+                language,
+                &quiz_item(question),
+                correct,
+                &AttemptAnswer {
+                    score,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
         }
 
+        let report = mastery_report_at_path(&db_path, today).unwrap();
+        let ranked: Vec<(&str, &str)> = report
+            .entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.knowledge_type_group.as_str(),
+                    entry.knowledge_type_language.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                ("Traits", "Rust"),
+                ("Ownership", "Rust"),
+                ("Pipes", ""),
+                ("Traits", "Python"),
+            ]
+        );
+        let traits = &report.entries[0];
+        assert_eq!(traits.attempts, 3);
+        assert_eq!(traits.last_attempt, Some(today));
+        assert!(
+            traits.score > 0.75 && traits.score < 0.85,
+            "{}",
+            traits.score
+        );
+        assert!((report.entries[1].score - 0.55).abs() < 1e-9);
+        assert!((report.entries[2].score - 0.5).abs() < 0.01);
+        assert_eq!(report.strongest(1)[0].knowledge_type_group, "Traits");
+        assert_eq!(report.weakest(1)[0].knowledge_type_language, "Python");
+
         fs::remove_dir_all(&temp_dir).unwrap();
     }
 
     #[test]
-    fn record_quiz_first_attempt_handles_missing_language() {
+    fn topics_match_similar_groups_and_support_rename_and_merge() {
         let mut temp_dir = std::env::temp_dir();
         temp_dir.push(format!(
-            "learnchain-quiz-attempts-lang-{}",
+            "learnchain-topics-{}",
             Utc::now().timestamp_nanos_opt().unwrap_or_default()
         ));
         fs::create_dir_all(&temp_dir).unwrap();
         let db_path = temp_dir.join("test.sqlite");
 
-        record_quiz_first_attempt_at_path(
+        let group = |name: &str, question: &str| KnowledgeResponse {
+            knowledge_type_group: name.to_string(),
+            quiz: vec![QuizItem {
+                question: question.to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        // Written before topics existed, so it is picked up by the sync.
+        record_learning_response_at_path(
             &db_path,
             "2024-05-01",
-            "Rust Fundamentals",
+            &StructuredLearningResponse {
+                response: vec![group("Bash builtins and external commands", "Q0")],
+            },
+        )
+        .unwrap();
+
+        let mut response = StructuredLearningResponse {
+            response: vec![
+                group("Shell builtins", "Q1"),
+                group("shell builtin", "Q2"),
+                group("Traits", "Q3"),
+            ],
+        };
+        canonicalize_knowledge_groups_at_path(&db_path, &mut response).unwrap();
+        let names: Vec<&str> = response
+            .response
+            .iter()
+            .map(|group| group.knowledge_type_group.as_str())
+            .collect();
+        assert_eq!(names, vec!["Shell builtins", "Traits"]);
+        assert_eq!(response.response[0].quiz.len(), 2);
+        record_learning_response_at_path(&db_path, "2024-05-02", &response).unwrap();
+        record_quiz_attempt_at_path(
+            &db_path,
+            "2024-05-02",
+            "Shell builtins",
             None,
-            "What guarantees memory safety?",
+            &quiz_item("Q1"),
             true,
+            &AttemptAnswer::default(),
         )
         .unwrap();
 
-        {
-            let connection = Connection::open(&db_path).unwrap();
-            let language: Option<String> = connection
-                .query_row(
-                    "SELECT knowledge_type_language FROM quiz_attempts LIMIT 1",
-                    [],
-                    |row| row.get(0),
+        let topics = list_topics_at_path(&db_path).unwrap();
+        let summary: Vec<(&str, usize, Vec<String>)> = topics
+            .iter()
+            .map(|topic| {
+                (
+                    topic.name.as_str(),
+                    topic.question_count,
+                    topic.aliases.clone(),
                 )
-                .unwrap();
-            assert!(language.is_none());
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Bash builtins and external commands", 1, vec![]),
+                ("Shell builtins", 2, vec!["shell builtin".to_string()]),
+                ("Traits", 1, vec![]),
+            ]
+        );
+
+        let id_of = |name: &str| topics.iter().find(|topic| topic.name == name).unwrap().id;
+        assert!(rename_topic_at_path(&db_path, id_of("Traits"), "shell builtins").is_err());
+        rename_topic_at_path(&db_path, id_of("Shell builtins"), "Shell commands").unwrap();
+        merge_topics_at_path(
+            &db_path,
+            id_of("Bash builtins and external commands"),
+            id_of("Shell builtins"),
+        )
+        .unwrap();
+
+        let topics = list_topics_at_path(&db_path).unwrap();
+        assert_eq!(topics.len(), 2);
+        assert_eq!(topics[0].name, "Shell commands");
+        assert_eq!(topics[0].question_count, 3);
+        let mut again = StructuredLearningResponse {
+            response: vec![group("Bash builtins and external commands", "Q4")],
+        };
+        canonicalize_knowledge_groups_at_path(&db_path, &mut again).unwrap();
+        assert_eq!(again.response[0].knowledge_type_group, "Shell commands");
+        let in_language = |name: &str, language: &str, question: &str| KnowledgeResponse {
+            knowledge_type_language: language.to_string(),
+            ..group(name, question)
+        };
+        let mut languages = StructuredLearningResponse {
+            response: vec![
+                in_language("Traits", "Rust", "Q5"),
+                in_language("traits", "Scala", "Q6"),
+                in_language("Trait", "Rust", "Q7"),
+            ],
+        };
+        canonicalize_knowledge_groups_at_path(&db_path, &mut languages).unwrap();
+        let groups: Vec<(&str, &str, usize)> = languages
+            .response
+            .iter()
+            .map(|group| {
+                (
+                    group.knowledge_type_group.as_str(),
+                    group.knowledge_type_language.as_str(),
+                    group.quiz.len(),
+                )
+            })
+            .collect();
+        assert_eq!(groups, vec![("Traits", "Rust", 2), ("Traits", "Scala", 1)]);
+        assert_eq!(
+            load_mastered_concepts_at_path(&db_path).unwrap(),
+            Vec::<String>::new()
+        );
+        let accuracy = load_group_accuracy_at_path(&db_path).unwrap();
+        assert_eq!(accuracy[0].knowledge_type_group, "Shell commands");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn merging_topics_keeps_every_attempt_and_the_target_first_try() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-topic-merge-attempts-{}",
+            Utc::now().timestamp_nanos_opt().unwrap()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+
+        let item = quiz_item("What does a move do?");
+        for group in ["Ownership", "Memory layout"] {
+            record_learning_response_at_path(
+                &db_path,
+                "2024-05-01",
+                &StructuredLearningResponse {
+                    response: vec![KnowledgeResponse {
+                        knowledge_type_group: group.to_string(),
+                        quiz: vec![item.clone()],
+                        ..Default::default()
+                    }],
+                },
+            )
+            .unwrap();
         }
+        for (group, correct) in [
+            ("Memory layout", false),
+            ("Ownership", true),
+            ("Memory layout", true),
+        ] {
+            record_quiz_attempt_at_path(
+                &db_path,
+                "2024-05-01",
+                group,
+                None,
+                &item,
+                correct,
+                &AttemptAnswer::default(),
+            )
+            .unwrap();
+        }
+        let attempts = |db_path: &Path| -> Vec<(String, i64, bool)> {
+            let connection = connection_for_path(db_path).unwrap();
+            let mut statement = connection
+                .prepare(
+                    "SELECT knowledge_type_group, attempt_number, correct FROM answer_attempts
+                    ORDER BY knowledge_type_group, attempt_number",
+                )
+                .unwrap();
+            statement
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .unwrap()
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .unwrap()
+        };
+        assert_eq!(attempts(&db_path).len(), 3);
+
+        let topics = list_topics_at_path(&db_path).unwrap();
+        let id_of = |name: &str| topics.iter().find(|topic| topic.name == name).unwrap().id;
+        merge_topics_at_path(&db_path, id_of("Memory layout"), id_of("Ownership")).unwrap();
+
+        assert_eq!(
+            attempts(&db_path),
+            vec![
+                ("Ownership".to_string(), 1, true),
+                ("Ownership".to_string(), 2, false),
+                ("Ownership".to_string(), 3, true),
+            ]
+        );
+        let accuracy = load_group_accuracy_at_path(&db_path).unwrap();
+        assert_eq!(
+            (accuracy[0].attempts, accuracy[0].first_try_correct),
+            (1, 1)
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn response_cache_round_trips_by_key() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-response-cache-{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+
+        assert!(
+            load_cached_response_at_path(&db_path, "missing")
+                .unwrap()
+                .is_none()
+        );
+
+        let response = sample_response();
+        store_cached_response_at_path(&db_path, "key-1", "gpt-5-mini", &response).unwrap();
+        let cached = load_cached_response_at_path(&db_path, "key-1")
+            .unwrap()
+            .expect("cached response should exist");
+        assert_eq!(cached.response.len(), 1);
+        assert_eq!(
+            cached.response[0].quiz[0].question,
+            "What guarantees memory safety?"
+        );
+        assert!(
+            load_cached_response_at_path(&db_path, "key-2")
+                .unwrap()
+                .is_none()
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn generation_usage_totals_appear_in_analytics_snapshot() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-generation-usage-{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+
+        let usage = GenerationUsage {
+            model_name: "gpt-5".to_string(),
+            prompt_tokens: 1_000,
+            completion_tokens: 500,
+            latency_ms: 1_200,
+        };
+        record_generation_usage_at_path(&db_path, &usage, "lesson").unwrap();
+        record_generation_usage_at_path(&db_path, &usage, "lesson").unwrap();
+
+        let analytics = load_analytics_snapshot_from_path(&db_path, 7).unwrap();
+        assert_eq!(analytics.usage_today.requests, 2);
+        assert_eq!(analytics.usage_today.prompt_tokens, 2_000);
+        assert_eq!(analytics.usage_today.completion_tokens, 1_000);
+        let expected_cost = usage.estimated_cost_usd() * 2.0;
+        assert!((analytics.usage_this_month.estimated_cost_usd - expected_cost).abs() < 1e-9);
 
         fs::remove_dir_all(&temp_dir).unwrap();
     }
@@ -572,16 +3836,20 @@ mod tests {
                             options: vec![QuizOption {
                                 selection: "Answer".to_string(),
                                 is_correct_answer: true,
+                                ..Default::default()
                             }],
                             resources: Vec::new(),
+                            ..Default::default()
                         },
                         QuizItem {
                             question: "Question 2".to_string(),
                             options: vec![QuizOption {
                                 selection: "Answer".to_string(),
                                 is_correct_answer: true,
+                                ..Default::default()
                             }],
                             resources: Vec::new(),
+                            ..Default::default()
                         },
                     ],
                     knowledge_type_language: "Rust".to_string(),
@@ -594,8 +3862,10 @@ mod tests {
                         options: vec![QuizOption {
                             selection: "Answer".to_string(),
                             is_correct_answer: true,
+                            ..Default::default()
                         }],
                         resources: Vec::new(),
+                        ..Default::default()
                     }],
                     knowledge_type_language: "Rust".to_string(),
                 },
@@ -611,22 +3881,24 @@ mod tests {
         record_learning_response_at_path(&db_path, &day1_str, &response).unwrap();
         record_learning_response_at_path(&db_path, &day2_str, &response).unwrap();
 
-        record_quiz_first_attempt_at_path(
+        record_quiz_attempt_at_path(
             &db_path,
             &day1_str,
             "Rust Ownership",
             Some("Rust"),
-            "Question 1",
+            &quiz_item("Question 1"),
             true,
+            &AttemptAnswer::default(),
         )
         .unwrap();
-        record_quiz_first_attempt_at_path(
+        record_quiz_attempt_at_path(
             &db_path,
             &day2_str,
             "Traits",
             Some("Rust"),
-            "Question 3",
+            &quiz_item("Question 3"),
             false,
+            &AttemptAnswer::default(),
         )
         .unwrap();
 
@@ -668,4 +3940,215 @@ mod tests {
 
         fs::remove_dir_all(&temp_dir).unwrap();
     }
+
+    #[test]
+    fn review_queue_puts_misses_first_then_unanswered_shared_and_personal() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-review-queue-{}",
+            Utc::now().timestamp_nanos_opt().unwrap()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+
+        let group = |name: &str, questions: &[&str]| KnowledgeResponse {
+            knowledge_type_group: name.to_string(),
+            knowledge_type_language: "Rust".to_string(),
+            summary: format!("{} summary", name),
+            quiz: questions
+                .iter()
+                .map(|question| quiz_item(question))
+                .collect(),
+        };
+        record_learning_response_at_path(
+            &db_path,
+            "2024-05-01",
+            &StructuredLearningResponse {
+                response: vec![group("Ownership", &["Missed", "Mastered", "Unseen"])],
+            },
+        )
+        .unwrap();
+        let answer = AttemptAnswer::default();
+        for (question, correct) in [("Missed", true), ("Missed", false), ("Mastered", true)] {
+            record_quiz_attempt_at_path(
+                &db_path,
+                "2024-05-01",
+                "Ownership",
+                Some("Rust"),
+                &quiz_item(question),
+                correct,
+                &answer,
+            )
+            .unwrap();
+        }
+
+        let mut shared = group("Traits", &["Shared", "Mastered"]);
+        shared.quiz[0].author = Some("Ada".to_string());
+        let queue = load_review_queue_at_path(&db_path, &[shared.clone()], 10).unwrap();
+        let questions: Vec<(&str, &str)> = queue
+            .response
+            .iter()
+            .flat_map(|group| {
+                group
+                    .quiz
+                    .iter()
+                    .map(|item| (group.knowledge_type_group.as_str(), item.question.as_str()))
+            })
+            .collect();
+        assert_eq!(
+            questions,
+            vec![
+                ("Ownership", "Missed"),
+                ("Ownership", "Unseen"),
+                ("Traits", "Shared"),
+            ]
+        );
+        assert_eq!(queue.response[0].summary, "Ownership summary");
+        assert_eq!(queue.response[1].quiz[0].author.as_deref(), Some("Ada"));
+
+        let limited = load_review_queue_at_path(&db_path, &[shared], 2).unwrap();
+        let limited: Vec<&str> = limited
+            .response
+            .iter()
+            .flat_map(|group| group.quiz.iter().map(|item| item.question.as_str()))
+            .collect();
+        assert_eq!(limited, vec!["Missed", "Shared"]);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn flagged_questions_leave_reviews_and_statistics_but_bookmarks_stay_listed() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-question-marks-{}",
+            Utc::now().timestamp_nanos_opt().unwrap()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+
+        let today = Utc::now().date_naive().format("%Y-%m-%d").to_string();
+        let group = KnowledgeResponse {
+            knowledge_type_group: "Ownership".to_string(),
+            knowledge_type_language: "Rust".to_string(),
+            summary: "Moves and borrows".to_string(),
+            quiz: vec![quiz_item("Wrong question"), quiz_item("Good question")],
+        };
+        record_learning_response_at_path(
+            &db_path,
+            &today,
+            &StructuredLearningResponse {
+                response: vec![group.clone()],
+            },
+        )
+        .unwrap();
+        for item in &group.quiz {
+            record_quiz_attempt_at_path(
+                &db_path,
+                &today,
+                "Ownership",
+                Some("Rust"),
+                item,
+                false,
+                &AttemptAnswer::default(),
+            )
+            .unwrap();
+        }
+
+        set_question_mark_at_path(
+            &db_path,
+            QuestionMarkKind::Flag,
+            &group,
+            &group.quiz[0],
+            "  Two answers are correct  ",
+        )
+        .unwrap();
+        set_question_mark_at_path(
+            &db_path,
+            QuestionMarkKind::Bookmark,
+            &group,
+            &group.quiz[1],
+            "",
+        )
+        .unwrap();
+
+        let flags = load_question_marks_at_path(&db_path, Some(QuestionMarkKind::Flag)).unwrap();
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].note, "Two answers are correct");
+        assert_eq!(flags[0].question.question, "Wrong question");
+        let bookmarks =
+            load_question_marks_at_path(&db_path, Some(QuestionMarkKind::Bookmark)).unwrap();
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].summary, "Moves and borrows");
+        assert_eq!(
+            load_question_marks_at_path(&db_path, None).unwrap().len(),
+            2
+        );
+
+        let queue = load_review_queue_at_path(&db_path, &[], 10).unwrap();
+        assert_eq!(queue.response[0].quiz.len(), 1);
+        assert_eq!(queue.response[0].quiz[0].question, "Good question");
+
+        let analytics = load_analytics_snapshot_from_path(&db_path, 1).unwrap();
+        assert_eq!(analytics.total_questions, 1);
+        assert_eq!(analytics.total_attempts, 1);
+        let accuracy = load_group_accuracy_at_path(&db_path).unwrap();
+        assert_eq!(accuracy[0].attempts, 1);
+
+        clear_question_mark_at_path(
+            &db_path,
+            QuestionMarkKind::Flag,
+            &question_id(&group.quiz[0]),
+        )
+        .unwrap();
+        let analytics = load_analytics_snapshot_from_path(&db_path, 1).unwrap();
+        assert_eq!(analytics.total_questions, 2);
+        assert_eq!(analytics.total_attempts, 2);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn challenge_runs_report_personal_bests_and_rank_by_score() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-challenge-runs-{}",
+            Utc::now().timestamp_nanos_opt().unwrap()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+
+        let run = |score: u32, duration_ms: u64| ChallengeRun {
+            session_date: "2024-05-01".to_string(),
+            started_at: "2024-05-01T10:00:00+00:00".to_string(),
+            finished_at: "2024-05-01T10:02:00+00:00".to_string(),
+            knowledge_groups: vec!["Git".to_string(), "Rust".to_string()],
+            question_count: 5,
+            correct_count: 3,
+            timed_out_count: 1,
+            score,
+            seconds_per_question: 30,
+            duration_ms,
+        };
+
+        assert!(
+            load_challenge_leaderboard_at_path(&db_path, 10)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(record_challenge_run_at_path(&db_path, &run(420, 90_000)).unwrap());
+        assert!(!record_challenge_run_at_path(&db_path, &run(300, 60_000)).unwrap());
+        assert!(!record_challenge_run_at_path(&db_path, &run(420, 80_000)).unwrap());
+        assert!(record_challenge_run_at_path(&db_path, &run(510, 100_000)).unwrap());
+
+        let leaderboard = load_challenge_leaderboard_at_path(&db_path, 3).unwrap();
+        let ranked: Vec<(u32, u64)> = leaderboard
+            .iter()
+            .map(|run| (run.score, run.duration_ms))
+            .collect();
+        assert_eq!(ranked, vec![(510, 100_000), (420, 80_000), (420, 90_000)]);
+        assert_eq!(leaderboard[0], run(510, 100_000));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
 }
@@ -1,25 +1,46 @@
 mod ai_manager;
+mod challenge;
+mod cli;
+mod code_highlight;
 mod config;
 mod knowledge_store;
 mod log_util;
 mod markdown_rules;
 mod output_manager;
+mod prompt_templates;
+mod question_history;
+mod quiz_export;
+mod quiz_validation;
 mod session_manager;
+mod shared_bank;
 mod ui_renderer;
 mod view_managers;
 
-use ai_manager::{AiManager, StructuredLearningResponse, poll_ai_messages};
+use ai_manager::{
+    AiManager, FollowupChat, FreeTextGrade, GeneratedLesson, GradingRequest,
+    StructuredLearningResponse, poll_ai_messages, poll_followup_messages, poll_grading_messages,
+};
+use challenge::ChallengeState;
 use color_eyre::Result;
 use config::ConfigForm;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use dotenvy::dotenv;
-use knowledge_store::KnowledgeAnalytics;
+use knowledge_store::{KnowledgeAnalytics, QuestionMarkKind};
 use output_manager::OutputManager;
 use ratatui::{DefaultTerminal, Frame};
 use session_manager::{SessionEvent, SessionLoad, SessionManager};
-use std::{collections::HashSet, path::PathBuf, sync::mpsc::Receiver, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::mpsc::Receiver,
+    time::{Duration, Instant},
+};
 use ui_renderer::UiRenderer;
-use view_managers::{AnalyticsManager, ConfigManager, LearningManager, MenuManager};
+use view_managers::{
+    AnalyticsManager, BookmarksManager, BookmarksState, ConfigManager, HistoryManager,
+    HistoryState, LeaderboardManager, LeaderboardState, LearningManager, MenuManager,
+    ResultsManager, ResultsState, TopicsManager, TopicsState,
+};
 
 pub(crate) const AI_LOADING_FRAMES: [&str; 4] = ["-", "\\", "|", "/"];
 pub(crate) const OPENAI_KEY_HELP: &str = "OpenAI API key not configured. Open the Config view (select \"OpenAI API key\" and press Enter) or run `learnchain --set-openai-key <your-key>` to add it.";
@@ -31,11 +52,34 @@ pub(crate) enum AppView {
     Learning,
     Config,
     Analytics,
+    Topics,
+    History,
+    Bookmarks,
+    Leaderboard,
+    Results,
 }
 
 #[derive(Debug)]
 enum AiTaskMessage {
-    Success(StructuredLearningResponse),
+    Success(GeneratedLesson),
+    Error(String),
+}
+
+#[derive(Debug)]
+enum GradingMessage {
+    Success(GradingRequest, FreeTextGrade),
+    Error(String),
+}
+
+#[derive(Debug)]
+enum FollowupTaskMessage {
+    /// The tutor's reply, with the question it answers.
+    Reply {
+        group_index: usize,
+        quiz_index: usize,
+        question_id: String,
+        reply: String,
+    },
     Error(String),
 }
 
@@ -68,9 +112,25 @@ fn main() -> color_eyre::Result<()> {
                 println!("Cleared OpenAI API key from config/app_config.toml.");
                 return Ok(());
             }
+            "prompt" => {
+                cli::run_prompt_command(&args[2..])?;
+                return Ok(());
+            }
+            "mastery" => {
+                cli::run_mastery_command()?;
+                return Ok(());
+            }
+            "export" => {
+                cli::run_export_command(&args[2..])?;
+                return Ok(());
+            }
+            "import" => {
+                cli::run_import_command(&args[2..])?;
+                return Ok(());
+            }
             "--help" | "-h" => {
                 println!(
-                    "learnchain options:\n  --set-openai-key <key>    store your OpenAI API key in the app config\n  --clear-openai-key       remove the stored OpenAI API key\n  --help                   show this message\n  --version                show version"
+                    "learnchain options:\n  --set-openai-key <key>    store your OpenAI API key in the app config\n  --clear-openai-key       remove the stored OpenAI API key\n  --help                   show this message\n  --version                show version\n\ncommands:\n  prompt preview           print the rendered prompts for the current session\n  mastery                  rank knowledge groups by recency-weighted mastery\n  export --format <fmt>    export stored quizzes: anki (Anki deck), json or csv (whole store); filters: --since/--until YYYY-MM-DD, --group, --language, --output\n  import <file>            merge a json or csv export into the knowledge store"
                 );
                 return Ok(());
             }
@@ -133,6 +193,12 @@ pub struct App {
     pub(crate) ai_loading_frame: usize,
     /// Receives background AI task updates.
     pub(crate) ai_result_receiver: Option<Receiver<AiTaskMessage>>,
+    /// Receives the grade for a free-text answer being assessed in the background.
+    pub(crate) grading_receiver: Option<Receiver<GradingMessage>>,
+    /// Follow-up conversation about the current question; `Some` while the panel is open.
+    pub(crate) followup: Option<FollowupChat>,
+    /// Receives the tutor's reply to the latest follow-up question.
+    pub(crate) followup_receiver: Option<Receiver<FollowupTaskMessage>>,
     /// Cached learning response from the most recent AI generation.
     pub(crate) learning_response: Option<StructuredLearningResponse>,
     /// Index of the currently selected knowledge group within the learning response.
@@ -147,20 +213,46 @@ pub struct App {
     pub(crate) learning_summary_revealed: bool,
     /// Indicates that the correct answer was chosen and we are waiting to advance.
     pub(crate) learning_waiting_for_next: bool,
+    /// Options marked for a multi-select answer, or picked in sequence for an order-lines answer.
+    pub(crate) learning_marked_options: Vec<usize>,
+    /// Answer being typed for a fill-in-the-blank or free-text question; `Some` while the input has focus.
+    pub(crate) learning_text_input: Option<String>,
+    /// Note being typed while flagging the active question as incorrect; `Some` while the input has focus.
+    pub(crate) learning_flag_note: Option<String>,
     /// Holds the editable configuration state when rendering the config view.
     pub(crate) config_form: ConfigForm,
     /// Whether artifacts should be written to disk.
     pub(crate) write_output_artifacts: bool,
     /// Currently selected OpenAI model.
     pub(crate) openai_model: config::OpenAiModelKind,
-    /// Tracks which quiz questions have already had their first attempt persisted.
-    pub(crate) quiz_first_attempts: HashSet<(usize, usize)>,
+    /// First-try result (correct or not) of each quiz question answered at least once.
+    pub(crate) quiz_first_attempts: HashMap<(usize, usize), bool>,
+    /// When the current lesson was opened, used to report time spent on the results screen.
+    pub(crate) learning_started_at: Option<Instant>,
+    /// Whether the lesson shown is a retry of missed questions, whose answers are logged as retries.
+    pub(crate) learning_retry: bool,
+    /// Question on screen and when it was first shown, used to time answer attempts.
+    pub(crate) learning_question_shown_at: Option<((usize, usize), Instant)>,
+    /// Ids of bookmarked and flagged questions, mirrored from the knowledge store.
+    pub(crate) question_marks: HashSet<(String, QuestionMarkKind)>,
+    /// Timed challenge running over the current lesson, if any.
+    pub(crate) challenge: Option<ChallengeState>,
     /// Cached analytics snapshot for the dashboard view.
     pub(crate) analytics_snapshot: Option<KnowledgeAnalytics>,
     /// Any error that occurred when loading analytics data.
     pub(crate) analytics_error: Option<String>,
     /// Timestamp of the most recent analytics refresh.
     pub(crate) analytics_refreshed_at: Option<String>,
+    /// Topic management screen state.
+    pub(crate) topics: TopicsState,
+    /// Past lessons screen state.
+    pub(crate) history: HistoryState,
+    /// Bookmarked questions screen state.
+    pub(crate) bookmarks: BookmarksState,
+    /// Challenge leaderboard screen state.
+    pub(crate) leaderboard: LeaderboardState,
+    /// End-of-lesson results screen state.
+    pub(crate) results: ResultsState,
 }
 
 impl App {
@@ -195,6 +287,26 @@ impl App {
             }
         };
 
+        // Marks are only persisted with artifacts enabled, so skip creating the store otherwise.
+        let stored_marks = if write_output_artifacts {
+            knowledge_store::load_question_marks(None)
+        } else {
+            Ok(Vec::new())
+        };
+        let question_marks = match stored_marks {
+            Ok(marks) => marks
+                .into_iter()
+                .map(|mark| (mark.question_id, mark.kind))
+                .collect(),
+            Err(err) => {
+                Self::push_error(
+                    &mut aggregated_error,
+                    format!("Bookmarks unavailable: {}", err),
+                );
+                HashSet::new()
+            }
+        };
+
         let mut app = Self {
             running: false,
             view: AppView::Menu,
@@ -213,6 +325,9 @@ impl App {
             ai_loading: false,
             ai_loading_frame: 0,
             ai_result_receiver: None,
+            grading_receiver: None,
+            followup: None,
+            followup_receiver: None,
             learning_response: None,
             learning_group_index: 0,
             learning_quiz_index: 0,
@@ -220,13 +335,26 @@ impl App {
             learning_feedback: None,
             learning_summary_revealed: false,
             learning_waiting_for_next: false,
+            learning_marked_options: Vec::new(),
+            learning_text_input: None,
+            learning_flag_note: None,
             config_form: ConfigForm::from_config(config_snapshot.clone()),
             write_output_artifacts,
             openai_model,
-            quiz_first_attempts: HashSet::new(),
+            quiz_first_attempts: HashMap::new(),
+            learning_started_at: None,
+            learning_retry: false,
+            learning_question_shown_at: None,
+            question_marks,
+            challenge: None,
             analytics_snapshot: None,
             analytics_error: None,
             analytics_refreshed_at: None,
+            topics: TopicsState::default(),
+            history: HistoryState::default(),
+            bookmarks: BookmarksState::default(),
+            leaderboard: LeaderboardState::default(),
+            results: ResultsState::default(),
         };
 
         app.apply_session_load(session_load);
@@ -317,6 +445,8 @@ impl App {
                 _ => {}
             }
             poll_ai_messages(self);
+            poll_grading_messages(self);
+            poll_followup_messages(self);
         } else {
             self.on_tick();
         }
@@ -329,10 +459,30 @@ impl App {
             self.update_loading_status();
         }
         poll_ai_messages(self);
+        poll_grading_messages(self);
+        poll_followup_messages(self);
+        if self.challenge.is_some() && matches!(self.view, AppView::Learning) {
+            LearningManager::new(self).on_challenge_tick();
+        }
     }
 
     /// Handles the key events and updates the state of [`App`].
     fn on_key_event(&mut self, key: KeyEvent) {
+        if self.is_capturing_text() {
+            if key.modifiers == KeyModifiers::CONTROL
+                && matches!(key.code, KeyCode::Char('c') | KeyCode::Char('C'))
+            {
+                self.quit();
+            } else if matches!(self.view, AppView::Config) {
+                ConfigManager::new(self).handle_key(key);
+            } else if matches!(self.view, AppView::Learning) {
+                LearningManager::new(self).handle_key(key);
+            } else if matches!(self.view, AppView::Topics) {
+                TopicsManager::new(self).handle_key(key);
+            }
+            return;
+        }
+
         match (key.modifiers, key.code) {
             (_, KeyCode::Esc | KeyCode::Char('q'))
             | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
@@ -342,10 +492,29 @@ impl App {
                 AppView::Learning => LearningManager::new(self).handle_key(key),
                 AppView::Config => ConfigManager::new(self).handle_key(key),
                 AppView::Analytics => AnalyticsManager::new(self).handle_key(key),
+                AppView::Topics => TopicsManager::new(self).handle_key(key),
+                AppView::History => HistoryManager::new(self).handle_key(key),
+                AppView::Bookmarks => BookmarksManager::new(self).handle_key(key),
+                AppView::Leaderboard => LeaderboardManager::new(self).handle_key(key),
+                AppView::Results => ResultsManager::new(self).handle_key(key),
             },
         }
     }
 
+    /// Whether a text field currently owns the keyboard, so Esc and q must not quit.
+    fn is_capturing_text(&self) -> bool {
+        match self.view {
+            AppView::Config => self.config_form.is_editing_text(),
+            AppView::Learning => {
+                self.learning_text_input.is_some()
+                    || self.learning_flag_note.is_some()
+                    || self.followup.is_some()
+            }
+            AppView::Topics => self.topics.rename_buffer.is_some(),
+            _ => false,
+        }
+    }
+
     pub(crate) fn return_to_menu(&mut self) {
         if matches!(self.view, AppView::Config) {
             self.config_form = ConfigForm::from_config(config::current());
@@ -353,6 +522,12 @@ impl App {
         self.view = AppView::Menu;
     }
 
+    /// Close the follow-up panel, dropping any reply still on its way.
+    pub(crate) fn close_followup(&mut self) {
+        self.followup = None;
+        self.followup_receiver = None;
+    }
+
     /// Set running to false to quit the application.
     fn quit(&mut self) {
         self.running = false;
@@ -368,18 +543,48 @@ impl App {
         }
     }
 
-    pub(crate) fn record_quiz_first_attempt(
+    /// Milliseconds since the given question was shown, if it is the one on screen.
+    pub(crate) fn question_elapsed_ms(
+        &self,
+        group_index: usize,
+        question_index: usize,
+    ) -> Option<u64> {
+        self.learning_question_shown_at
+            .filter(|(shown, _)| *shown == (group_index, question_index))
+            .map(|(_, shown_at)| shown_at.elapsed().as_millis() as u64)
+    }
+
+    /// `answer` completed with what the app knows about the attempt: the question's difficulty,
+    /// whether the lesson is a retry, and how long the question has been on screen.
+    pub(crate) fn attempt_answer(
+        &self,
+        group_index: usize,
+        question_index: usize,
+        question: &ai_manager::QuizItem,
+        answer: &knowledge_store::AttemptAnswer,
+    ) -> knowledge_store::AttemptAnswer {
+        knowledge_store::AttemptAnswer {
+            difficulty: Some(question.difficulty),
+            retry: self.learning_retry,
+            elapsed_ms: answer
+                .elapsed_ms
+                .or_else(|| self.question_elapsed_ms(group_index, question_index)),
+            ..answer.clone()
+        }
+    }
+
+    /// Log an answer attempt for a quiz question. Every attempt is kept; the first one per
+    /// question drives first-try statistics.
+    pub(crate) fn record_quiz_attempt(
         &mut self,
         group_index: usize,
         question_index: usize,
         correct: bool,
+        answer: &knowledge_store::AttemptAnswer,
     ) {
-        if !self
-            .quiz_first_attempts
-            .insert((group_index, question_index))
-        {
-            return;
-        }
+        self.quiz_first_attempts
+            .entry((group_index, question_index))
+            .or_insert(correct);
 
         if !self.write_output_artifacts {
             crate::log_util::log_debug(
@@ -415,16 +620,17 @@ impl App {
             Some(group.knowledge_type_language.as_str())
         };
 
-        match crate::knowledge_store::record_quiz_first_attempt(
+        match crate::knowledge_store::record_quiz_attempt(
             &self.session_date,
             &group.knowledge_type_group,
             language,
-            &question.question,
+            question,
             correct,
+            &self.attempt_answer(group_index, question_index, question, answer),
         ) {
             Ok(_) => {
                 crate::log_util::log_debug(&format!(
-                    "App: recorded first attempt for '{}' (correct: {})",
+                    "App: recorded attempt for '{}' (correct: {})",
                     question.question, correct
                 ));
                 self.analytics_snapshot = None;
@@ -70,10 +70,16 @@ impl OutputManager {
         let selected_events = rules.select_events(events);
         for event in &selected_events {
             had_content = true;
-            document.push_str(&format!(
-                "## {} - {}\n\n",
-                event.timestamp, event.payload_type
-            ));
+            match event.call_id.as_deref() {
+                Some(call_id) => document.push_str(&format!(
+                    "## {} - {} (call_id: {})\n\n",
+                    event.timestamp, event.payload_type, call_id
+                )),
+                None => document.push_str(&format!(
+                    "## {} - {}\n\n",
+                    event.timestamp, event.payload_type
+                )),
+            }
             for text in &event.content_texts {
                 document.push_str(text);
                 document.push_str("\n\n");
@@ -0,0 +1,554 @@
+use color_eyre::eyre::{Result, eyre};
+use std::{fs, io, path::PathBuf};
+
+use crate::{
+    config::{self, AppConfig},
+    question_history::{self, LearnerHistory},
+};
+
+/// File in the config directory that overrides the system prompt.
+pub const SYSTEM_TEMPLATE_FILENAME: &str = "system_prompt.md";
+/// File in the config directory that overrides the user prompt.
+pub const USER_TEMPLATE_FILENAME: &str = "user_prompt.md";
+
+/// Placeholders that templates may reference. Anything else in `{UPPER_CASE}` form is rejected.
+pub const TEMPLATE_VARIABLES: [&str; 7] = [
+    "MIN_QUIZ_QUESTIONS",
+    "LANGUAGE_FOCUS",
+    "LEVEL",
+    "MASTERED_CONCEPTS",
+    "DIFFICULTY",
+    "SUMMARY",
+    "SCHEMA",
+];
+
+const SYSTEM_REQUIRED_VARIABLES: [&str; 1] = ["MIN_QUIZ_QUESTIONS"];
+const USER_REQUIRED_VARIABLES: [&str; 2] = ["SUMMARY", "SCHEMA"];
+
+const DEFAULT_SYSTEM_TEMPLATE: &str = r#"You are a precise curriculum planner that helps the student learn about coding concepts.
+You will produce a quiz that will teach the user about a coding concept based on the provided context.
+You should base each quiz item on the provided context to help the student learn new language features or concepts.
+All context examples include bash scripts. The contents of the bash updates are what should be considered for quiz updates.
+Example full bash script json:
+```
+{'command':['bash','-lc','apply_patch <<'PATCH'
+*** Begin Patch
+*** Update File: src/ai_manager.rs
+@@
+-    #[serde(default)]
+-    pub knowledge_type_language: Option<String>,
++    #[serde(default)]
++    pub knowledge_type_language: String,
+ }
+*** End Patch
+PATCH
+'],'workdir':'/Users/davidnorman/learnchain'}
+```
+Example subset of what should actually be considered for learning content:
+```
+Update File: src/ai_manager.rs
+@@
+-    #[serde(default)]
+-    pub knowledge_type_language: Option<String>,
++    #[serde(default)]
++    pub knowledge_type_language: String,
+ }
+```
+All questions should be language specific and should not quiz based on implementation of the specific program.
+Learner focus: {LANGUAGE_FOCUS}
+Learner level: {LEVEL}
+Pitch each question at the learner's level for its language.
+Already mastered: {MASTERED_CONCEPTS}
+Difficulty: {DIFFICULTY}
+You should return a minimum of {MIN_QUIZ_QUESTIONS} quiz questions.
+Return JSON that strictly matches the provided schema."#;
+
+const DEFAULT_USER_TEMPLATE: &str = "Analyse the following session summary and produce a JSON payload that adheres to the provided schema. Return only valid JSON with double-quoted keys and strings.\n\nSchema:\n```json\n{SCHEMA}\n```\n\nSession summary:\n```markdown\n{SUMMARY}\n```";
+
+/// Which of the two prompt templates is being handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateKind {
+    System,
+    User,
+}
+
+impl TemplateKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::System => "system prompt",
+            Self::User => "user prompt",
+        }
+    }
+
+    fn filename(self) -> &'static str {
+        match self {
+            Self::System => SYSTEM_TEMPLATE_FILENAME,
+            Self::User => USER_TEMPLATE_FILENAME,
+        }
+    }
+
+    fn default_template(self) -> &'static str {
+        match self {
+            Self::System => DEFAULT_SYSTEM_TEMPLATE,
+            Self::User => DEFAULT_USER_TEMPLATE,
+        }
+    }
+
+    fn required_variables(self) -> &'static [&'static str] {
+        match self {
+            Self::System => &SYSTEM_REQUIRED_VARIABLES,
+            Self::User => &USER_REQUIRED_VARIABLES,
+        }
+    }
+}
+
+/// Values substituted into the prompt templates.
+#[derive(Debug, Clone)]
+pub struct PromptVariables {
+    pub min_quiz_questions: usize,
+    pub language_focus: String,
+    pub level: String,
+    pub mastered_concepts: String,
+    pub difficulty: String,
+    pub summary: String,
+    pub schema: String,
+}
+
+impl PromptVariables {
+    pub fn from_config(
+        config: &AppConfig,
+        summary: &str,
+        schema: &str,
+        history: &LearnerHistory,
+    ) -> Self {
+        Self {
+            min_quiz_questions: config.min_quiz_questions,
+            language_focus: describe_language_focus(config),
+            level: describe_level(config),
+            mastered_concepts: describe_mastered_concepts(&history.mastered_concepts),
+            difficulty: describe_difficulty(history),
+            summary: summary.to_string(),
+            schema: schema.to_string(),
+        }
+    }
+
+    fn value_for(&self, name: &str) -> Option<String> {
+        match name {
+            "MIN_QUIZ_QUESTIONS" => Some(self.min_quiz_questions.to_string()),
+            "LANGUAGE_FOCUS" => Some(self.language_focus.clone()),
+            "LEVEL" => Some(self.level.clone()),
+            "MASTERED_CONCEPTS" => Some(self.mastered_concepts.clone()),
+            "DIFFICULTY" => Some(self.difficulty.clone()),
+            "SUMMARY" => Some(self.summary.clone()),
+            "SCHEMA" => Some(self.schema.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Describe the learner's language focus, ignored languages, and skipped topics for the prompt.
+fn describe_language_focus(config: &AppConfig) -> String {
+    let mut parts = Vec::new();
+    if !config.focus_languages.is_empty() {
+        parts.push(format!(
+            "Prioritise questions about {}.",
+            config.focus_languages.join(", ")
+        ));
+    }
+    if !config.ignored_languages.is_empty() {
+        parts.push(format!(
+            "Do not write questions about {}.",
+            config.ignored_languages.join(", ")
+        ));
+    }
+    if !config.skip_topics.is_empty() {
+        parts.push(format!(
+            "Skip these topics entirely: {}.",
+            config.skip_topics.join(", ")
+        ));
+    }
+    if parts.is_empty() {
+        "No language preference; infer languages from the session.".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Describe the learner's self-reported proficiency per language for the prompt.
+fn describe_level(config: &AppConfig) -> String {
+    if config.language_proficiency.is_empty() {
+        return "No proficiency level specified; assume an intermediate developer.".to_string();
+    }
+    let levels: Vec<String> = config
+        .language_proficiency
+        .iter()
+        .map(|(language, level)| format!("{}: {}", language, level.label()))
+        .collect();
+    format!(
+        "{}. Assume intermediate for languages not listed.",
+        levels.join("; ")
+    )
+}
+
+/// List the concepts the learner has already mastered so the model can steer away from them.
+fn describe_mastered_concepts(mastered_concepts: &[String]) -> String {
+    if mastered_concepts.is_empty() {
+        return "Nothing yet.".to_string();
+    }
+    format!(
+        "{}. Avoid questions on these concepts and pick other ideas from the session.",
+        mastered_concepts.join(", ")
+    )
+}
+
+/// Ask for harder questions in groups the learner aces and easier ones where they struggle.
+/// Mastered groups are left out: the prompt already asks to avoid them, and mastery needs
+/// more evidence than the harder band so aced groups are pitched hard first.
+fn describe_difficulty(history: &LearnerHistory) -> String {
+    let mut parts = Vec::new();
+    for accuracy in &history.group_accuracy {
+        if history
+            .mastered_concepts
+            .contains(&accuracy.knowledge_type_group)
+        {
+            continue;
+        }
+        let Some(difficulty) = question_history::target_difficulty(accuracy) else {
+            continue;
+        };
+        parts.push(format!(
+            "{} questions for {} ({:.0}% first-try over {})",
+            difficulty.as_str(),
+            accuracy.knowledge_type_group,
+            accuracy.accuracy() * 100.0,
+            accuracy.attempts
+        ));
+    }
+    if parts.is_empty() {
+        return "No accuracy history yet; mix easy, medium and hard questions and rate each one."
+            .to_string();
+    }
+    format!(
+        "Write {}. Use medium for other groups and rate each question.",
+        parts.join("; ")
+    )
+}
+
+/// Final system and user messages sent to the model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedPrompt {
+    pub system: String,
+    pub user: String,
+}
+
+/// Render both prompts for the given config and session summary, honouring any template overrides.
+pub fn render_prompt(
+    config: &AppConfig,
+    summary: &str,
+    schema: &str,
+    history: &LearnerHistory,
+) -> Result<RenderedPrompt> {
+    let variables = PromptVariables::from_config(config, summary, schema, history);
+    Ok(RenderedPrompt {
+        system: render_template(&load_template(TemplateKind::System)?, &variables),
+        user: render_template(&load_template(TemplateKind::User)?, &variables),
+    })
+}
+
+/// Path of the override file for `kind`, next to the main configuration file.
+pub fn template_path(kind: TemplateKind) -> PathBuf {
+    let config_path = config::config_file_path();
+    match config_path.parent() {
+        Some(dir) => dir.join(kind.filename()),
+        None => PathBuf::from(kind.filename()),
+    }
+}
+
+/// Load the override template for `kind` if present, otherwise the built-in default.
+pub fn load_template(kind: TemplateKind) -> Result<String> {
+    let path = template_path(kind);
+    match fs::read_to_string(&path) {
+        Ok(contents) => {
+            validate_template(kind, &contents).map_err(|err| {
+                eyre!(
+                    "invalid {} template at {}: {}",
+                    kind.label(),
+                    path.display(),
+                    err
+                )
+            })?;
+            Ok(contents)
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            Ok(kind.default_template().to_string())
+        }
+        Err(err) => Err(eyre!(
+            "failed to read {} template at {}: {}",
+            kind.label(),
+            path.display(),
+            err
+        )),
+    }
+}
+
+/// Check that a template contains its required placeholders and no unknown ones.
+pub fn validate_template(kind: TemplateKind, template: &str) -> Result<()> {
+    let placeholders = find_placeholders(template);
+    let missing: Vec<&str> = kind
+        .required_variables()
+        .iter()
+        .copied()
+        .filter(|name| !placeholders.iter().any(|found| found == name))
+        .collect();
+    let unknown: Vec<&str> = placeholders
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !TEMPLATE_VARIABLES.contains(name))
+        .collect();
+
+    let mut problems = Vec::new();
+    if !missing.is_empty() {
+        problems.push(format!(
+            "missing required placeholder(s) {}",
+            format_placeholders(&missing)
+        ));
+    }
+    if !unknown.is_empty() {
+        problems.push(format!(
+            "unknown placeholder(s) {} (supported: {})",
+            format_placeholders(&unknown),
+            format_placeholders(&TEMPLATE_VARIABLES)
+        ));
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(eyre!(problems.join("; ")))
+    }
+}
+
+fn render_template(template: &str, variables: &PromptVariables) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let candidate = &rest[start..];
+        match placeholder_at(candidate).and_then(|name| {
+            variables
+                .value_for(name)
+                .map(|value| (value, name.len() + 2))
+        }) {
+            Some((value, consumed)) => {
+                rendered.push_str(&value);
+                rest = &candidate[consumed..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &candidate[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Collect every `{UPPER_CASE}` placeholder in the template, in order of appearance.
+fn find_placeholders(template: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let candidate = &rest[start..];
+        if let Some(name) = placeholder_at(candidate) {
+            if !found.iter().any(|existing| existing == name) {
+                found.push(name.to_string());
+            }
+            rest = &candidate[name.len() + 2..];
+        } else {
+            rest = &candidate[1..];
+        }
+    }
+    found
+}
+
+/// If `text` starts with `{NAME}` where NAME is upper-case letters and underscores, return NAME.
+fn placeholder_at(text: &str) -> Option<&str> {
+    let inner = text.strip_prefix('{')?;
+    let end = inner.find('}')?;
+    let name = &inner[..end];
+    let valid = !name.is_empty()
+        && name.chars().all(|ch| ch.is_ascii_uppercase() || ch == '_')
+        && name.chars().any(|ch| ch.is_ascii_uppercase());
+    valid.then_some(name)
+}
+
+fn format_placeholders(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|name| format!("{{{}}}", name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ai_manager::QuizItem,
+        knowledge_store::{self, GroupAccuracy},
+    };
+
+    fn variables() -> PromptVariables {
+        PromptVariables {
+            min_quiz_questions: 7,
+            language_focus: "Rust".to_string(),
+            level: "beginner".to_string(),
+            mastered_concepts: "Shell navigation.".to_string(),
+            difficulty: "Write hard questions for Git.".to_string(),
+            summary: "did things".to_string(),
+            schema: "{}".to_string(),
+        }
+    }
+
+    #[test]
+    fn default_templates_are_valid() {
+        validate_template(TemplateKind::System, DEFAULT_SYSTEM_TEMPLATE).unwrap();
+        validate_template(TemplateKind::User, DEFAULT_USER_TEMPLATE).unwrap();
+    }
+
+    #[test]
+    fn render_substitutes_known_placeholders_and_keeps_other_braces() {
+        let rendered = render_template(
+            "At least {MIN_QUIZ_QUESTIONS} for {LEVEL} {'command': 1} {lower} {SUMMARY}",
+            &variables(),
+        );
+        assert_eq!(
+            rendered,
+            "At least 7 for beginner {'command': 1} {lower} did things"
+        );
+
+        let system = render_template(DEFAULT_SYSTEM_TEMPLATE, &variables());
+        assert!(system.contains("a minimum of 7 quiz questions"));
+        assert!(system.contains("{'command':['bash'"));
+    }
+
+    #[test]
+    fn learner_profile_is_described_in_prompt_variables() {
+        let mut config = AppConfig::default();
+        let neutral = PromptVariables::from_config(&config, "", "", &LearnerHistory::default());
+        assert!(neutral.language_focus.contains("No language preference"));
+        assert!(neutral.level.contains("No proficiency level"));
+
+        config
+            .language_proficiency
+            .insert("Rust".to_string(), config::ProficiencyLevel::Beginner);
+        config
+            .language_proficiency
+            .insert("Python".to_string(), config::ProficiencyLevel::Advanced);
+        config.focus_languages = vec!["Rust".to_string()];
+        config.ignored_languages = vec!["Shell".to_string()];
+        config.skip_topics = vec!["lifetimes".to_string(), "macros".to_string()];
+
+        let history = LearnerHistory {
+            mastered_concepts: vec!["Shell navigation".to_string()],
+            group_accuracy: vec![
+                GroupAccuracy {
+                    knowledge_type_group: "Git".to_string(),
+                    attempts: 10,
+                    first_try_correct: 9,
+                },
+                GroupAccuracy {
+                    knowledge_type_group: "Rust".to_string(),
+                    attempts: 4,
+                    first_try_correct: 1,
+                },
+                GroupAccuracy {
+                    knowledge_type_group: "Docker".to_string(),
+                    attempts: 2,
+                    first_try_correct: 0,
+                },
+            ],
+            ..Default::default()
+        };
+        let variables = PromptVariables::from_config(&config, "", "", &history);
+        assert_eq!(
+            variables.language_focus,
+            "Prioritise questions about Rust. Do not write questions about Shell. Skip these topics entirely: lifetimes, macros."
+        );
+        assert_eq!(
+            variables.level,
+            "Python: advanced; Rust: beginner. Assume intermediate for languages not listed."
+        );
+
+        let system = render_template(DEFAULT_SYSTEM_TEMPLATE, &variables);
+        assert!(system.contains("Learner level: Python: advanced; Rust: beginner."));
+        assert!(system.contains("Already mastered: Shell navigation. Avoid questions"));
+        assert_eq!(
+            variables.difficulty,
+            "Write hard questions for Git (90% first-try over 10); easy questions for Rust (25% first-try over 4). Use medium for other groups and rate each question."
+        );
+        assert!(neutral.difficulty.starts_with("No accuracy history yet"));
+    }
+
+    #[test]
+    fn groups_are_made_harder_before_they_count_as_mastered() {
+        let mut temp_dir = std::env::temp_dir();
+        temp_dir.push(format!(
+            "learnchain-difficulty-bands-{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap()
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.sqlite");
+        let attempts = [("Shell", 3, 3), ("Git", 5, 5), ("Rust", 4, 1)];
+        for (group, total, correct) in attempts {
+            for index in 0..total {
+                knowledge_store::record_quiz_attempt_at_path(
+                    &db_path,
+                    "2024-05-01",
+                    group,
+                    None,
+                    &QuizItem {
+                        question: format!("{} question {}", group, index),
+                        ..Default::default()
+                    },
+                    index < correct,
+                    &knowledge_store::AttemptAnswer::default(),
+                )
+                .unwrap();
+            }
+        }
+        let history = LearnerHistory {
+            mastered_concepts: knowledge_store::load_mastered_concepts_at_path(&db_path).unwrap(),
+            group_accuracy: knowledge_store::load_group_accuracy_at_path(&db_path).unwrap(),
+            ..Default::default()
+        };
+        let variables = PromptVariables::from_config(&AppConfig::default(), "", "", &history);
+        let system = render_template(DEFAULT_SYSTEM_TEMPLATE, &variables);
+
+        assert!(system.contains("hard questions for Shell (100% first-try over 3)"));
+        assert!(system.contains("Already mastered: Git. Avoid questions"));
+        assert!(!variables.difficulty.contains("Git"));
+        assert_eq!(
+            variables.difficulty,
+            "Write easy questions for Rust (25% first-try over 4); hard questions for Shell (100% first-try over 3). Use medium for other groups and rate each question."
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn validation_reports_missing_and_unknown_placeholders() {
+        let err = validate_template(TemplateKind::User, "Only {SUMMARY} and {TOPIC}")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("missing required placeholder(s) {SCHEMA}"));
+        assert!(err.contains("unknown placeholder(s) {TOPIC}"));
+
+        assert!(validate_template(TemplateKind::System, "No placeholders here").is_err());
+        validate_template(
+            TemplateKind::System,
+            "{MIN_QUIZ_QUESTIONS} {LANGUAGE_FOCUS} {LEVEL}",
+        )
+        .unwrap();
+    }
+}
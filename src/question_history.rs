@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+
+use crate::{
+    ai_manager::QuizDifficulty,
+    knowledge_store::{self, GroupAccuracy},
+    log_util::log_debug,
+};
+
+/// Trigram similarity at or above which two questions count as the same question.
+pub const DUPLICATE_SIMILARITY: f64 = 0.8;
+/// First attempts needed on a knowledge group before its difficulty is adjusted.
+pub const ADAPTIVE_MIN_ATTEMPTS: usize = 3;
+/// First-try accuracy at or above which a group gets harder questions.
+const HARDER_ACCURACY: f64 = 0.8;
+/// First attempts needed on a knowledge group before it can count as mastered. Mastery asks
+/// for more evidence than the harder band, so groups are pitched hard before being avoided.
+pub const MASTERY_MIN_ATTEMPTS: usize = 5;
+/// First-try accuracy at or above which a knowledge group counts as mastered.
+pub const MASTERY_MIN_ACCURACY: f64 = 0.9;
+/// First-try accuracy below which a group gets easier questions.
+const EASIER_ACCURACY: f64 = 0.5;
+
+/// What the learner has already seen, used to steer generation away from repeats
+/// and to pitch each knowledge group at the right difficulty.
+#[derive(Debug, Clone, Default)]
+pub struct LearnerHistory {
+    pub mastered_concepts: Vec<String>,
+    pub past_questions: QuestionHistory,
+    pub group_accuracy: Vec<GroupAccuracy>,
+}
+
+impl LearnerHistory {
+    /// Load the history from the knowledge store. Failures are logged and leave that part empty.
+    pub fn load() -> Self {
+        let mastered_concepts = knowledge_store::load_mastered_concepts().unwrap_or_else(|err| {
+            log_debug(&format!("App: failed to load mastered concepts: {}", err));
+            Vec::new()
+        });
+        let past_questions = knowledge_store::load_past_questions().unwrap_or_else(|err| {
+            log_debug(&format!("App: failed to load past questions: {}", err));
+            Vec::new()
+        });
+        let group_accuracy = knowledge_store::load_group_accuracy().unwrap_or_else(|err| {
+            log_debug(&format!("App: failed to load group accuracy: {}", err));
+            Vec::new()
+        });
+        log_debug(&format!(
+            "App: loaded {} past question(s), {} mastered concept(s), accuracy for {} group(s)",
+            past_questions.len(),
+            mastered_concepts.len(),
+            group_accuracy.len()
+        ));
+        Self {
+            mastered_concepts,
+            past_questions: QuestionHistory::new(past_questions),
+            group_accuracy,
+        }
+    }
+}
+
+/// Difficulty to request next for a group, or `None` until it has enough attempts.
+pub fn target_difficulty(accuracy: &GroupAccuracy) -> Option<QuizDifficulty> {
+    if accuracy.attempts < ADAPTIVE_MIN_ATTEMPTS {
+        return None;
+    }
+    let ratio = accuracy.accuracy();
+    Some(if ratio >= HARDER_ACCURACY {
+        QuizDifficulty::Hard
+    } else if ratio < EASIER_ACCURACY {
+        QuizDifficulty::Easy
+    } else {
+        QuizDifficulty::Medium
+    })
+}
+
+/// Previously asked questions, indexed for near-duplicate lookups.
+#[derive(Debug, Clone, Default)]
+pub struct QuestionHistory {
+    entries: Vec<HistoryEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    question: String,
+    normalized: String,
+    trigrams: HashSet<String>,
+}
+
+impl QuestionHistory {
+    pub fn new<I, S>(questions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut history = Self::default();
+        for question in questions {
+            history.insert(question.as_ref());
+        }
+        history
+    }
+
+    pub fn insert(&mut self, question: &str) {
+        let normalized = normalize_text(question);
+        if normalized.is_empty() {
+            return;
+        }
+        self.entries.push(HistoryEntry {
+            question: question.trim().to_string(),
+            trigrams: trigrams(&normalized),
+            normalized,
+        });
+    }
+
+    /// The earlier question that `question` repeats, if any.
+    pub fn find_duplicate(&self, question: &str) -> Option<&str> {
+        let normalized = normalize_text(question);
+        if normalized.is_empty() {
+            return None;
+        }
+        let candidate = trigrams(&normalized);
+        self.entries
+            .iter()
+            .find(|entry| {
+                entry.normalized == normalized
+                    || jaccard(&entry.trigrams, &candidate) >= DUPLICATE_SIMILARITY
+            })
+            .map(|entry| entry.question.as_str())
+    }
+}
+
+/// Trigram similarity of two short texts after normalisation, from 0.0 to 1.0.
+pub fn similarity(left: &str, right: &str) -> f64 {
+    let left = normalize_text(left);
+    let right = normalize_text(right);
+    if left.is_empty() || right.is_empty() {
+        return 0.0;
+    }
+    if left == right {
+        return 1.0;
+    }
+    jaccard(&trigrams(&left), &trigrams(&right))
+}
+
+/// Lower-case the text and reduce punctuation and runs of whitespace to single spaces.
+pub fn normalize_text(text: &str) -> String {
+    text.chars()
+        .map(|ch| {
+            if ch.is_alphanumeric() {
+                ch.to_ascii_lowercase()
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn trigrams(normalized: &str) -> HashSet<String> {
+    let padded: Vec<char> = format!("  {} ", normalized).chars().collect();
+    padded
+        .windows(3)
+        .map(|window| window.iter().collect())
+        .collect()
+}
+
+fn jaccard(left: &HashSet<String>, right: &HashSet<String>) -> f64 {
+    let union = left.union(right).count();
+    if union == 0 {
+        return 0.0;
+    }
+    left.intersection(right).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn near_duplicates_are_found_and_different_questions_are_not() {
+        let history = QuestionHistory::new([
+            "What does `cd` do?",
+            "Which keyword declares a mutable binding in Rust?",
+        ]);
+
+        assert_eq!(
+            history.find_duplicate("what does cd do"),
+            Some("What does `cd` do?")
+        );
+        assert_eq!(
+            history.find_duplicate("Which keyword declares a mutable binding in Rust ?"),
+            Some("Which keyword declares a mutable binding in Rust?")
+        );
+        assert!(
+            history
+                .find_duplicate("What does `ls -la` print?")
+                .is_none()
+        );
+        assert!(history.find_duplicate("   ").is_none());
+    }
+}
@@ -0,0 +1,514 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{Context, Result, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ai_manager::{QuizDifficulty, QuizItem, QuizKind},
+    knowledge_store::{
+        self, PORTABLE_FORMAT_VERSION, PortableAttempt, PortableResponse, PortableStore,
+        StoredResponse,
+    },
+    output_manager::OutputManager,
+};
+
+/// Folder inside the output directory that exports are written to by default.
+const EXPORT_DIRECTORY: &str = "exports";
+
+/// Header lines telling Anki how to read the file: tab separated HTML fields, the question
+/// id as note GUID so re-importing updates notes instead of duplicating them, and tags last.
+const ANKI_HEADER: &str = "#separator:tab\n#html:true\n#guid column:1\n#tags column:4\n";
+
+/// File formats that carry the whole knowledge store between machines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortableFormat {
+    Json,
+    Csv,
+}
+
+impl PortableFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+
+    /// Format implied by a file's extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Self::parse)
+    }
+}
+
+/// One CSV row. Response rows carry their quiz as JSON in `quiz_json`; attempt rows fill the
+/// attempt columns instead. `record` says which kind of row it is.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CsvRecord {
+    record: String,
+    session_date: String,
+    recorded_at: String,
+    knowledge_type_group: String,
+    knowledge_type_language: Option<String>,
+    summary: Option<String>,
+    quiz_json: Option<String>,
+    question: Option<String>,
+    question_id: Option<String>,
+    attempt_number: Option<u32>,
+    selected_answer: Option<String>,
+    correct: Option<bool>,
+    elapsed_ms: Option<u64>,
+    score: Option<u8>,
+    difficulty: Option<QuizDifficulty>,
+}
+
+const CSV_RESPONSE: &str = "response";
+const CSV_ATTEMPT: &str = "attempt";
+
+/// Write `store` as JSON or CSV. Without a `path` the file goes to `output/exports/`.
+pub fn write_portable_store(
+    store: &PortableStore,
+    format: PortableFormat,
+    path: Option<&Path>,
+    label: &str,
+) -> Result<PathBuf> {
+    let contents = match format {
+        PortableFormat::Json => serde_json::to_string_pretty(store)
+            .wrap_err("failed to serialise knowledge store export")?,
+        PortableFormat::Csv => render_csv(store)?,
+    };
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => default_export_path(&format!("store-{}", label), format.extension())?,
+    };
+    write_export_file(&path, &contents)?;
+    Ok(path)
+}
+
+/// Read a JSON or CSV export, choosing the format from the file extension.
+pub fn read_portable_store(path: &Path) -> Result<PortableStore> {
+    let format = PortableFormat::from_path(path).ok_or_else(|| {
+        eyre!(
+            "cannot tell the format of {}; use a .json or .csv file",
+            path.display()
+        )
+    })?;
+    let contents =
+        fs::read_to_string(path).wrap_err_with(|| format!("failed to read {}", path.display()))?;
+    match format {
+        PortableFormat::Json => serde_json::from_str(&contents)
+            .wrap_err_with(|| format!("{} is not a learnchain JSON export", path.display())),
+        PortableFormat::Csv => parse_csv(&contents)
+            .wrap_err_with(|| format!("{} is not a learnchain CSV export", path.display())),
+    }
+}
+
+fn render_csv(store: &PortableStore) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for response in &store.responses {
+        writer
+            .serialize(CsvRecord {
+                record: CSV_RESPONSE.to_string(),
+                session_date: response.session_date.clone(),
+                recorded_at: response.recorded_at.clone(),
+                knowledge_type_group: response.knowledge_type_group.clone(),
+                knowledge_type_language: Some(response.knowledge_type_language.clone()),
+                summary: Some(response.summary.clone()),
+                quiz_json: Some(
+                    serde_json::to_string(&response.quiz)
+                        .wrap_err("failed to serialise quiz for CSV export")?,
+                ),
+                ..Default::default()
+            })
+            .wrap_err("failed to write CSV response row")?;
+    }
+    for attempt in &store.attempts {
+        writer
+            .serialize(CsvRecord {
+                record: CSV_ATTEMPT.to_string(),
+                session_date: attempt.session_date.clone(),
+                recorded_at: attempt.recorded_at.clone(),
+                knowledge_type_group: attempt.knowledge_type_group.clone(),
+                knowledge_type_language: attempt.knowledge_type_language.clone(),
+                question: Some(attempt.question.clone()),
+                question_id: attempt.question_id.clone(),
+                attempt_number: Some(attempt.attempt_number),
+                selected_answer: attempt.selected_answer.clone(),
+                correct: Some(attempt.correct),
+                elapsed_ms: attempt.elapsed_ms,
+                score: attempt.score,
+                difficulty: attempt.difficulty,
+                ..Default::default()
+            })
+            .wrap_err("failed to write CSV attempt row")?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|err| eyre!("failed to finish CSV export: {}", err))?;
+    String::from_utf8(bytes).wrap_err("CSV export is not valid UTF-8")
+}
+
+fn parse_csv(contents: &str) -> Result<PortableStore> {
+    let mut store = PortableStore {
+        version: PORTABLE_FORMAT_VERSION,
+        ..Default::default()
+    };
+    let mut reader = csv::Reader::from_reader(contents.as_bytes());
+    for (index, row) in reader.deserialize::<CsvRecord>().enumerate() {
+        let row = row.wrap_err_with(|| format!("invalid CSV row {}", index + 2))?;
+        match row.record.as_str() {
+            CSV_RESPONSE => store.responses.push(PortableResponse {
+                session_date: row.session_date,
+                recorded_at: row.recorded_at,
+                knowledge_type_group: row.knowledge_type_group,
+                knowledge_type_language: row.knowledge_type_language.unwrap_or_default(),
+                summary: row.summary.unwrap_or_default(),
+                quiz: serde_json::from_str(row.quiz_json.as_deref().unwrap_or("[]"))
+                    .wrap_err_with(|| format!("invalid quiz_json in CSV row {}", index + 2))?,
+            }),
+            CSV_ATTEMPT => store.attempts.push(PortableAttempt {
+                session_date: row.session_date,
+                recorded_at: row.recorded_at,
+                knowledge_type_group: row.knowledge_type_group,
+                knowledge_type_language: row.knowledge_type_language,
+                question: row.question.unwrap_or_default(),
+                question_id: row.question_id,
+                attempt_number: row.attempt_number.unwrap_or(1),
+                selected_answer: row.selected_answer,
+                correct: row.correct.unwrap_or(false),
+                elapsed_ms: row.elapsed_ms,
+                score: row.score,
+                difficulty: row.difficulty,
+            }),
+            other => {
+                return Err(eyre!(
+                    "unknown record type '{}' in CSV row {}",
+                    other,
+                    index + 2
+                ));
+            }
+        }
+    }
+    Ok(store)
+}
+
+/// Write the questions in `responses` as an Anki deck. Without a `path` the deck goes to
+/// `output/exports/`, named after `label`. Returns the file written and the note count.
+pub fn write_anki_deck(
+    responses: &[StoredResponse],
+    path: Option<&Path>,
+    label: &str,
+) -> Result<(PathBuf, usize)> {
+    let (deck, notes) = render_anki_deck(responses);
+    if notes == 0 {
+        return Err(eyre!("no quiz questions match the export filters"));
+    }
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => default_export_path(&format!("anki-{}", label), "txt")?,
+    };
+    write_export_file(&path, &deck)?;
+    Ok((path, notes))
+}
+
+fn write_export_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).wrap_err_with(|| {
+            format!("failed to create export directory at {}", parent.display())
+        })?;
+    }
+    fs::write(path, contents)
+        .wrap_err_with(|| format!("failed to write export to {}", path.display()))
+}
+
+/// Render an Anki plain-text import with one note per distinct question.
+pub fn render_anki_deck(responses: &[StoredResponse]) -> (String, usize) {
+    let mut deck = ANKI_HEADER.to_string();
+    let mut seen = HashSet::new();
+    for stored in responses {
+        let tags = anki_tags(stored);
+        for item in &stored.response.quiz {
+            let id = knowledge_store::question_id(item);
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            deck.push_str(&format!(
+                "{}\t{}\t{}\t{}\n",
+                id,
+                anki_front(item),
+                anki_back(item, &stored.response.summary),
+                tags
+            ));
+        }
+    }
+    (deck, seen.len())
+}
+
+/// An unused file name in `output/exports/` for an export called `name`.
+pub fn default_export_path(name: &str, extension: &str) -> Result<PathBuf> {
+    let output_dir = OutputManager::new()
+        .output_directory()
+        .map_err(|err| eyre!(err))?
+        .join(EXPORT_DIRECTORY);
+    let mut path = output_dir.join(format!("learnchain-{}.{}", name, extension));
+    let mut counter = 2;
+    while path.exists() {
+        path = output_dir.join(format!("learnchain-{}-{}.{}", name, counter, extension));
+        counter += 1;
+    }
+    Ok(path)
+}
+
+fn anki_front(item: &QuizItem) -> String {
+    let mut front = format!(
+        "{}<br><br><i>{}</i>",
+        html_escape(&item.question),
+        item.kind.label()
+    );
+    if !item.kind.is_typed_answer() {
+        for (index, option) in item.options.iter().enumerate() {
+            front.push_str(&format!(
+                "<br>{}. {}",
+                (b'A' + (index % 26) as u8) as char,
+                html_escape(&option.selection)
+            ));
+        }
+    }
+    front
+}
+
+fn anki_back(item: &QuizItem, summary: &str) -> String {
+    let answer = match item.kind {
+        QuizKind::FillInBlank => item
+            .accepted_answers
+            .iter()
+            .map(|answer| html_escape(answer))
+            .collect::<Vec<_>>()
+            .join(" / "),
+        QuizKind::FreeText => html_escape(&item.rubric),
+        QuizKind::OrderLines => {
+            let mut lines: Vec<(usize, &str)> = item
+                .options
+                .iter()
+                .enumerate()
+                .map(|(index, option)| {
+                    (option.position.unwrap_or(index), option.selection.as_str())
+                })
+                .collect();
+            lines.sort_by_key(|(position, _)| *position);
+            format!(
+                "<pre>{}</pre>",
+                lines
+                    .iter()
+                    .map(|(_, line)| html_escape(line))
+                    .collect::<Vec<_>>()
+                    .join("<br>")
+            )
+        }
+        QuizKind::MultipleChoice | QuizKind::TrueFalse | QuizKind::MultiSelect => item
+            .options
+            .iter()
+            .filter(|option| option.is_correct_answer)
+            .map(|option| {
+                let explanation = option.explanation.trim();
+                if explanation.is_empty() {
+                    format!("<b>{}</b>", html_escape(&option.selection))
+                } else {
+                    format!(
+                        "<b>{}</b> — {}",
+                        html_escape(&option.selection),
+                        html_escape(explanation)
+                    )
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("<br>"),
+    };
+    let mut back = answer;
+    if !summary.trim().is_empty() {
+        back.push_str(&format!("<hr>{}", html_escape(summary.trim())));
+    }
+    back
+}
+
+/// Space-separated Anki tags: the app, the knowledge group, the language and the session date.
+fn anki_tags(stored: &StoredResponse) -> String {
+    let mut tags = vec!["learnchain".to_string()];
+    for value in [
+        stored.response.knowledge_type_group.as_str(),
+        stored.response.knowledge_type_language.as_str(),
+        stored.session_date.as_str(),
+    ] {
+        let tag = value.split_whitespace().collect::<Vec<_>>().join("_");
+        if !tag.is_empty() {
+            tags.push(tag);
+        }
+    }
+    tags.join(" ")
+}
+
+/// Escape text for an HTML field. Tabs and newlines would break the note layout, so they
+/// become spaces and line breaks.
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\t', " ")
+        .replace("\r\n", "\n")
+        .replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_manager::{KnowledgeResponse, QuizOption};
+
+    fn stored(group: &str, quiz: Vec<QuizItem>) -> StoredResponse {
+        StoredResponse {
+            session_date: "2024-05-01".to_string(),
+            response: KnowledgeResponse {
+                knowledge_type_group: group.to_string(),
+                summary: "Borrowing <rules>".to_string(),
+                knowledge_type_language: "Rust".to_string(),
+                quiz,
+            },
+        }
+    }
+
+    #[test]
+    fn csv_export_round_trips_responses_and_attempts() {
+        let store = PortableStore {
+            version: PORTABLE_FORMAT_VERSION,
+            exported_at: "2024-05-02T08:00:00Z".to_string(),
+            responses: vec![PortableResponse {
+                session_date: "2024-05-01".to_string(),
+                recorded_at: "2024-05-01T10:00:00Z".to_string(),
+                knowledge_type_group: "Shell".to_string(),
+                knowledge_type_language: String::new(),
+                summary: "Quoting, \"commas\", and\nnewlines".to_string(),
+                quiz: vec![QuizItem {
+                    question: "What does `ls -a` show?".to_string(),
+                    ..Default::default()
+                }],
+            }],
+            attempts: vec![PortableAttempt {
+                session_date: "2024-05-01".to_string(),
+                recorded_at: "2024-05-01T10:01:00Z".to_string(),
+                knowledge_type_group: "Shell".to_string(),
+                question: "What does `ls -a` show?".to_string(),
+                question_id: Some("abc".to_string()),
+                attempt_number: 2,
+                selected_answer: Some("Hidden files, too".to_string()),
+                correct: true,
+                elapsed_ms: Some(1_500),
+                difficulty: Some(QuizDifficulty::Hard),
+                ..Default::default()
+            }],
+        };
+
+        let parsed = parse_csv(&render_csv(&store).unwrap()).unwrap();
+
+        assert_eq!(parsed.responses.len(), 1);
+        assert_eq!(parsed.responses[0].summary, store.responses[0].summary);
+        assert_eq!(
+            parsed.responses[0].quiz[0].question,
+            store.responses[0].quiz[0].question
+        );
+        assert_eq!(parsed.attempts, store.attempts);
+        assert!(
+            parse_csv("record,session_date,recorded_at,knowledge_type_group\nbogus,,,\n").is_err()
+        );
+    }
+
+    #[test]
+    fn anki_deck_has_one_tab_separated_note_per_distinct_question() {
+        let choice = QuizItem {
+            question: "Which\tkeyword borrows?".to_string(),
+            options: vec![
+                QuizOption {
+                    selection: "&".to_string(),
+                    is_correct_answer: true,
+                    explanation: "Takes a reference.".to_string(),
+                    ..Default::default()
+                },
+                QuizOption {
+                    selection: "move".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let blank = QuizItem {
+            question: "Declare a mutable binding: let ____ x = 1;".to_string(),
+            kind: QuizKind::FillInBlank,
+            accepted_answers: vec!["mut".to_string()],
+            ..Default::default()
+        };
+        let order = QuizItem {
+            question: "Order the lines".to_string(),
+            kind: QuizKind::OrderLines,
+            options: vec![
+                QuizOption {
+                    selection: "}".to_string(),
+                    position: Some(2),
+                    ..Default::default()
+                },
+                QuizOption {
+                    selection: "fn main() {".to_string(),
+                    position: Some(0),
+                    ..Default::default()
+                },
+                QuizOption {
+                    selection: "    run();".to_string(),
+                    position: Some(1),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let responses = vec![
+            stored("Ownership basics", vec![choice.clone(), blank, order]),
+            stored("Ownership basics", vec![choice.clone()]),
+        ];
+
+        let (deck, notes) = render_anki_deck(&responses);
+
+        assert_eq!(notes, 3);
+        let lines: Vec<&str> = deck.lines().collect();
+        assert_eq!(&lines[..4], ANKI_HEADER.lines().collect::<Vec<_>>());
+        assert_eq!(lines.len(), 7);
+        assert!(
+            lines[6]
+                .split('\t')
+                .nth(2)
+                .unwrap()
+                .starts_with("<pre>fn main() {<br>    run();<br>}</pre>")
+        );
+        let fields: Vec<&str> = lines[4].split('\t').collect();
+        assert_eq!(fields.len(), 4);
+        assert_eq!(fields[0], knowledge_store::question_id(&choice));
+        assert!(fields[1].starts_with("Which keyword borrows?"));
+        assert!(fields[1].contains("A. &amp;"));
+        assert!(fields[2].starts_with("<b>&amp;</b> — Takes a reference."));
+        assert!(fields[2].ends_with("<hr>Borrowing &lt;rules&gt;"));
+        assert_eq!(fields[3], "learnchain Ownership_basics Rust 2024-05-01");
+        assert!(lines[5].split('\t').nth(2).unwrap().starts_with("mut"));
+    }
+}
@@ -0,0 +1,640 @@
+use std::collections::HashSet;
+
+use crate::{
+    ai_manager::{QuizItem, QuizKind, QuizOption, StructuredLearningResponse},
+    question_history::QuestionHistory,
+};
+
+/// Fraction of questions that may be dropped before the output is considered unusable.
+const MAX_DROPPED_RATIO: f64 = 0.5;
+
+/// Categories of structural problems found in LLM quiz output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuizDefectKind {
+    EmptyQuestion,
+    EmptySelection,
+    DuplicateOption,
+    NoCorrectAnswer,
+    MultipleCorrectAnswers,
+    TooFewOptions,
+    NotTrueFalse,
+    NoAcceptedAnswer,
+    NoRubric,
+    RepeatsPastQuestion,
+    EmptyGroup,
+    TooFewQuestions { expected: usize, found: usize },
+}
+
+impl QuizDefectKind {
+    fn describe(&self) -> String {
+        match self {
+            Self::EmptyQuestion => "question text is empty".to_string(),
+            Self::EmptySelection => "an option has an empty selection".to_string(),
+            Self::DuplicateOption => "the same option appears more than once".to_string(),
+            Self::NoCorrectAnswer => "no option is marked is_correct_answer: true".to_string(),
+            Self::MultipleCorrectAnswers => {
+                "more than one option is marked is_correct_answer: true".to_string()
+            }
+            Self::TooFewOptions => "fewer than two distinct options remain".to_string(),
+            Self::NotTrueFalse => {
+                "a true_false question needs exactly two options, True and False".to_string()
+            }
+            Self::NoAcceptedAnswer => {
+                "a fill_in_blank question has no accepted_answers".to_string()
+            }
+            Self::NoRubric => "a free_text question has no rubric".to_string(),
+            Self::RepeatsPastQuestion => {
+                "the learner has already been asked this question; replace it with one about a different concept".to_string()
+            }
+            Self::EmptyGroup => "the knowledge group has no usable quiz questions".to_string(),
+            Self::TooFewQuestions { expected, found } => format!(
+                "only {} valid quiz question(s) were returned but at least {} are required",
+                found, expected
+            ),
+        }
+    }
+}
+
+/// What the validator did about a defect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefectAction {
+    Repaired,
+    Dropped,
+    Reported,
+}
+
+#[derive(Debug, Clone)]
+pub struct QuizDefect {
+    pub group: String,
+    pub question: Option<String>,
+    pub kind: QuizDefectKind,
+    pub action: DefectAction,
+}
+
+/// Outcome of validating a [`StructuredLearningResponse`].
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub defects: Vec<QuizDefect>,
+    pub total_questions: usize,
+    pub kept_questions: usize,
+    pub min_questions: usize,
+}
+
+impl ValidationReport {
+    pub fn dropped_questions(&self) -> usize {
+        self.total_questions.saturating_sub(self.kept_questions)
+    }
+
+    pub fn repaired_count(&self) -> usize {
+        self.defects
+            .iter()
+            .filter(|defect| defect.action == DefectAction::Repaired)
+            .count()
+    }
+
+    /// Whether too much of the output was invalid to use without asking the model again.
+    pub fn needs_reprompt(&self) -> bool {
+        if self.kept_questions == 0 || self.kept_questions < self.min_questions {
+            return true;
+        }
+        self.total_questions > 0
+            && self.dropped_questions() as f64 / self.total_questions as f64 > MAX_DROPPED_RATIO
+    }
+
+    /// Short human-readable summary suitable for logs and status lines.
+    pub fn summary(&self) -> String {
+        format!(
+            "kept {} of {} question(s), repaired {} issue(s), dropped {}",
+            self.kept_questions,
+            self.total_questions,
+            self.repaired_count(),
+            self.dropped_questions()
+        )
+    }
+
+    /// Follow-up instructions sent back to the model when the output must be regenerated.
+    pub fn reprompt_message(&self) -> String {
+        let mut lines = vec![
+            "Your previous JSON failed validation. Fix these problems and return the complete corrected JSON:"
+                .to_string(),
+        ];
+        for defect in &self.defects {
+            let location = match &defect.question {
+                Some(question) => format!("group '{}', question '{}'", defect.group, question),
+                None if defect.group.is_empty() => "response".to_string(),
+                None => format!("group '{}'", defect.group),
+            };
+            lines.push(format!("- {}: {}", location, defect.kind.describe()));
+        }
+        lines.push(
+            "Every question needs a non-empty question. multiple_choice and true_false need exactly one option with is_correct_answer set to true, multi_select needs at least one, order_lines needs at least two lines in the correct order, fill_in_blank needs at least one accepted_answers entry, and free_text needs a rubric."
+                .to_string(),
+        );
+        lines.join("\n")
+    }
+}
+
+/// Validate the quiz payload in place, repairing fixable items and dropping the rest.
+/// Questions that repeat one from `history`, or an earlier one in the same payload, are dropped.
+pub fn validate_and_repair(
+    response: &mut StructuredLearningResponse,
+    min_questions: usize,
+    history: &QuestionHistory,
+) -> ValidationReport {
+    let mut report = ValidationReport {
+        min_questions,
+        ..ValidationReport::default()
+    };
+    let mut seen = history.clone();
+
+    for group in &mut response.response {
+        report.total_questions += group.quiz.len();
+        let group_name = group.knowledge_type_group.clone();
+        group.quiz.retain_mut(|item| {
+            let mut keep = repair_quiz_item(item, &group_name, &mut report.defects);
+            if keep && seen.find_duplicate(&item.question).is_some() {
+                report.defects.push(QuizDefect {
+                    group: group_name.clone(),
+                    question: Some(item.question.clone()),
+                    kind: QuizDefectKind::RepeatsPastQuestion,
+                    action: DefectAction::Dropped,
+                });
+                keep = false;
+            }
+            if keep {
+                seen.insert(&item.question);
+                report.kept_questions += 1;
+            }
+            keep
+        });
+    }
+
+    response.response.retain(|group| {
+        if group.quiz.is_empty() {
+            report.defects.push(QuizDefect {
+                group: group.knowledge_type_group.clone(),
+                question: None,
+                kind: QuizDefectKind::EmptyGroup,
+                action: DefectAction::Dropped,
+            });
+            false
+        } else {
+            true
+        }
+    });
+
+    if report.kept_questions < min_questions {
+        report.defects.push(QuizDefect {
+            group: String::new(),
+            question: None,
+            kind: QuizDefectKind::TooFewQuestions {
+                expected: min_questions,
+                found: report.kept_questions,
+            },
+            action: DefectAction::Reported,
+        });
+    }
+
+    report
+}
+
+/// Trim every entry, dropping blanks and repeats while keeping the original order.
+fn trim_unique(values: &mut Vec<String>) {
+    let mut kept: Vec<String> = Vec::with_capacity(values.len());
+    for value in values.drain(..) {
+        let value = value.trim().to_string();
+        if !value.is_empty() && !kept.contains(&value) {
+            kept.push(value);
+        }
+    }
+    *values = kept;
+}
+
+/// Spell the two options of a true_false question as `True` and `False`, whatever their case.
+/// Returns false when the options are anything else.
+fn normalize_true_false(options: &mut [QuizOption]) -> bool {
+    let mut labels: Vec<String> = options
+        .iter()
+        .map(|option| option.selection.to_lowercase())
+        .collect();
+    labels.sort();
+    if labels != ["false", "true"] {
+        return false;
+    }
+    for option in options {
+        option.selection = if option.selection.eq_ignore_ascii_case("true") {
+            "True".to_string()
+        } else {
+            "False".to_string()
+        };
+    }
+    true
+}
+
+fn repair_quiz_item(item: &mut QuizItem, group: &str, defects: &mut Vec<QuizDefect>) -> bool {
+    let mut record = |kind: QuizDefectKind, action: DefectAction, question: &str| {
+        defects.push(QuizDefect {
+            group: group.to_string(),
+            question: Some(question.to_string()),
+            kind,
+            action,
+        });
+    };
+
+    item.question = item.question.trim().to_string();
+    if item.question.is_empty() {
+        record(QuizDefectKind::EmptyQuestion, DefectAction::Dropped, "");
+        return false;
+    }
+
+    trim_unique(&mut item.accepted_answers);
+    trim_unique(&mut item.source_refs);
+
+    if item.kind == QuizKind::FreeText {
+        item.rubric = item.rubric.trim().to_string();
+        item.options.clear();
+        if item.rubric.is_empty() {
+            record(
+                QuizDefectKind::NoRubric,
+                DefectAction::Dropped,
+                &item.question,
+            );
+            return false;
+        }
+        return true;
+    }
+
+    if item.kind == QuizKind::FillInBlank {
+        // Some replies put the answer in a correct option instead of accepted_answers.
+        if item.accepted_answers.is_empty() {
+            item.accepted_answers = item
+                .options
+                .iter()
+                .filter(|option| option.is_correct_answer && !option.selection.trim().is_empty())
+                .map(|option| option.selection.trim().to_string())
+                .collect();
+            if !item.accepted_answers.is_empty() {
+                record(
+                    QuizDefectKind::NoAcceptedAnswer,
+                    DefectAction::Repaired,
+                    &item.question,
+                );
+            }
+        }
+        item.options.clear();
+        if item.accepted_answers.is_empty() {
+            record(
+                QuizDefectKind::NoAcceptedAnswer,
+                DefectAction::Dropped,
+                &item.question,
+            );
+            return false;
+        }
+        return true;
+    }
+
+    let before = item.options.len();
+    item.options
+        .retain(|option| !option.selection.trim().is_empty());
+    if item.options.len() != before {
+        record(
+            QuizDefectKind::EmptySelection,
+            DefectAction::Repaired,
+            &item.question,
+        );
+    }
+
+    if item.kind == QuizKind::OrderLines {
+        // Repeated lines such as closing braces are legitimate, so only trailing whitespace is trimmed.
+        for option in &mut item.options {
+            option.selection = option.selection.trim_end().to_string();
+        }
+        if item.options.len() < 2 {
+            record(
+                QuizDefectKind::TooFewOptions,
+                DefectAction::Dropped,
+                &item.question,
+            );
+            return false;
+        }
+        return true;
+    }
+
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut deduped = Vec::with_capacity(item.options.len());
+    let mut had_duplicate = false;
+    for mut option in item.options.drain(..) {
+        option.selection = option.selection.trim().to_string();
+        option.explanation = option.explanation.trim().to_string();
+        let key = option.selection.to_lowercase();
+        if seen.insert(key.clone()) {
+            deduped.push(option);
+        } else {
+            had_duplicate = true;
+            // Keep the correct flag if any copy of the duplicate was marked correct.
+            if option.is_correct_answer
+                && let Some(existing) = deduped
+                    .iter_mut()
+                    .find(|existing| existing.selection.to_lowercase() == key)
+            {
+                existing.is_correct_answer = true;
+            }
+        }
+    }
+    item.options = deduped;
+    if had_duplicate {
+        record(
+            QuizDefectKind::DuplicateOption,
+            DefectAction::Repaired,
+            &item.question,
+        );
+    }
+
+    if item.options.len() < 2 {
+        record(
+            QuizDefectKind::TooFewOptions,
+            DefectAction::Dropped,
+            &item.question,
+        );
+        return false;
+    }
+
+    if item.kind == QuizKind::TrueFalse && !normalize_true_false(&mut item.options) {
+        record(
+            QuizDefectKind::NotTrueFalse,
+            DefectAction::Dropped,
+            &item.question,
+        );
+        return false;
+    }
+
+    let correct_count = item
+        .options
+        .iter()
+        .filter(|option| option.is_correct_answer)
+        .count();
+    match (item.kind, correct_count) {
+        (_, 0) => {
+            record(
+                QuizDefectKind::NoCorrectAnswer,
+                DefectAction::Dropped,
+                &item.question,
+            );
+            false
+        }
+        (QuizKind::MultiSelect, _) | (_, 1) => true,
+        _ => {
+            record(
+                QuizDefectKind::MultipleCorrectAnswers,
+                DefectAction::Dropped,
+                &item.question,
+            );
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_manager::{KnowledgeResponse, QuizOption};
+
+    fn option(selection: &str, correct: bool) -> QuizOption {
+        QuizOption {
+            selection: selection.to_string(),
+            is_correct_answer: correct,
+            ..Default::default()
+        }
+    }
+
+    fn item(question: &str, options: Vec<QuizOption>) -> QuizItem {
+        QuizItem {
+            question: question.to_string(),
+            options,
+            ..Default::default()
+        }
+    }
+
+    fn response_with(quiz: Vec<QuizItem>) -> StructuredLearningResponse {
+        StructuredLearningResponse {
+            response: vec![KnowledgeResponse {
+                knowledge_type_group: "Rust Basics".to_string(),
+                summary: String::new(),
+                quiz,
+                knowledge_type_language: "Rust".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn valid_output_passes_untouched() {
+        let mut response = response_with(vec![item(
+            "What does `&` create?",
+            vec![option("A reference", true), option("A copy", false)],
+        )]);
+
+        let report = validate_and_repair(&mut response, 1, &QuestionHistory::default());
+
+        assert!(report.defects.is_empty());
+        assert!(!report.needs_reprompt());
+        assert_eq!(report.kept_questions, 1);
+        assert_eq!(response.response[0].quiz[0].options.len(), 2);
+    }
+
+    #[test]
+    fn repairs_empty_and_duplicate_options() {
+        let mut response = response_with(vec![item(
+            "Which keyword declares a constant?",
+            vec![
+                option("const", false),
+                option("  ", false),
+                option("let", false),
+                option("Const ", true),
+            ],
+        )]);
+
+        let report = validate_and_repair(&mut response, 1, &QuestionHistory::default());
+
+        let options = &response.response[0].quiz[0].options;
+        assert_eq!(options.len(), 2);
+        assert_eq!(options[0].selection, "const");
+        assert!(options[0].is_correct_answer);
+        assert_eq!(report.repaired_count(), 2);
+        assert_eq!(report.dropped_questions(), 0);
+    }
+
+    #[test]
+    fn drops_items_without_exactly_one_correct_answer() {
+        let mut response = response_with(vec![
+            item("No answer", vec![option("a", false), option("b", false)]),
+            item("Two answers", vec![option("a", true), option("b", true)]),
+            item("Good", vec![option("a", true), option("b", false)]),
+        ]);
+
+        let report = validate_and_repair(&mut response, 1, &QuestionHistory::default());
+
+        assert_eq!(report.kept_questions, 1);
+        assert_eq!(report.dropped_questions(), 2);
+        assert!(
+            report
+                .defects
+                .iter()
+                .any(|defect| defect.kind == QuizDefectKind::NoCorrectAnswer)
+        );
+        assert!(
+            report
+                .defects
+                .iter()
+                .any(|defect| defect.kind == QuizDefectKind::MultipleCorrectAnswers)
+        );
+        assert!(
+            report.needs_reprompt(),
+            "two of three dropped exceeds the ratio"
+        );
+    }
+
+    #[test]
+    fn validates_each_question_kind() {
+        let mut multi = item(
+            "Which are integer types?",
+            vec![
+                option("i32", true),
+                option("u8", true),
+                option("f64", false),
+            ],
+        );
+        multi.kind = QuizKind::MultiSelect;
+        let mut true_false = item(
+            "Is String heap allocated?",
+            vec![
+                option("True", true),
+                option("False", false),
+                option("Maybe", false),
+            ],
+        );
+        true_false.kind = QuizKind::TrueFalse;
+        let mut blank = item("let v = ____![1, 2];", vec![option("vec", true)]);
+        blank.kind = QuizKind::FillInBlank;
+        let mut order = item(
+            "Order the match arms",
+            vec![
+                option("match x {", false),
+                option("}", false),
+                option("}", false),
+            ],
+        );
+        order.kind = QuizKind::OrderLines;
+        let mut explain = item("Explain borrowing", vec![]);
+        explain.kind = QuizKind::FreeText;
+        let mut response = response_with(vec![multi, true_false, blank, order, explain]);
+
+        let report = validate_and_repair(&mut response, 1, &QuestionHistory::default());
+
+        let quiz = &response.response[0].quiz;
+        assert_eq!(quiz.len(), 3);
+        assert_eq!(quiz[0].kind, QuizKind::MultiSelect);
+        assert_eq!(quiz[1].accepted_answers, vec!["vec".to_string()]);
+        assert!(quiz[1].options.is_empty());
+        assert_eq!(quiz[2].options.len(), 3, "repeated lines are kept");
+        assert!(
+            report
+                .defects
+                .iter()
+                .any(|defect| defect.kind == QuizDefectKind::NoRubric)
+        );
+        assert!(
+            report
+                .defects
+                .iter()
+                .any(|defect| defect.kind == QuizDefectKind::NotTrueFalse)
+        );
+    }
+
+    #[test]
+    fn true_false_options_must_be_true_and_false() {
+        let mut lowercase = item(
+            "Is String heap allocated?",
+            vec![option("true", true), option("FALSE", false)],
+        );
+        lowercase.kind = QuizKind::TrueFalse;
+        let mut yes_no = item(
+            "Is &str owned?",
+            vec![option("Yes", false), option("No", true)],
+        );
+        yes_no.kind = QuizKind::TrueFalse;
+        let mut response = response_with(vec![lowercase, yes_no]);
+
+        let report = validate_and_repair(&mut response, 1, &QuestionHistory::default());
+
+        let quiz = &response.response[0].quiz;
+        assert_eq!(quiz.len(), 1);
+        let labels: Vec<&str> = quiz[0]
+            .options
+            .iter()
+            .map(|option| option.selection.as_str())
+            .collect();
+        assert_eq!(labels, ["True", "False"]);
+        assert!(quiz[0].options[0].is_correct_answer);
+        assert!(report.defects.iter().any(|defect| {
+            defect.kind == QuizDefectKind::NotTrueFalse
+                && defect.question.as_deref() == Some("Is &str owned?")
+        }));
+    }
+
+    #[test]
+    fn flags_too_few_questions_and_removes_empty_groups() {
+        let mut response = response_with(vec![item("", vec![option("a", true)])]);
+
+        let report = validate_and_repair(&mut response, 3, &QuestionHistory::default());
+
+        assert!(response.response.is_empty());
+        assert!(report.needs_reprompt());
+        assert!(report.defects.iter().any(|defect| matches!(
+            defect.kind,
+            QuizDefectKind::TooFewQuestions {
+                expected: 3,
+                found: 0
+            }
+        )));
+        assert!(
+            report
+                .reprompt_message()
+                .contains("at least 3 are required")
+        );
+    }
+
+    #[test]
+    fn drops_questions_repeating_history_or_each_other() {
+        let answers = || {
+            vec![
+                option("Changes directory", true),
+                option("Lists files", false),
+            ]
+        };
+        let mut response = response_with(vec![
+            item("What does `cd` do?", answers()),
+            item("What does the `pwd` command print?", answers()),
+            item("what does the PWD command print", answers()),
+        ]);
+        let history = QuestionHistory::new(["What does cd do?"]);
+
+        let report = validate_and_repair(&mut response, 1, &history);
+
+        let questions: Vec<&str> = response.response[0]
+            .quiz
+            .iter()
+            .map(|item| item.question.as_str())
+            .collect();
+        assert_eq!(questions, vec!["What does the `pwd` command print?"]);
+        assert_eq!(
+            report
+                .defects
+                .iter()
+                .filter(|defect| defect.kind == QuizDefectKind::RepeatsPastQuestion)
+                .count(),
+            2
+        );
+        assert!(report.needs_reprompt(), "two of three dropped");
+        assert!(report.reprompt_message().contains("already been asked"));
+    }
+}
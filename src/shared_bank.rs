@@ -0,0 +1,224 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{Context, Result, eyre};
+
+use crate::{
+    ai_manager::{KnowledgeResponse, StructuredLearningResponse},
+    config,
+    log_util::log_debug,
+};
+
+/// The configured shared question bank directory, or `None` when no bank is set up.
+pub fn configured_bank_dir() -> Option<PathBuf> {
+    bank_dir_from_setting(&config::current().shared_bank_path)
+}
+
+/// Name stamped on published questions: the configured author, falling back to `$USER`.
+pub fn publishing_author() -> String {
+    let configured = config::current().author_name;
+    if !configured.trim().is_empty() {
+        return configured.trim().to_string();
+    }
+    env::var("USER")
+        .ok()
+        .filter(|user| !user.trim().is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn bank_dir_from_setting(setting: &str) -> Option<PathBuf> {
+    let setting = setting.trim();
+    if setting.is_empty() {
+        return None;
+    }
+    match (setting.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => Some(PathBuf::from(home).join(rest)),
+        _ => Some(PathBuf::from(setting)),
+    }
+}
+
+/// Write `response` to the bank as a new JSON file. Questions without an author are stamped
+/// with `author` and `published_at`; questions republished from the bank keep their credit.
+/// Returns the file written and how many questions it holds.
+pub fn publish_response(
+    bank_dir: &Path,
+    response: &StructuredLearningResponse,
+    author: &str,
+    published_at: DateTime<Utc>,
+) -> Result<(PathBuf, usize)> {
+    let mut published = response.clone();
+    published.response.retain(|group| !group.quiz.is_empty());
+    let question_count: usize = published
+        .response
+        .iter()
+        .map(|group| group.quiz.len())
+        .sum();
+    if question_count == 0 {
+        return Err(eyre!("the lesson has no questions to publish"));
+    }
+
+    let timestamp = published_at.to_rfc3339();
+    for item in published
+        .response
+        .iter_mut()
+        .flat_map(|group| group.quiz.iter_mut())
+    {
+        if item.author.is_none() {
+            item.author = Some(author.to_string());
+            item.published_at = Some(timestamp.clone());
+        }
+    }
+
+    fs::create_dir_all(bank_dir).wrap_err_with(|| {
+        format!(
+            "failed to create shared bank directory at {}",
+            bank_dir.display()
+        )
+    })?;
+    let stem = format!(
+        "{}-{}",
+        published_at.format("%Y%m%d-%H%M%S"),
+        file_slug(author)
+    );
+    let mut path = bank_dir.join(format!("{}.json", stem));
+    let mut counter = 2;
+    while path.exists() {
+        path = bank_dir.join(format!("{}-{}.json", stem, counter));
+        counter += 1;
+    }
+    let contents = serde_json::to_string_pretty(&published)
+        .wrap_err("failed to serialize lesson for the shared bank")?;
+    fs::write(&path, contents)
+        .wrap_err_with(|| format!("failed to publish lesson to {}", path.display()))?;
+    Ok((path, question_count))
+}
+
+/// Every knowledge response published to the bank, in file name (publish) order. Files that
+/// do not parse are skipped, so one bad file does not hide the rest of the bank.
+pub fn load_bank(bank_dir: &Path) -> Result<Vec<KnowledgeResponse>> {
+    let entries = fs::read_dir(bank_dir).wrap_err_with(|| {
+        format!(
+            "failed to read shared bank directory at {}",
+            bank_dir.display()
+        )
+    })?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut responses = Vec::new();
+    for path in paths {
+        let parsed = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                serde_json::from_str::<StructuredLearningResponse>(&contents)
+                    .map_err(|err| err.to_string())
+            });
+        match parsed {
+            Ok(structured) => responses.extend(structured.response),
+            Err(err) => log_debug(&format!("SharedBank: skipping {}: {}", path.display(), err)),
+        }
+    }
+    Ok(responses)
+}
+
+fn file_slug(author: &str) -> String {
+    let slug: String = author
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() {
+                ch.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = slug
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "lesson".to_string()
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_manager::QuizItem;
+
+    #[test]
+    fn published_lessons_load_back_with_author_and_date() {
+        let mut bank_dir = env::temp_dir();
+        bank_dir.push(format!(
+            "learnchain-shared-bank-{}",
+            Utc::now().timestamp_nanos_opt().unwrap()
+        ));
+        let question = |text: &str| QuizItem {
+            question: text.to_string(),
+            ..Default::default()
+        };
+        let mut republished = question("Already shared");
+        republished.author = Some("Ada".to_string());
+        republished.published_at = Some("2024-01-01T00:00:00+00:00".to_string());
+        let lesson = StructuredLearningResponse {
+            response: vec![
+                KnowledgeResponse {
+                    knowledge_type_group: "Git".to_string(),
+                    quiz: vec![question("What does `git stash` do?"), republished],
+                    ..Default::default()
+                },
+                KnowledgeResponse {
+                    knowledge_type_group: "Empty".to_string(),
+                    ..Default::default()
+                },
+            ],
+        };
+        let published_at = Utc::now();
+
+        let (first, count) =
+            publish_response(&bank_dir, &lesson, "Grace Hopper", published_at).unwrap();
+        let (second, _) =
+            publish_response(&bank_dir, &lesson, "Grace Hopper", published_at).unwrap();
+        assert_eq!(count, 2);
+        assert_ne!(first, second);
+        assert!(
+            first
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .ends_with("-grace-hopper.json")
+        );
+        fs::write(bank_dir.join("notes.json"), "not json").unwrap();
+        fs::write(bank_dir.join("README.md"), "# Team bank").unwrap();
+
+        let bank = load_bank(&bank_dir).unwrap();
+        assert_eq!(bank.len(), 2);
+        assert!(bank.iter().all(|group| group.knowledge_type_group == "Git"));
+        let fresh = &bank[0].quiz[0];
+        assert_eq!(fresh.author.as_deref(), Some("Grace Hopper"));
+        assert_eq!(
+            fresh.published_at.as_deref(),
+            Some(published_at.to_rfc3339().as_str())
+        );
+        let kept = &bank[0].quiz[1];
+        assert_eq!(kept.author.as_deref(), Some("Ada"));
+        assert_eq!(
+            kept.published_at.as_deref(),
+            Some("2024-01-01T00:00:00+00:00")
+        );
+
+        let empty = StructuredLearningResponse::default();
+        assert!(publish_response(&bank_dir, &empty, "Grace", published_at).is_err());
+
+        fs::remove_dir_all(&bank_dir).unwrap();
+    }
+}
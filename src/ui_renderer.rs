@@ -1,7 +1,9 @@
-use crate::view_managers::menu_manager::MENU_OPTIONS;
+use crate::view_managers::menu_manager::{ACTION_OPTIONS, MENU_OPTIONS};
 use crate::{
-    AI_LOADING_FRAMES, App, AppView, config,
-    knowledge_store::{DailyAnalytics, KnowledgeAnalytics},
+    AI_LOADING_FRAMES, App, AppView,
+    ai_manager::{ChatRole, QuizKind},
+    challenge, code_highlight, config,
+    knowledge_store::{self, DailyAnalytics, KnowledgeAnalytics, MasteryEntry, QuestionMarkKind},
     reset_learning_feedback,
     view_managers::LearningManager,
 };
@@ -11,10 +13,13 @@ use ratatui::{
     layout::{Constraint, Direction, Layout},
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span, Text},
-    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{Block, List, ListItem, ListState, Paragraph, Row, Table, Wrap},
 };
 use std::cmp;
 
+/// Knowledge groups listed in each of the strongest and weakest mastery tables.
+const MASTERY_ROWS: usize = 5;
+
 pub(crate) struct UiRenderer<'a> {
     app: &'a mut App,
 }
@@ -31,6 +36,11 @@ impl<'a> UiRenderer<'a> {
             AppView::Learning => self.render_learning(frame),
             AppView::Config => self.render_config(frame),
             AppView::Analytics => self.render_analytics(frame),
+            AppView::Topics => self.render_topics(frame),
+            AppView::History => self.render_history(frame),
+            AppView::Bookmarks => self.render_bookmarks(frame),
+            AppView::Leaderboard => self.render_leaderboard(frame),
+            AppView::Results => self.render_results(frame),
         }
     }
 
@@ -64,10 +74,13 @@ impl<'a> UiRenderer<'a> {
 
         let menu_sections = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(5), Constraint::Min(3)])
+            .constraints([
+                Constraint::Length(ACTION_OPTIONS as u16 + 2),
+                Constraint::Min(3),
+            ])
             .split(layout[1]);
 
-        let actions_items: Vec<ListItem> = MENU_OPTIONS[..2]
+        let actions_items: Vec<ListItem> = MENU_OPTIONS[..ACTION_OPTIONS]
             .iter()
             .map(|label| ListItem::new(*label))
             .collect();
@@ -86,7 +99,7 @@ impl<'a> UiRenderer<'a> {
             &mut actions_state,
         );
 
-        let config_items: Vec<ListItem> = MENU_OPTIONS[2..]
+        let config_items: Vec<ListItem> = MENU_OPTIONS[ACTION_OPTIONS..]
             .iter()
             .map(|label| ListItem::new(*label))
             .collect();
@@ -112,7 +125,7 @@ impl<'a> UiRenderer<'a> {
             status_lines.push(format!("AI: {}", status));
         }
         status_lines.push("Use ↑/↓ or j/k to choose. Press Enter to select.".to_string());
-        status_lines.push("Press 1-4 for quick selection. Esc, Ctrl-C, or q to quit.".to_string());
+        status_lines.push("Press 1-9 for quick selection. Esc, Ctrl-C, or q to quit.".to_string());
         if app.learning_response.is_some() {
             status_lines.push("Press l to revisit the latest learning response.".to_string());
         }
@@ -132,6 +145,7 @@ impl<'a> UiRenderer<'a> {
             .constraints([
                 Constraint::Length(5),
                 Constraint::Min(14),
+                Constraint::Length(MASTERY_ROWS as u16 + 4),
                 Constraint::Length(5),
             ])
             .split(frame.area());
@@ -227,6 +241,30 @@ impl<'a> UiRenderer<'a> {
             );
         }
 
+        let mastery_sections = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .margin(1)
+            .split(layout[2]);
+        let mastery = app
+            .analytics_snapshot
+            .as_ref()
+            .map(|snapshot| &snapshot.mastery);
+        let strongest: Vec<&MasteryEntry> = mastery
+            .map(|report| report.strongest(MASTERY_ROWS).iter().collect())
+            .unwrap_or_default();
+        let weakest: Vec<&MasteryEntry> = mastery
+            .map(|report| report.weakest(MASTERY_ROWS))
+            .unwrap_or_default();
+        frame.render_widget(
+            Self::mastery_table("Strongest", &strongest, Color::Rgb(189, 255, 154)),
+            mastery_sections[0],
+        );
+        frame.render_widget(
+            Self::mastery_table("Weakest", &weakest, Color::Rgb(255, 180, 140)),
+            mastery_sections[1],
+        );
+
         let mut footer_lines = Vec::new();
         footer_lines.push("Press r to refresh analytics.".to_string());
         footer_lines.push("Press m to return to the main menu.".to_string());
@@ -238,10 +276,58 @@ impl<'a> UiRenderer<'a> {
                         .title(Line::from("Commands"))
                         .border_style(Style::default().fg(Color::Rgb(120, 140, 220))),
                 ),
-            layout[2],
+            layout[3],
         );
     }
 
+    fn mastery_table<'b>(title: &'b str, entries: &[&MasteryEntry], color: Color) -> Table<'b> {
+        let header = Row::new(vec!["Mastery", "Tries", "Language", "Knowledge group"]).style(
+            Style::default()
+                .fg(Color::Rgb(140, 160, 220))
+                .add_modifier(Modifier::DIM),
+        );
+        let rows: Vec<Row> = if entries.is_empty() {
+            vec![Row::new(vec![
+                String::new(),
+                String::new(),
+                String::new(),
+                "Answer quiz questions to build mastery.".to_string(),
+            ])]
+        } else {
+            entries
+                .iter()
+                .map(|entry| {
+                    Row::new(vec![
+                        format!("{:>5.0}%", entry.score * 100.0),
+                        entry.attempts.to_string(),
+                        if entry.knowledge_type_language.is_empty() {
+                            "-".to_string()
+                        } else {
+                            entry.knowledge_type_language.clone()
+                        },
+                        entry.knowledge_type_group.clone(),
+                    ])
+                })
+                .collect()
+        };
+        Table::new(
+            rows,
+            [
+                Constraint::Length(7),
+                Constraint::Length(5),
+                Constraint::Length(10),
+                Constraint::Min(10),
+            ],
+        )
+        .header(header)
+        .style(Style::default().fg(color))
+        .block(
+            Block::bordered()
+                .title(Line::from(format!("{} knowledge groups", title)))
+                .border_style(Style::default().fg(Color::Rgb(120, 140, 220))),
+        )
+    }
+
     fn analytics_heatmap(snapshot: &KnowledgeAnalytics) -> Text<'static> {
         if snapshot.daily.is_empty() {
             return Text::from(vec![Line::from(
@@ -437,6 +523,8 @@ impl<'a> UiRenderer<'a> {
             Color::Rgb(189, 255, 154),
         ));
 
+        lines.extend(Self::usage_lines(snapshot));
+
         lines.extend(Self::group_bar_lines(snapshot));
 
         if let Some(refreshed) = app.analytics_refreshed_at.as_ref() {
@@ -467,6 +555,56 @@ impl<'a> UiRenderer<'a> {
         lines
     }
 
+    fn usage_lines(snapshot: &KnowledgeAnalytics) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+        for (label, totals) in [
+            ("AI cost today", &snapshot.usage_today),
+            ("AI cost this month", &snapshot.usage_this_month),
+        ] {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{label}: "),
+                    Style::default()
+                        .fg(Color::Rgb(189, 255, 154))
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!(
+                        "${:.4} ({} request(s), {} in / {} out tokens)",
+                        totals.estimated_cost_usd,
+                        totals.requests,
+                        totals.prompt_tokens,
+                        totals.completion_tokens
+                    ),
+                    Style::default().fg(Color::Rgb(180, 205, 255)),
+                ),
+            ]));
+        }
+
+        let budget = config::current().monthly_budget_usd;
+        if budget > 0.0 {
+            let spent = snapshot.usage_this_month.estimated_cost_usd;
+            let percentage = (spent / budget) * 100.0;
+            let color = if spent >= budget {
+                Color::Red
+            } else {
+                Color::Rgb(189, 255, 154)
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    "Monthly budget: ",
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("${:.2} of ${:.2} ({:.1}%)", spent, budget, percentage),
+                    Style::default().fg(color),
+                ),
+            ]));
+        }
+
+        lines
+    }
+
     fn metric_line(label: &str, value: u32, color: Color) -> Line<'static> {
         let bold = Style::default().fg(color).add_modifier(Modifier::BOLD);
         Line::from(vec![
@@ -557,6 +695,442 @@ impl<'a> UiRenderer<'a> {
         lines
     }
 
+    fn render_topics(&mut self, frame: &mut Frame) {
+        let app = &mut *self.app;
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(6),
+                Constraint::Length(5),
+            ])
+            .split(frame.area());
+
+        frame.render_widget(
+            Paragraph::new(format!(
+                "{} topic(s). New knowledge groups are matched to these automatically.",
+                app.topics.topics.len()
+            ))
+            .block(Block::bordered().title(Line::from("Topics").bold().blue().centered()))
+            .centered(),
+            layout[0],
+        );
+
+        let list_items: Vec<ListItem> = if app.topics.topics.is_empty() {
+            vec![ListItem::new(
+                "No topics yet. They are created as lessons are generated.",
+            )]
+        } else {
+            app.topics
+                .topics
+                .iter()
+                .map(|topic| {
+                    let marker = if app.topics.merge_source == Some(topic.id) {
+                        "[merge] "
+                    } else {
+                        ""
+                    };
+                    let aliases = if topic.aliases.is_empty() {
+                        String::new()
+                    } else {
+                        format!("  (also: {})", topic.aliases.join(", "))
+                    };
+                    ListItem::new(format!(
+                        "{}{} — {} question(s){}",
+                        marker, topic.name, topic.question_count, aliases
+                    ))
+                })
+                .collect()
+        };
+        let mut list_state = ListState::default();
+        if !app.topics.topics.is_empty() {
+            list_state.select(Some(app.topics.selected));
+        }
+        frame.render_stateful_widget(
+            List::new(list_items)
+                .block(Block::bordered().title(Line::from("Canonical topics")))
+                .highlight_symbol("▶ ")
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            layout[1],
+            &mut list_state,
+        );
+
+        let mut status_lines = Vec::new();
+        if let Some(buffer) = app.topics.rename_buffer.as_deref() {
+            status_lines.push(format!("New name: {}_", buffer));
+            status_lines.push("Press Enter to rename, Esc to cancel.".to_string());
+        } else {
+            if let Some(status) = app.topics.status.as_deref() {
+                status_lines.push(status.to_string());
+            }
+            status_lines.push(
+                "Use ↑/↓ or j/k to choose. Press r to rename, g to merge into another topic."
+                    .to_string(),
+            );
+            status_lines.push("Press m to return to the main menu.".to_string());
+        }
+        frame.render_widget(
+            Paragraph::new(status_lines.join("\n"))
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(Line::from("Status"))),
+            layout[2],
+        );
+    }
+
+    fn render_history(&mut self, frame: &mut Frame) {
+        let app = &mut *self.app;
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(6),
+                Constraint::Length(4),
+            ])
+            .split(frame.area());
+
+        frame.render_widget(
+            Paragraph::new(format!(
+                "{} lesson(s) in your knowledge history.",
+                app.history.lessons.len()
+            ))
+            .block(Block::bordered().title(Line::from("Past Lessons").bold().blue().centered()))
+            .centered(),
+            layout[0],
+        );
+
+        let list_items: Vec<ListItem> = if app.history.lessons.is_empty() {
+            vec![ListItem::new(
+                "No lessons recorded yet. Generate one with artifact output enabled.",
+            )]
+        } else {
+            app.history
+                .lessons
+                .iter()
+                .map(|lesson| {
+                    let score = if lesson.answered == 0 {
+                        "not attempted".to_string()
+                    } else {
+                        format!(
+                            "first try {}/{} ({:.0}%)",
+                            lesson.first_try_correct,
+                            lesson.answered,
+                            lesson.first_try_correct as f64 / lesson.answered as f64 * 100.0
+                        )
+                    };
+                    ListItem::new(format!(
+                        "{}  {} — {} question(s) • {}",
+                        lesson.session_date,
+                        lesson.groups.join(", "),
+                        lesson.question_count,
+                        score
+                    ))
+                })
+                .collect()
+        };
+        let mut list_state = ListState::default();
+        if !app.history.lessons.is_empty() {
+            list_state.select(Some(app.history.selected));
+        }
+        frame.render_stateful_widget(
+            List::new(list_items)
+                .block(Block::bordered().title(Line::from("Lessons by date")))
+                .highlight_symbol("▶ ")
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            layout[1],
+            &mut list_state,
+        );
+
+        let mut status_lines = Vec::new();
+        if let Some(status) = app.history.status.as_deref() {
+            status_lines.push(status.to_string());
+        }
+        status_lines.push(
+            "Use ↑/↓ or j/k to choose. Press Enter to replay the lesson, a to export it as an Anki deck, m for the main menu."
+                .to_string(),
+        );
+        frame.render_widget(
+            Paragraph::new(status_lines.join("\n"))
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(Line::from("Status"))),
+            layout[2],
+        );
+    }
+
+    fn render_bookmarks(&mut self, frame: &mut Frame) {
+        let app = &mut *self.app;
+        let flagged = app.bookmarks.kind == QuestionMarkKind::Flag;
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(6),
+                Constraint::Length(4),
+            ])
+            .split(frame.area());
+
+        frame.render_widget(
+            Paragraph::new(format!(
+                "{} {} question(s).",
+                app.bookmarks.marks.len(),
+                if flagged { "flagged" } else { "bookmarked" }
+            ))
+            .block(
+                Block::bordered().title(
+                    Line::from(if flagged {
+                        "Flagged Questions"
+                    } else {
+                        "Bookmarks"
+                    })
+                    .bold()
+                    .blue()
+                    .centered(),
+                ),
+            )
+            .centered(),
+            layout[0],
+        );
+
+        let list_items: Vec<ListItem> = if app.bookmarks.marks.is_empty() {
+            vec![ListItem::new(if flagged {
+                "No flagged questions. Press w on a question in the Learning view to flag it."
+            } else {
+                "No bookmarks yet. Press b on a question in the Learning view to bookmark it."
+            })]
+        } else {
+            app.bookmarks
+                .marks
+                .iter()
+                .map(|mark| {
+                    let note = if mark.note.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" — note: {}", mark.note)
+                    };
+                    ListItem::new(format!(
+                        "{}  [{}] {}{}",
+                        mark.marked_at.get(..10).unwrap_or(&mark.marked_at),
+                        mark.knowledge_type_group,
+                        mark.question.question,
+                        note
+                    ))
+                })
+                .collect()
+        };
+        let mut list_state = ListState::default();
+        if !app.bookmarks.marks.is_empty() {
+            list_state.select(Some(app.bookmarks.selected));
+        }
+        frame.render_stateful_widget(
+            List::new(list_items)
+                .block(Block::bordered().title(Line::from("Newest first")))
+                .highlight_symbol("▶ ")
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            layout[1],
+            &mut list_state,
+        );
+
+        let mut status_lines = Vec::new();
+        if let Some(status) = app.bookmarks.status.as_deref() {
+            status_lines.push(status.to_string());
+        }
+        status_lines.push(if flagged {
+            "Use ↑/↓ or j/k to choose. Press d to remove the flag, f to show bookmarks, m for the main menu."
+                .to_string()
+        } else {
+            "Use ↑/↓ or j/k to choose. Press Enter to practice bookmarks from here, d to remove the bookmark, f to show flagged questions, m for the main menu."
+                .to_string()
+        });
+        frame.render_widget(
+            Paragraph::new(status_lines.join("\n"))
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(Line::from("Status"))),
+            layout[2],
+        );
+    }
+
+    fn render_leaderboard(&mut self, frame: &mut Frame) {
+        let app = &mut *self.app;
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(6),
+                Constraint::Length(4),
+            ])
+            .split(frame.area());
+
+        let best = app
+            .leaderboard
+            .runs
+            .first()
+            .map(|run| format!("Personal best: {} points.", run.score))
+            .unwrap_or_else(|| "No challenge runs yet.".to_string());
+        frame.render_widget(
+            Paragraph::new(best)
+                .block(
+                    Block::bordered()
+                        .title(Line::from("Challenge Leaderboard").bold().blue().centered()),
+                )
+                .centered(),
+            layout[0],
+        );
+
+        let list_items: Vec<ListItem> = if app.leaderboard.runs.is_empty() {
+            vec![ListItem::new(
+                "Open a lesson and press t in the Learning view to start a timed challenge.",
+            )]
+        } else {
+            app.leaderboard
+                .runs
+                .iter()
+                .enumerate()
+                .map(|(index, run)| {
+                    ListItem::new(format!(
+                        "{:>2}. {:>5} pts  {}/{} correct, {} timed out  {:.0}s  {}  {}",
+                        index + 1,
+                        run.score,
+                        run.correct_count,
+                        run.question_count,
+                        run.timed_out_count,
+                        run.duration_ms as f64 / 1000.0,
+                        run.finished_at.get(..10).unwrap_or(&run.finished_at),
+                        run.knowledge_groups.join(", ")
+                    ))
+                })
+                .collect()
+        };
+        frame.render_widget(
+            List::new(list_items).block(Block::bordered().title(Line::from("Best runs"))),
+            layout[1],
+        );
+
+        let mut status_lines = Vec::new();
+        if let Some(status) = app.leaderboard.status.as_deref() {
+            status_lines.push(status.to_string());
+        }
+        status_lines.push(format!(
+            "Each question has {} seconds: {} points for a right answer plus up to {} for speed. Press r to refresh, m for the main menu.",
+            challenge::SECONDS_PER_QUESTION,
+            challenge::CORRECT_POINTS,
+            challenge::MAX_SPEED_BONUS
+        ));
+        frame.render_widget(
+            Paragraph::new(status_lines.join("\n"))
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(Line::from("Status"))),
+            layout[2],
+        );
+    }
+
+    fn render_results(&mut self, frame: &mut Frame) {
+        let app = &mut *self.app;
+        let results = &app.results.results;
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(results.groups.len() as u16 + 3),
+                Constraint::Min(6),
+                Constraint::Length(4),
+            ])
+            .split(frame.area());
+
+        let questions: usize = results.groups.iter().map(|group| group.questions).sum();
+        let correct: usize = results
+            .groups
+            .iter()
+            .map(|group| group.first_try_correct)
+            .sum();
+        let seconds = results.time_spent.as_secs();
+        frame.render_widget(
+            Paragraph::new(format!(
+                "{} of {} correct on the first try • {} missed • Time spent: {}m {:02}s",
+                correct,
+                questions,
+                results.missed.len(),
+                seconds / 60,
+                seconds % 60
+            ))
+            .block(Block::bordered().title(Line::from("Lesson Results").bold().green().centered()))
+            .centered(),
+            layout[0],
+        );
+
+        let rows: Vec<Row> = results
+            .groups
+            .iter()
+            .map(|group| {
+                let accuracy = if group.answered > 0 {
+                    group.first_try_correct as f64 / group.answered as f64 * 100.0
+                } else {
+                    0.0
+                };
+                Row::new(vec![
+                    group.knowledge_type_group.clone(),
+                    format!("{}/{}", group.first_try_correct, group.questions),
+                    format!("{}", group.questions - group.answered),
+                    format!("{:>5.1}%", accuracy),
+                ])
+            })
+            .collect();
+        frame.render_widget(
+            Table::new(
+                rows,
+                [
+                    Constraint::Percentage(55),
+                    Constraint::Percentage(15),
+                    Constraint::Percentage(15),
+                    Constraint::Percentage(15),
+                ],
+            )
+            .header(Row::new(vec!["Knowledge group", "Score", "Skipped", "Accuracy"]).bold())
+            .block(Block::bordered().title(Line::from("Score by group"))),
+            layout[1],
+        );
+
+        let missed_items: Vec<ListItem> = if results.missed.is_empty() {
+            vec![ListItem::new("No missed questions. Well done!")]
+        } else {
+            results
+                .missed
+                .iter()
+                .map(|missed| {
+                    ListItem::new(format!(
+                        "[{}] {}\n    Answer: {}",
+                        missed.knowledge_type_group, missed.question, missed.correct_answer
+                    ))
+                })
+                .collect()
+        };
+        let mut list_state = ListState::default();
+        if !results.missed.is_empty() {
+            list_state.select(Some(app.results.selected));
+        }
+        frame.render_stateful_widget(
+            List::new(missed_items)
+                .block(Block::bordered().title(Line::from("Missed on the first try")))
+                .highlight_symbol("▶ ")
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            layout[2],
+            &mut list_state,
+        );
+
+        let mut status_lines = Vec::new();
+        if let Some(status) = app.results.status.as_deref() {
+            status_lines.push(status.to_string());
+        }
+        status_lines.push(
+            "Press r to retry missed questions only, e to export the lesson as an Anki deck, l to go back to the lesson, m for the main menu."
+                .to_string(),
+        );
+        frame.render_widget(
+            Paragraph::new(status_lines.join("\n"))
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(Line::from("Status"))),
+            layout[3],
+        );
+    }
+
     fn render_events(&mut self, frame: &mut Frame) {
         let app = &mut *self.app;
         let session_title = if app.session_source == "Claude Code" {
@@ -721,45 +1295,70 @@ impl<'a> UiRenderer<'a> {
 
         let main_sections = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(8), Constraint::Length(6)])
+            .constraints(if app.followup.is_some() {
+                [Constraint::Percentage(45), Constraint::Percentage(55)]
+            } else {
+                [Constraint::Min(8), Constraint::Length(6)]
+            })
             .split(layout[1]);
 
-        let mut question_text =
-            String::from("No learning response available. Generate one from the main menu.");
+        // Wrapped here rather than by the paragraph so highlighted code keeps its own wrapping.
+        let code_width = main_sections[0].width.saturating_sub(2);
+        let mut question_text = code_highlight::plain_text(
+            "No learning response available. Generate one from the main menu.",
+            code_width,
+        );
         let mut resources_text = String::from("No resources to display.");
         let mut status_lines: Vec<String> = Vec::new();
 
         if app.ai_loading {
             let frame_symbol = AI_LOADING_FRAMES[app.ai_loading_frame % AI_LOADING_FRAMES.len()];
-            question_text = format!(
-                "{} Generating learning response…\n\nWe'll show the quiz once the AI reply is ready.",
-                frame_symbol
+            question_text = code_highlight::plain_text(
+                &format!(
+                    "{} Generating learning response…\n\nWe'll show the quiz once the AI reply is ready.",
+                    frame_symbol
+                ),
+                code_width,
             );
             resources_text = String::from("Resources will appear after generation completes.");
         } else if let Some(response) = &app.learning_response {
             if response.response.is_empty() {
-                question_text =
-                    String::from("The generated response did not include any knowledge groups.");
+                question_text = code_highlight::plain_text(
+                    "The generated response did not include any knowledge groups.",
+                    code_width,
+                );
                 resources_text = String::from("No additional resources provided.");
             } else {
                 let group_count = response.response.len();
                 let group_index = app.learning_group_index.min(group_count.saturating_sub(1));
                 let group = &response.response[group_index];
                 let quiz_count = group.quiz.len();
+                let language = group.knowledge_type_language.as_str();
                 let language_line = match group.knowledge_type_language.trim() {
                     "" => String::new(),
                     lang => format!("\nLanguage: {}", lang),
                 };
 
                 if quiz_count == 0 {
-                    question_text = format!(
-                        "Knowledge group {}/{}\nName: {}{}\nSummary: {}\n\nNo quiz questions were provided for this topic.",
-                        group_index + 1,
-                        group_count,
-                        group.knowledge_type_group,
-                        language_line,
-                        group.summary
+                    question_text = code_highlight::plain_text(
+                        &format!(
+                            "Knowledge group {}/{}\nName: {}{}\nSummary:",
+                            group_index + 1,
+                            group_count,
+                            group.knowledge_type_group,
+                            language_line,
+                        ),
+                        code_width,
                     );
+                    question_text.extend(code_highlight::highlight_text(
+                        &group.summary,
+                        language,
+                        code_width,
+                    ));
+                    question_text.extend(code_highlight::plain_text(
+                        "\nNo quiz questions were provided for this topic.",
+                        code_width,
+                    ));
                     app.learning_option_index = 0;
                     reset_learning_feedback(
                         &mut app.learning_feedback,
@@ -772,9 +1371,26 @@ impl<'a> UiRenderer<'a> {
                     let question = group.quiz.get(quiz_index).cloned().unwrap_or_default();
 
                     let option_count = question.options.len();
-                    let mut option_lines = Vec::new();
-                    if option_count == 0 {
-                        option_lines.push(String::from("- No answer options provided"));
+                    let mut option_lines = Text::default();
+                    if question.kind.is_typed_answer() {
+                        let prompt = match app.learning_text_input.as_deref() {
+                            Some(input) => {
+                                format!("▶ {}_  (Enter to submit, Esc to cancel)", input)
+                            }
+                            None if app.grading_receiver.is_some() => {
+                                String::from("Grading your answer…")
+                            }
+                            None if app.learning_waiting_for_next => match question.kind {
+                                QuizKind::FreeText => format!("Rubric: {}", question.rubric),
+                                _ => format!("Accepted: {}", question.accepted_answers.join(" | ")),
+                            },
+                            None => String::from("Press Enter to type your answer."),
+                        };
+                        option_lines = code_highlight::plain_text(&prompt, code_width);
+                        app.learning_option_index = 0;
+                    } else if option_count == 0 {
+                        option_lines =
+                            code_highlight::plain_text("- No answer options provided", code_width);
                         app.learning_option_index = 0;
                         reset_learning_feedback(
                             &mut app.learning_feedback,
@@ -786,59 +1402,129 @@ impl<'a> UiRenderer<'a> {
                         let answered = app.learning_feedback.is_some();
                         for (index, option) in question.options.iter().enumerate() {
                             let label = ((b'A' + (index % 26) as u8) as char).to_string();
-                            let marker = if answered && option.is_correct_answer {
-                                "[✓]"
-                            } else {
-                                "[ ]"
+                            let picked = app
+                                .learning_marked_options
+                                .iter()
+                                .position(|&marked| marked == index);
+                            let marker = match question.kind {
+                                QuizKind::OrderLines => picked
+                                    .map(|order| format!("[{}]", order + 1))
+                                    .unwrap_or_else(|| String::from("[ ]")),
+                                QuizKind::MultiSelect
+                                    if app.learning_waiting_for_next
+                                        && option.is_correct_answer =>
+                                {
+                                    String::from("[✓]")
+                                }
+                                QuizKind::MultiSelect if picked.is_some() => String::from("[x]"),
+                                QuizKind::MultiSelect => String::from("[ ]"),
+                                _ if answered && option.is_correct_answer => String::from("[✓]"),
+                                _ => String::from("[ ]"),
                             };
                             let prefix = if index == selected_option { "▶" } else { " " };
-                            option_lines.push(format!(
-                                "{} {} {} {}",
-                                prefix, marker, label, option.selection
+                            option_lines.extend(code_highlight::highlight_labelled(
+                                &format!("{} {} {} ", prefix, marker, label),
+                                &option.selection,
+                                language,
+                                code_width,
                             ));
                         }
                         app.learning_option_index = selected_option;
                     }
-                    let options_text = option_lines.join("\n");
-                    let summary_line = if app.learning_summary_revealed {
-                        format!("\n\nSummary: {}", group.summary)
-                    } else {
-                        String::new()
-                    };
-                    let feedback_line = if let Some(feedback) = app.learning_feedback.as_deref() {
-                        format!("\n\nFeedback: {}", feedback)
-                    } else {
-                        String::new()
-                    };
 
                     if app.learning_waiting_for_next {
-                        let mut segments = vec![format!(
-                            "Knowledge group {}/{}\nName: {}{}",
-                            group_index + 1,
-                            group_count,
-                            group.knowledge_type_group,
-                            language_line,
-                        )];
-                        segments.push(format!("Summary: {}", group.summary));
+                        question_text = code_highlight::plain_text(
+                            &format!(
+                                "Knowledge group {}/{}\nName: {}{}\n\nSummary:",
+                                group_index + 1,
+                                group_count,
+                                group.knowledge_type_group,
+                                language_line,
+                            ),
+                            code_width,
+                        );
+                        question_text.extend(code_highlight::highlight_text(
+                            &group.summary,
+                            language,
+                            code_width,
+                        ));
+                        let mut segments = Vec::new();
                         if let Some(feedback) = app.learning_feedback.as_deref() {
                             segments.push(format!("Result: {}", feedback));
                         }
                         segments.push(String::from("Press any key to continue."));
-                        question_text = segments.join("\n\n");
+                        question_text.extend(code_highlight::plain_text(
+                            &format!("\n{}", segments.join("\n\n")),
+                            code_width,
+                        ));
                     } else {
-                        question_text = format!(
-                            "Knowledge group {}/{}\nName: {}{}\n\nQuestion {}/{}:\n{}\n\nOptions:\n{}{}{}",
-                            group_index + 1,
-                            group_count,
-                            group.knowledge_type_group,
-                            language_line,
-                            quiz_index + 1,
-                            quiz_count,
-                            question.question,
-                            options_text,
-                            feedback_line,
-                            summary_line
+                        let shared_line = match question.author.as_deref() {
+                            Some(author) => format!(
+                                "\nShared by {}{}",
+                                author,
+                                question
+                                    .published_at
+                                    .as_deref()
+                                    .and_then(|published| published.get(..10))
+                                    .map(|date| format!(" on {}", date))
+                                    .unwrap_or_default()
+                            ),
+                            None => String::new(),
+                        };
+                        let id = knowledge_store::question_id(&question);
+                        let marks: Vec<&str> = [
+                            (QuestionMarkKind::Bookmark, "bookmarked"),
+                            (QuestionMarkKind::Flag, "flagged as incorrect"),
+                        ]
+                        .into_iter()
+                        .filter(|(kind, _)| app.question_marks.contains(&(id.clone(), *kind)))
+                        .map(|(_, label)| label)
+                        .collect();
+                        let marks_line = if marks.is_empty() {
+                            String::new()
+                        } else {
+                            format!("\nMarked: {}", marks.join(", "))
+                        };
+                        question_text = code_highlight::plain_text(
+                            &format!(
+                                "Knowledge group {}/{}\nName: {}{}{}{}\n\nQuestion {}/{} ({}):",
+                                group_index + 1,
+                                group_count,
+                                group.knowledge_type_group,
+                                language_line,
+                                shared_line,
+                                marks_line,
+                                quiz_index + 1,
+                                quiz_count,
+                                question.difficulty.as_str(),
+                            ),
+                            code_width,
                         );
+                        question_text.extend(code_highlight::highlight_text(
+                            &question.question,
+                            language,
+                            code_width,
+                        ));
+                        question_text.extend(code_highlight::plain_text(
+                            &format!("\n{}:", Self::answer_heading(question.kind)),
+                            code_width,
+                        ));
+                        question_text.extend(option_lines);
+                        if let Some(feedback) = app.learning_feedback.as_deref() {
+                            question_text.extend(code_highlight::plain_text(
+                                &format!("\nFeedback: {}", feedback),
+                                code_width,
+                            ));
+                        }
+                        if app.learning_summary_revealed {
+                            question_text
+                                .extend(code_highlight::plain_text("\nSummary:", code_width));
+                            question_text.extend(code_highlight::highlight_text(
+                                &group.summary,
+                                language,
+                                code_width,
+                            ));
+                        }
                     }
 
                     app.learning_option_index = app
@@ -856,6 +1542,12 @@ impl<'a> UiRenderer<'a> {
                             .collect::<Vec<_>>()
                             .join("\n")
                     };
+                    if !question.source_refs.is_empty() {
+                        resources_text.push_str(&format!(
+                            "\nFrom session events: {} (press v to view)",
+                            question.source_refs.join(", ")
+                        ));
+                    }
                 }
             }
         }
@@ -866,22 +1558,97 @@ impl<'a> UiRenderer<'a> {
         if let Some(status) = &app.ai_status {
             status_lines.push(format!("AI: {}", status));
         }
-        status_lines.push("Press r to regenerate quiz from the latest session events.".to_string());
-        status_lines.push("Press m to return to the main menu.".to_string());
+        if let Some(note) = app.learning_flag_note.as_deref() {
+            status_lines.push(format!(
+                "Flag as incorrect, optional note: {}_ (Enter to flag, Esc to cancel)",
+                note
+            ));
+        } else if app.followup.is_some() {
+            status_lines.push(
+                "Type a follow-up question and press Enter to send, Esc to close the chat."
+                    .to_string(),
+            );
+        } else if let Some(challenge) = app.challenge.as_ref() {
+            let countdown = if app.learning_waiting_for_next {
+                "answered".to_string()
+            } else {
+                app.question_elapsed_ms(app.learning_group_index, app.learning_quiz_index)
+                    .map(|elapsed| {
+                        format!("{}s left", challenge.remaining_ms(elapsed).div_ceil(1000))
+                    })
+                    .unwrap_or_default()
+            };
+            status_lines.push(format!(
+                "Challenge: {} • Score {} • Question {}/{}",
+                countdown,
+                challenge.score,
+                (challenge.answered + 1).min(challenge.question_count),
+                challenge.question_count
+            ));
+            status_lines.push(
+                "Answer before the countdown ends; unanswered questions count as wrong. Press m to abandon the challenge."
+                    .to_string(),
+            );
+        } else {
+            status_lines.push(
+                "Press r to regenerate quiz from the latest session events (reuses cached questions), f to fetch fresh ones, t to start a timed challenge."
+                    .to_string(),
+            );
+            status_lines.push(
+                "Press x after answering to ask follow-up questions, b to bookmark, w to flag as incorrect, u to publish the lesson to the shared bank, m to return to the main menu."
+                    .to_string(),
+            );
+        }
 
         frame.render_widget(
             Paragraph::new(question_text)
-                .wrap(Wrap { trim: false })
                 .block(Block::bordered().title(Line::from("Learning Question"))),
             main_sections[0],
         );
 
-        frame.render_widget(
-            Paragraph::new(resources_text)
-                .wrap(Wrap { trim: false })
-                .block(Block::bordered().title(Line::from("Resources"))),
-            main_sections[1],
-        );
+        if let Some(chat) = &app.followup {
+            let mut chat_lines: Vec<String> = chat
+                .messages
+                .iter()
+                .map(|message| {
+                    let speaker = match message.role {
+                        ChatRole::User => "You",
+                        ChatRole::Assistant => "Tutor",
+                    };
+                    format!("{}: {}", speaker, message.content)
+                })
+                .collect();
+            if chat_lines.is_empty() {
+                chat_lines.push(String::from(
+                    "Ask anything about this question, its answer, or the code it came from.",
+                ));
+            }
+            if app.followup_receiver.is_some() {
+                chat_lines.push(String::from("Tutor: Thinking…"));
+            }
+            chat_lines.push(format!("> {}_", chat.input));
+            let line_count = chat_lines.len();
+            let visible_height = main_sections[1].height.saturating_sub(2) as usize;
+            let scroll = line_count.saturating_sub(visible_height) as u16;
+            frame.render_widget(
+                Paragraph::new(chat_lines.join("\n"))
+                    .wrap(Wrap { trim: false })
+                    .scroll((scroll, 0))
+                    .block(Block::bordered().title(Line::from(format!(
+                        "Follow-up • group {} question {}",
+                        chat.group_index + 1,
+                        chat.quiz_index + 1
+                    )))),
+                main_sections[1],
+            );
+        } else {
+            frame.render_widget(
+                Paragraph::new(resources_text)
+                    .wrap(Wrap { trim: false })
+                    .block(Block::bordered().title(Line::from("Resources"))),
+                main_sections[1],
+            );
+        }
 
         frame.render_widget(
             Paragraph::new(status_lines.join("\n"))
@@ -890,6 +1657,16 @@ impl<'a> UiRenderer<'a> {
         );
     }
 
+    fn answer_heading(kind: QuizKind) -> String {
+        let hint = match kind {
+            QuizKind::MultipleChoice | QuizKind::TrueFalse => "",
+            QuizKind::MultiSelect => " (Space marks, Enter submits)",
+            QuizKind::FillInBlank | QuizKind::FreeText => " (Enter to answer)",
+            QuizKind::OrderLines => " (Enter picks the next line, Backspace undoes)",
+        };
+        format!("{}{}", kind.label(), hint)
+    }
+
     fn render_config(&mut self, frame: &mut Frame) {
         let app = &mut *self.app;
         let session_title = if app.session_source == "Claude Code" {
@@ -957,6 +1734,55 @@ impl<'a> UiRenderer<'a> {
             } else {
                 format!("OpenAI API key: {}", app.config_form.masked_openai_key())
             }),
+            ListItem::new(if app.config_form.monthly_budget_usd > 0.0 {
+                format!(
+                    "Monthly AI budget: ${:.2}",
+                    app.config_form.monthly_budget_usd
+                )
+            } else {
+                "Monthly AI budget: unlimited".to_string()
+            }),
+            ListItem::new(format!(
+                "Learner profile language: {}",
+                app.config_form.selected_language()
+            )),
+            ListItem::new(format!(
+                "  Proficiency: {}",
+                app.config_form
+                    .proficiency_for(app.config_form.selected_language())
+                    .map(|level| level.label())
+                    .unwrap_or("not set")
+            )),
+            ListItem::new(format!(
+                "  Focus: {}",
+                app.config_form
+                    .focus_for(app.config_form.selected_language())
+                    .label()
+            )),
+            ListItem::new(if app.config_form.is_editing_skip_topics() {
+                format!("Skip topics (editing): {}_", app.config_form.edit_buffer())
+            } else if app.config_form.skip_topics.is_empty() {
+                "Skip topics: <none>".to_string()
+            } else {
+                format!("Skip topics: {}", app.config_form.skip_topics.join(", "))
+            }),
+            ListItem::new(if app.config_form.is_editing_shared_bank_path() {
+                format!(
+                    "Shared question bank (editing): {}_",
+                    app.config_form.edit_buffer()
+                )
+            } else if app.config_form.shared_bank_path.is_empty() {
+                "Shared question bank: <not set>".to_string()
+            } else {
+                format!("Shared question bank: {}", app.config_form.shared_bank_path)
+            }),
+            ListItem::new(if app.config_form.is_editing_author_name() {
+                format!("Author name (editing): {}_", app.config_form.edit_buffer())
+            } else if app.config_form.author_name.is_empty() {
+                "Author name: <not set>".to_string()
+            } else {
+                format!("Author name: {}", app.config_form.author_name)
+            }),
         ];
 
         let mut list_state = ListState::default();
@@ -983,7 +1809,7 @@ impl<'a> UiRenderer<'a> {
                 .to_string(),
         );
         status_lines.push(
-            "Select \"OpenAI API key\" and press Enter to edit. Type to update, Enter to save, Esc to cancel.".to_string(),
+            "Select a text field such as \"OpenAI API key\" or \"Shared question bank\" and press Enter to edit. Type to update, Enter to save, Esc to cancel.".to_string(),
         );
         status_lines
             .push("Press s to save, r to reset, m to save and return to the menu.".to_string());
@@ -0,0 +1,155 @@
+use crate::{
+    App, AppView,
+    ai_manager::{self, KnowledgeResponse, StructuredLearningResponse},
+    knowledge_store::{self, QuestionMark, QuestionMarkKind},
+    log_util::log_debug,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// State of the bookmarked questions screen, which can also list flagged questions.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BookmarksState {
+    pub(crate) kind: QuestionMarkKind,
+    pub(crate) marks: Vec<QuestionMark>,
+    pub(crate) selected: usize,
+    pub(crate) status: Option<String>,
+}
+
+pub(crate) struct BookmarksManager<'a> {
+    app: &'a mut App,
+}
+
+impl<'a> BookmarksManager<'a> {
+    pub(crate) fn new(app: &'a mut App) -> Self {
+        Self { app }
+    }
+
+    pub(crate) fn show_bookmarks(app: &'a mut App) {
+        let mut manager = Self::new(app);
+        manager.app.bookmarks.status = None;
+        manager.app.bookmarks.kind = QuestionMarkKind::Bookmark;
+        manager.refresh();
+        manager.app.view = AppView::Bookmarks;
+        log_debug("App: opened bookmarks view");
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) {
+        match (key.modifiers, key.code) {
+            (KeyModifiers::NONE, KeyCode::Down | KeyCode::Char('j')) => self.select_next(),
+            (KeyModifiers::NONE, KeyCode::Up | KeyCode::Char('k')) => self.select_previous(),
+            (KeyModifiers::NONE, KeyCode::Enter) => self.practice_bookmarks(),
+            (KeyModifiers::NONE, KeyCode::Char('d')) => self.remove_selected(),
+            (KeyModifiers::NONE, KeyCode::Char('f')) => self.toggle_flagged(),
+            (KeyModifiers::NONE, KeyCode::Char('m')) => self.app.return_to_menu(),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn refresh(&mut self) {
+        match knowledge_store::load_question_marks(Some(self.app.bookmarks.kind)) {
+            Ok(marks) => {
+                self.app.bookmarks.selected = self
+                    .app
+                    .bookmarks
+                    .selected
+                    .min(marks.len().saturating_sub(1));
+                self.app.bookmarks.marks = marks;
+            }
+            Err(err) => {
+                self.app.bookmarks.marks.clear();
+                self.app.bookmarks.status = Some(format!("Unable to load marks: {}", err));
+                log_debug(&format!("App: failed to load question marks: {}", err));
+            }
+        }
+    }
+
+    fn select_next(&mut self) {
+        let count = self.app.bookmarks.marks.len();
+        if count > 0 {
+            self.app.bookmarks.selected = (self.app.bookmarks.selected + 1) % count;
+        }
+    }
+
+    fn select_previous(&mut self) {
+        let count = self.app.bookmarks.marks.len();
+        if count > 0 {
+            self.app.bookmarks.selected = (self.app.bookmarks.selected + count - 1) % count;
+        }
+    }
+
+    /// Switch between listing bookmarks and questions flagged as incorrect.
+    fn toggle_flagged(&mut self) {
+        self.app.bookmarks.kind = match self.app.bookmarks.kind {
+            QuestionMarkKind::Bookmark => QuestionMarkKind::Flag,
+            QuestionMarkKind::Flag => QuestionMarkKind::Bookmark,
+        };
+        self.app.bookmarks.selected = 0;
+        self.app.bookmarks.status = None;
+        self.refresh();
+    }
+
+    /// Open every bookmarked question in the Learning view, starting with the selected one.
+    /// Flagged questions are not practiced.
+    fn practice_bookmarks(&mut self) {
+        let marks = &self.app.bookmarks.marks;
+        if marks.is_empty() || self.app.bookmarks.kind != QuestionMarkKind::Bookmark {
+            return;
+        }
+        let ordered = marks[self.app.bookmarks.selected..]
+            .iter()
+            .chain(&marks[..self.app.bookmarks.selected]);
+        let structured = bookmarks_lesson(ordered);
+        let count = marks.len();
+        ai_manager::practice_bookmarks(self.app, structured, count);
+    }
+
+    fn remove_selected(&mut self) {
+        let Some(mark) = self
+            .app
+            .bookmarks
+            .marks
+            .get(self.app.bookmarks.selected)
+            .cloned()
+        else {
+            return;
+        };
+        match knowledge_store::clear_question_mark(mark.kind, &mark.question_id) {
+            Ok(()) => {
+                self.app
+                    .question_marks
+                    .remove(&(mark.question_id, mark.kind));
+                self.app.bookmarks.status = Some(format!("Removed {}.", mark.kind.as_str()));
+                self.refresh();
+            }
+            Err(err) => {
+                self.app.bookmarks.status =
+                    Some(format!("Unable to remove {}: {}", mark.kind.as_str(), err));
+                log_debug(&format!("App: failed to remove question mark: {}", err));
+            }
+        }
+    }
+}
+
+/// Group bookmarked questions back into knowledge groups, keeping their order.
+fn bookmarks_lesson<'m>(
+    marks: impl Iterator<Item = &'m QuestionMark>,
+) -> StructuredLearningResponse {
+    let mut response: Vec<KnowledgeResponse> = Vec::new();
+    for mark in marks {
+        match response.last_mut() {
+            Some(group)
+                if group.knowledge_type_group == mark.knowledge_type_group
+                    && group.knowledge_type_language == mark.knowledge_type_language =>
+            {
+                group.quiz.push(mark.question.clone());
+            }
+            _ => response.push(KnowledgeResponse {
+                knowledge_type_group: mark.knowledge_type_group.clone(),
+                summary: mark.summary.clone(),
+                knowledge_type_language: mark.knowledge_type_language.clone(),
+                quiz: vec![mark.question.clone()],
+            }),
+        }
+    }
+    StructuredLearningResponse { response }
+}
@@ -17,20 +17,20 @@ impl<'a> ConfigManager<'a> {
     pub(crate) fn show_config(&mut self) {
         self.app.config_form = ConfigForm::from_config(config::current());
         self.app.config_form.set_status(
-            "Use ←/→ to adjust values or cycle sources/model. Select the API key and press Enter to edit. s saves; m saves and returns.",
+            "Use ←/→ to adjust values or cycle sources/model/learner profile. Select the API key or skip topics and press Enter to edit. s saves; m saves and returns.",
         );
         self.app.view = AppView::Config;
     }
 
     pub(crate) fn handle_key(&mut self, key: KeyEvent) {
-        if self.app.config_form.is_editing_openai_key() {
+        if self.app.config_form.is_editing_text() {
             match key.code {
-                KeyCode::Esc => self.app.config_form.cancel_openai_key_edit(),
-                KeyCode::Enter => self.app.config_form.apply_openai_key_edit(),
-                KeyCode::Backspace => self.app.config_form.backspace_openai_key(),
+                KeyCode::Esc => self.app.config_form.cancel_text_edit(),
+                KeyCode::Enter => self.app.config_form.apply_text_edit(),
+                KeyCode::Backspace => self.app.config_form.backspace_text(),
                 KeyCode::Char(ch) => {
                     if !key.modifiers.contains(KeyModifiers::CONTROL) {
-                        self.app.config_form.push_openai_key_char(ch);
+                        self.app.config_form.push_text_char(ch);
                     }
                 }
                 _ => {}
@@ -55,9 +55,9 @@ impl<'a> ConfigManager<'a> {
                 self.app.config_form.adjust_current(1);
             }
             (KeyModifiers::NONE, KeyCode::Enter)
-                if self.app.config_form.is_openai_key_selected() =>
+                if self.app.config_form.is_text_field_selected() =>
             {
-                self.app.config_form.start_editing_openai_key();
+                self.app.config_form.start_text_edit();
             }
             (KeyModifiers::NONE, KeyCode::Char('s')) | (KeyModifiers::NONE, KeyCode::Enter) => {
                 self.save_config_changes();
@@ -88,6 +88,13 @@ impl<'a> ConfigManager<'a> {
         let target_write = self.app.config_form.write_output_artifacts;
         let target_model = self.app.config_form.openai_model;
         let target_key = self.app.config_form.openai_api_key.clone();
+        let target_budget = self.app.config_form.monthly_budget_usd;
+        let target_proficiency = self.app.config_form.language_proficiency.clone();
+        let target_focus = self.app.config_form.focus_languages.clone();
+        let target_ignored = self.app.config_form.ignored_languages.clone();
+        let target_skip_topics = self.app.config_form.skip_topics.clone();
+        let target_bank_path = self.app.config_form.shared_bank_path.clone();
+        let target_author = self.app.config_form.author_name.clone();
 
         match config::update(|config| {
             config.default_max_events = target_max;
@@ -96,6 +103,13 @@ impl<'a> ConfigManager<'a> {
             config.write_output_artifacts = target_write;
             config.openai_model = target_model;
             config.openai_api_key = target_key.clone();
+            config.monthly_budget_usd = target_budget;
+            config.language_proficiency = target_proficiency;
+            config.focus_languages = target_focus;
+            config.ignored_languages = target_ignored;
+            config.skip_topics = target_skip_topics;
+            config.shared_bank_path = target_bank_path;
+            config.author_name = target_author;
         }) {
             Ok(updated) => {
                 self.app.config_form.apply_saved(updated);
@@ -0,0 +1,126 @@
+use crate::{
+    App, AppView, ai_manager,
+    knowledge_store::{self, PastLesson, StoredResponse},
+    log_util::log_debug,
+    quiz_export,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// State of the past lessons screen.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HistoryState {
+    pub(crate) lessons: Vec<PastLesson>,
+    pub(crate) selected: usize,
+    pub(crate) status: Option<String>,
+}
+
+impl HistoryState {
+    pub(crate) fn selected_lesson(&self) -> Option<&PastLesson> {
+        self.lessons.get(self.selected)
+    }
+}
+
+pub(crate) struct HistoryManager<'a> {
+    app: &'a mut App,
+}
+
+impl<'a> HistoryManager<'a> {
+    pub(crate) fn new(app: &'a mut App) -> Self {
+        Self { app }
+    }
+
+    pub(crate) fn show_history(app: &'a mut App) {
+        let mut manager = Self::new(app);
+        manager.app.history.status = None;
+        manager.refresh();
+        manager.app.view = AppView::History;
+        log_debug("App: opened past lessons view");
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) {
+        match (key.modifiers, key.code) {
+            (KeyModifiers::NONE, KeyCode::Down | KeyCode::Char('j')) => self.select_next(),
+            (KeyModifiers::NONE, KeyCode::Up | KeyCode::Char('k')) => self.select_previous(),
+            (KeyModifiers::NONE, KeyCode::Enter) => self.replay_selected(),
+            (KeyModifiers::NONE, KeyCode::Char('a')) => self.export_selected_to_anki(),
+            (KeyModifiers::NONE, KeyCode::Char('m')) => self.app.return_to_menu(),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn refresh(&mut self) {
+        match knowledge_store::list_past_lessons() {
+            Ok(lessons) => {
+                self.app.history.selected = self
+                    .app
+                    .history
+                    .selected
+                    .min(lessons.len().saturating_sub(1));
+                self.app.history.lessons = lessons;
+            }
+            Err(err) => {
+                self.app.history.lessons.clear();
+                self.app.history.status = Some(format!("Unable to load past lessons: {}", err));
+                log_debug(&format!("App: failed to load past lessons: {}", err));
+            }
+        }
+    }
+
+    fn select_next(&mut self) {
+        let count = self.app.history.lessons.len();
+        if count > 0 {
+            self.app.history.selected = (self.app.history.selected + 1) % count;
+        }
+    }
+
+    fn select_previous(&mut self) {
+        let count = self.app.history.lessons.len();
+        if count > 0 {
+            self.app.history.selected = (self.app.history.selected + count - 1) % count;
+        }
+    }
+
+    fn replay_selected(&mut self) {
+        let Some(lesson) = self.app.history.selected_lesson().cloned() else {
+            return;
+        };
+        match knowledge_store::load_past_lesson(&lesson.session_date, &lesson.recorded_at) {
+            Ok(response) => {
+                ai_manager::replay_past_lesson(self.app, response, &lesson.session_date);
+                log_debug(&format!(
+                    "App: replaying lesson recorded at {}",
+                    lesson.recorded_at
+                ));
+            }
+            Err(err) => {
+                self.app.history.status = Some(format!("Unable to open lesson: {}", err));
+                log_debug(&format!("App: failed to load past lesson: {}", err));
+            }
+        }
+    }
+
+    fn export_selected_to_anki(&mut self) {
+        let Some(lesson) = self.app.history.selected_lesson().cloned() else {
+            return;
+        };
+        let result = knowledge_store::load_past_lesson(&lesson.session_date, &lesson.recorded_at)
+            .and_then(|structured| {
+                let responses: Vec<StoredResponse> = structured
+                    .response
+                    .into_iter()
+                    .map(|response| StoredResponse {
+                        session_date: lesson.session_date.clone(),
+                        response,
+                    })
+                    .collect();
+                quiz_export::write_anki_deck(&responses, None, &lesson.session_date)
+            });
+        self.app.history.status = Some(match result {
+            Ok((path, notes)) => {
+                log_debug(&format!("App: exported Anki deck to {}", path.display()));
+                format!("Exported {} note(s) to {}.", notes, path.display())
+            }
+            Err(err) => format!("Anki export failed: {}", err),
+        });
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{
+    App, AppView, challenge,
+    knowledge_store::{self, ChallengeRun},
+    log_util::log_debug,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// State of the challenge leaderboard screen.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LeaderboardState {
+    pub(crate) runs: Vec<ChallengeRun>,
+    pub(crate) status: Option<String>,
+}
+
+pub(crate) struct LeaderboardManager<'a> {
+    app: &'a mut App,
+}
+
+impl<'a> LeaderboardManager<'a> {
+    pub(crate) fn new(app: &'a mut App) -> Self {
+        Self { app }
+    }
+
+    pub(crate) fn show_leaderboard(app: &'a mut App) {
+        let mut manager = Self::new(app);
+        manager.refresh();
+        manager.app.view = AppView::Leaderboard;
+        log_debug("App: opened challenge leaderboard");
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) {
+        match (key.modifiers, key.code) {
+            (KeyModifiers::NONE, KeyCode::Char('r')) | (KeyModifiers::NONE, KeyCode::Char('R')) => {
+                self.refresh();
+            }
+            (KeyModifiers::NONE, KeyCode::Char('m')) => self.app.return_to_menu(),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn refresh(&mut self) {
+        match knowledge_store::load_challenge_leaderboard(challenge::LEADERBOARD_SIZE) {
+            Ok(runs) => {
+                self.app.leaderboard.runs = runs;
+                self.app.leaderboard.status = None;
+            }
+            Err(err) => {
+                self.app.leaderboard.runs.clear();
+                self.app.leaderboard.status =
+                    Some(format!("Unable to load the leaderboard: {}", err));
+                log_debug(&format!(
+                    "App: failed to load challenge leaderboard: {}",
+                    err
+                ));
+            }
+        }
+    }
+}
@@ -1,12 +1,21 @@
-use super::events_manager::EventsManager;
+use super::{events_manager::EventsManager, results_manager::ResultsManager};
 use crate::{
     App, AppView,
-    ai_manager::{self, StructuredLearningResponse},
+    ai_manager::{
+        self, ChatMessage, ChatRole, FollowupChat, FollowupContext, FreeTextGrade, GradingRequest,
+        QuizItem, QuizKind, StructuredLearningResponse,
+    },
+    challenge::{self, ChallengeState},
+    knowledge_store::{self, AttemptAnswer, QuestionMarkKind},
     log_util::log_debug,
     reset_learning_feedback,
+    session_manager::SessionEvent,
+    shared_bank,
 };
+use chrono::Utc;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use rand::{rng, seq::SliceRandom};
+use std::time::Instant;
 
 pub(crate) struct LearningManager<'a> {
     app: &'a mut App,
@@ -34,16 +43,136 @@ impl<'a> LearningManager<'a> {
         }
     }
 
+    /// Shuffle answer options, remembering each option's original position first so
+    /// order-lines questions can still be graded. True/false keeps its natural order.
     pub(crate) fn shuffle_quiz_options(response: &mut StructuredLearningResponse) {
         let mut rng = rng();
         for group in &mut response.response {
             for quiz in &mut group.quiz {
-                quiz.options.shuffle(&mut rng);
+                for (index, option) in quiz.options.iter_mut().enumerate() {
+                    option.position.get_or_insert(index);
+                }
+                if quiz.kind != QuizKind::TrueFalse {
+                    quiz.options.shuffle(&mut rng);
+                }
             }
         }
     }
 
     pub(crate) fn handle_key(&mut self, key: KeyEvent) {
+        if let Some(chat) = self.app.followup.as_mut() {
+            match key.code {
+                KeyCode::Esc => self.app.close_followup(),
+                KeyCode::Enter => self.send_followup_question(),
+                KeyCode::Backspace => {
+                    chat.input.pop();
+                }
+                KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    chat.input.push(ch);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        if let Some(input) = self.app.learning_text_input.as_mut() {
+            match key.code {
+                KeyCode::Esc => self.app.learning_text_input = None,
+                KeyCode::Enter => self.submit_text_answer(),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    input.push(ch);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        if let Some(note) = self.app.learning_flag_note.as_mut() {
+            match key.code {
+                KeyCode::Esc => self.app.learning_flag_note = None,
+                KeyCode::Enter => {
+                    let note = self.app.learning_flag_note.take().unwrap_or_default();
+                    self.set_question_mark(QuestionMarkKind::Flag, true, &note);
+                }
+                KeyCode::Backspace => {
+                    note.pop();
+                }
+                KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    note.push(ch);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        if self.app.challenge.is_some() {
+            match (key.modifiers, key.code) {
+                (KeyModifiers::NONE, KeyCode::Char('m')) => {
+                    self.app.challenge = None;
+                    self.app.ai_status = Some("Challenge abandoned.".to_string());
+                    self.app.return_to_menu();
+                    return;
+                }
+                // Restarting, publishing or leaving the lesson waits for the run to end, even
+                // between questions. Bookmarks and flags only mark the question and stay open.
+                (KeyModifiers::NONE, KeyCode::Char(ch)) if "tuvx".contains(ch) => return,
+                // Any other key moves on from an answered question.
+                _ if self.app.learning_waiting_for_next => {}
+                // Moving between questions or leaving the lesson would dodge the countdown.
+                (KeyModifiers::NONE, KeyCode::Char(ch)) if "hlnNpP[]{}rRfe".contains(ch) => {
+                    return;
+                }
+                (
+                    KeyModifiers::NONE,
+                    KeyCode::Left
+                    | KeyCode::Right
+                    | KeyCode::PageDown
+                    | KeyCode::PageUp
+                    | KeyCode::Tab,
+                )
+                | (_, KeyCode::BackTab) => return,
+                _ => {}
+            }
+        }
+
+        if key.modifiers == KeyModifiers::NONE && key.code == KeyCode::Char('t') {
+            self.start_challenge();
+            return;
+        }
+
+        if key.modifiers == KeyModifiers::NONE && key.code == KeyCode::Char('b') {
+            let marked = self.is_active_question_marked(QuestionMarkKind::Bookmark);
+            self.set_question_mark(QuestionMarkKind::Bookmark, !marked, "");
+            return;
+        }
+
+        if key.modifiers == KeyModifiers::NONE && key.code == KeyCode::Char('w') {
+            if self.is_active_question_marked(QuestionMarkKind::Flag) {
+                self.set_question_mark(QuestionMarkKind::Flag, false, "");
+            } else if self.active_question().is_some() {
+                self.app.learning_flag_note = Some(String::new());
+            }
+            return;
+        }
+
+        if key.modifiers == KeyModifiers::NONE && key.code == KeyCode::Char('x') {
+            self.open_followup();
+            return;
+        }
+
+        if key.modifiers == KeyModifiers::NONE && key.code == KeyCode::Char('v') {
+            self.show_source_event();
+            return;
+        }
+
+        if key.modifiers == KeyModifiers::NONE && key.code == KeyCode::Char('u') {
+            self.publish_to_shared_bank();
+            return;
+        }
+
         if self.app.learning_waiting_for_next {
             self.app.learning_waiting_for_next = false;
             self.next_question();
@@ -67,12 +196,17 @@ impl<'a> LearningManager<'a> {
             | (KeyModifiers::NONE, KeyCode::Char('{'))
             | (KeyModifiers::NONE, KeyCode::PageUp)
             | (KeyModifiers::NONE, KeyCode::BackTab) => self.previous_question(),
-            (KeyModifiers::NONE, KeyCode::Enter)
-            | (KeyModifiers::NONE, KeyCode::Char(' '))
-            | (KeyModifiers::NONE, KeyCode::Char('s')) => self.select_option(),
+            (KeyModifiers::NONE, KeyCode::Char(' ')) => self.mark_or_select_option(),
+            (KeyModifiers::NONE, KeyCode::Enter) | (KeyModifiers::NONE, KeyCode::Char('s')) => {
+                self.select_option()
+            }
+            (KeyModifiers::NONE, KeyCode::Backspace) => self.undo_last_mark(),
             (KeyModifiers::NONE, KeyCode::Char('r')) | (KeyModifiers::NONE, KeyCode::Char('R')) => {
                 ai_manager::trigger_learning_response(self.app)
             }
+            (KeyModifiers::NONE, KeyCode::Char('f')) => {
+                ai_manager::trigger_fresh_learning_response(self.app)
+            }
             (KeyModifiers::NONE, KeyCode::Char('m')) => self.app.return_to_menu(),
             (KeyModifiers::NONE, KeyCode::Char('e')) => EventsManager::show_events(self.app),
             _ => {}
@@ -134,22 +268,30 @@ impl<'a> LearningManager<'a> {
             Self::reset_feedback_state(self.app);
         }
 
-        let option_len = self
-            .app
-            .learning_response
-            .as_ref()
-            .and_then(|resp| resp.response.get(self.app.learning_group_index))
-            .and_then(|group| group.quiz.get(self.app.learning_quiz_index))
-            .map(|question| question.options.len())
-            .unwrap_or(0);
+        let (option_len, typed_answer) = self
+            .active_question()
+            .map(|question| (question.options.len(), question.kind.is_typed_answer()))
+            .unwrap_or((0, false));
 
         if option_len == 0 {
             self.app.learning_option_index = 0;
-            Self::reset_feedback_state(self.app);
+            // Typed-answer questions have no options by design; keep their feedback.
+            if !typed_answer {
+                Self::reset_feedback_state(self.app);
+            }
         } else if self.app.learning_option_index >= option_len {
             self.app.learning_option_index = 0;
             Self::reset_feedback_state(self.app);
         }
+
+        let current = (self.app.learning_group_index, self.app.learning_quiz_index);
+        if self
+            .app
+            .learning_question_shown_at
+            .is_none_or(|(shown, _)| shown != current)
+        {
+            self.app.learning_question_shown_at = Some((current, Instant::now()));
+        }
     }
 
     pub(crate) fn next_group(&mut self) {
@@ -189,6 +331,7 @@ impl<'a> LearningManager<'a> {
             if self.app.learning_quiz_index + 1 < quiz_len {
                 self.app.learning_quiz_index += 1;
                 self.app.learning_option_index = 0;
+                Self::reset_answer_state(self.app);
                 self.reset_feedback();
                 log_debug(&format!(
                     "App: moved to question {} of {} in group {}",
@@ -213,6 +356,7 @@ impl<'a> LearningManager<'a> {
             if self.app.learning_quiz_index > 0 {
                 self.app.learning_quiz_index -= 1;
                 self.app.learning_option_index = 0;
+                Self::reset_answer_state(self.app);
                 self.reset_feedback();
                 log_debug(&format!(
                     "App: moved to question {} of {} in group {}",
@@ -267,16 +411,59 @@ impl<'a> LearningManager<'a> {
         self.ensure_indices();
     }
 
+    /// Answer the active question the way its kind expects: grade single-choice and
+    /// multi-select answers, pick the next line for order-lines, or focus the answer input.
     pub(crate) fn select_option(&mut self) {
-        let Some(response) = self.app.learning_response.as_ref() else {
+        let Some(question) = self.active_question().cloned() else {
             return;
         };
-        let Some(group) = response.response.get(self.app.learning_group_index) else {
-            return;
-        };
-        let Some(question) = group.quiz.get(self.app.learning_quiz_index) else {
+        match question.kind {
+            QuizKind::MultipleChoice | QuizKind::TrueFalse => self.grade_single_choice(&question),
+            QuizKind::MultiSelect => self.grade_multi_select(&question),
+            QuizKind::FillInBlank | QuizKind::FreeText => {
+                if self.app.grading_receiver.is_some() {
+                    self.app.learning_feedback =
+                        Some("Still grading your previous answer…".to_string());
+                    return;
+                }
+                self.app.learning_text_input = Some(String::new());
+                self.app.learning_feedback = None;
+            }
+            QuizKind::OrderLines => self.pick_order_line(&question),
+        }
+    }
+
+    /// Space toggles a mark on multi-select questions and answers every other kind.
+    fn mark_or_select_option(&mut self) {
+        let is_multi_select = self
+            .active_question()
+            .map(|question| question.kind == QuizKind::MultiSelect)
+            .unwrap_or(false);
+        if !is_multi_select {
+            self.select_option();
             return;
-        };
+        }
+        let index = self.app.learning_option_index;
+        if let Some(position) = self
+            .app
+            .learning_marked_options
+            .iter()
+            .position(|&marked| marked == index)
+        {
+            self.app.learning_marked_options.remove(position);
+        } else {
+            self.app.learning_marked_options.push(index);
+        }
+        self.reset_feedback();
+    }
+
+    fn undo_last_mark(&mut self) {
+        if self.app.learning_marked_options.pop().is_some() {
+            self.reset_feedback();
+        }
+    }
+
+    fn grade_single_choice(&mut self, question: &QuizItem) {
         if question.options.is_empty() {
             self.app.learning_feedback =
                 Some("No answer options available for this question.".to_string());
@@ -288,30 +475,610 @@ impl<'a> LearningManager<'a> {
 
         let option_len = question.options.len();
         let selected_index = self.app.learning_option_index.min(option_len - 1);
-        let label = ((b'A' + (selected_index % 26) as u8) as char).to_string();
-        let correct = question.options[selected_index].is_correct_answer;
+        let label = option_label(selected_index);
+        let selected = &question.options[selected_index];
+        let correct = selected.is_correct_answer;
+        let explanation = with_explanation("", &selected.explanation);
+
+        let selection = selected.selection.clone();
+        if correct {
+            self.finish_attempt(
+                true,
+                selection,
+                format!(
+                    "Correct! Option {} is the right answer.{}",
+                    label, explanation
+                ),
+            );
+        } else {
+            self.finish_attempt(
+                false,
+                selection,
+                format!("Not quite.{} Try another option.", explanation),
+            );
+        }
+
+        log_debug(&format!(
+            "App: evaluated option {} (correct: {})",
+            label, correct
+        ));
+    }
+
+    fn grade_multi_select(&mut self, question: &QuizItem) {
+        if self.app.learning_marked_options.is_empty() {
+            self.app.learning_feedback =
+                Some("Mark every correct option with Space, then press Enter.".to_string());
+            return;
+        }
+        let correct = is_multi_select_correct(question, &self.app.learning_marked_options);
+        let selections = joined_selections(question, &self.app.learning_marked_options, " | ");
+        if correct {
+            self.finish_attempt(
+                true,
+                selections,
+                "Correct! You marked every right answer.".to_string(),
+            );
+        } else {
+            // Explain wrongly marked options without revealing which ones were missed.
+            let explanations: String = self
+                .app
+                .learning_marked_options
+                .iter()
+                .filter_map(|&index| question.options.get(index).map(|option| (index, option)))
+                .filter(|(_, option)| !option.is_correct_answer)
+                .map(|(index, option)| {
+                    with_explanation(&format!(" {}:", option_label(index)), &option.explanation)
+                })
+                .collect();
+            self.finish_attempt(
+                false,
+                selections,
+                format!(
+                    "Not quite. Some marks are wrong or missing; adjust them and try again.{}",
+                    explanations
+                ),
+            );
+        }
+        log_debug(&format!(
+            "App: evaluated multi-select answer {:?} (correct: {})",
+            self.app.learning_marked_options, correct
+        ));
+    }
 
-        self.app.record_quiz_first_attempt(
-            self.app.learning_group_index,
-            self.app.learning_quiz_index,
+    fn pick_order_line(&mut self, question: &QuizItem) {
+        let index = self.app.learning_option_index;
+        if index >= question.options.len() || self.app.learning_marked_options.contains(&index) {
+            return;
+        }
+        self.app.learning_marked_options.push(index);
+        self.reset_feedback();
+        if self.app.learning_marked_options.len() < question.options.len() {
+            return;
+        }
+
+        let correct = is_order_correct(question, &self.app.learning_marked_options);
+        let picked = joined_selections(question, &self.app.learning_marked_options, " → ");
+        if correct {
+            self.finish_attempt(
+                true,
+                picked,
+                "Correct! The lines are in the right order.".to_string(),
+            );
+        } else {
+            self.app.learning_marked_options.clear();
+            self.finish_attempt(
+                false,
+                picked,
+                "Not quite. The order is wrong; pick the lines again from the first one."
+                    .to_string(),
+            );
+        }
+        log_debug(&format!("App: evaluated line order (correct: {})", correct));
+    }
+
+    fn submit_text_answer(&mut self) {
+        let Some(answer) = self.app.learning_text_input.take() else {
+            return;
+        };
+        let Some(question) = self.active_question().cloned() else {
+            return;
+        };
+        if answer.trim().is_empty() {
+            self.app.learning_feedback = Some("Type an answer before pressing Enter.".to_string());
+            return;
+        }
+        if question.kind == QuizKind::FreeText {
+            let summary = self
+                .app
+                .learning_response
+                .as_ref()
+                .and_then(|response| response.response.get(self.app.learning_group_index))
+                .map(|group| group.summary.clone())
+                .unwrap_or_default();
+            let request = GradingRequest {
+                group_index: self.app.learning_group_index,
+                quiz_index: self.app.learning_quiz_index,
+                question_id: knowledge_store::question_id(&question),
+                question: question.question,
+                summary,
+                rubric: question.rubric,
+                answer: answer.trim().to_string(),
+                elapsed_ms: self.app.question_elapsed_ms(
+                    self.app.learning_group_index,
+                    self.app.learning_quiz_index,
+                ),
+            };
+            ai_manager::trigger_free_text_grading(self.app, request);
+            return;
+        }
+        let correct = is_text_answer_accepted(&question, &answer);
+        let typed = answer.trim().to_string();
+        if correct {
+            self.finish_attempt(
+                true,
+                typed.clone(),
+                format!("Correct! \"{}\" fills the blank.", typed),
+            );
+        } else {
+            self.finish_attempt(
+                false,
+                typed,
+                "Not quite. Press Enter to try another answer.".to_string(),
+            );
+        }
+        log_debug(&format!(
+            "App: evaluated fill-in answer (correct: {})",
+            correct
+        ));
+    }
+
+    /// Log the attempt with the answer given and show feedback; correct answers reveal the summary.
+    fn finish_attempt(&mut self, correct: bool, answer: String, feedback: String) {
+        let current = (self.app.learning_group_index, self.app.learning_quiz_index);
+        let first_attempt = !self.app.quiz_first_attempts.contains_key(&current);
+        let elapsed_ms = self.app.question_elapsed_ms(current.0, current.1);
+        self.app.record_quiz_attempt(
+            current.0,
+            current.1,
             correct,
+            &AttemptAnswer {
+                answer_text: Some(answer),
+                ..Default::default()
+            },
         );
+        self.app.learning_feedback = Some(feedback);
+        self.app.learning_summary_revealed = correct;
+        self.app.learning_waiting_for_next = correct;
+        if first_attempt {
+            self.score_challenge_answer(correct, elapsed_ms.unwrap_or_default());
+        }
+    }
 
-        if correct {
+    /// Restart the current lesson from its first question as a timed challenge.
+    fn start_challenge(&mut self) {
+        let question_count: usize = self
+            .app
+            .learning_response
+            .as_ref()
+            .map(|response| response.response.iter().map(|group| group.quiz.len()).sum())
+            .unwrap_or_default();
+        if question_count == 0 {
             self.app.learning_feedback =
-                Some(format!("Correct! Option {} is the right answer.", label));
-            self.app.learning_summary_revealed = true;
-            self.app.learning_waiting_for_next = true;
+                Some("This lesson has no questions to run a challenge on.".to_string());
+            return;
+        }
+
+        self.app.close_followup();
+        self.app.quiz_first_attempts.clear();
+        self.app.learning_started_at = Some(Instant::now());
+        self.app.learning_group_index = 0;
+        self.reset_question_state();
+        if self.group_quiz_len(0).is_none() {
+            self.move_to_next_group_with_quiz();
+        }
+        self.app.challenge = Some(ChallengeState::new(
+            question_count,
+            challenge::SECONDS_PER_QUESTION,
+        ));
+        self.app.learning_question_shown_at = None;
+        self.ensure_indices();
+        self.app.ai_status = Some(format!(
+            "Challenge started: {} question(s), {} seconds each. Press m to abandon.",
+            question_count,
+            challenge::SECONDS_PER_QUESTION
+        ));
+        log_debug(&format!(
+            "App: started a timed challenge with {} question(s)",
+            question_count
+        ));
+    }
+
+    /// Count a question as wrong once its challenge countdown runs out. Called from the app tick.
+    pub(crate) fn on_challenge_tick(&mut self) {
+        let Some(limit_ms) = self.app.challenge.as_ref().map(ChallengeState::limit_ms) else {
+            return;
+        };
+        if self.app.learning_waiting_for_next || self.app.grading_receiver.is_some() {
+            return;
+        }
+        let current = (self.app.learning_group_index, self.app.learning_quiz_index);
+        if self.app.quiz_first_attempts.contains_key(&current) {
+            return;
+        }
+        let Some(elapsed_ms) = self.app.question_elapsed_ms(current.0, current.1) else {
+            return;
+        };
+        if elapsed_ms < limit_ms {
+            return;
+        }
+
+        Self::reset_answer_state(self.app);
+        self.app.record_quiz_attempt(
+            current.0,
+            current.1,
+            false,
+            &AttemptAnswer {
+                elapsed_ms: Some(elapsed_ms),
+                ..Default::default()
+            },
+        );
+        if let Some(challenge) = self.app.challenge.as_mut() {
+            challenge.record_timeout();
+        }
+        self.app.learning_feedback = Some("Time's up! No points for this question.".to_string());
+        self.after_challenge_answer();
+        log_debug("App: challenge question timed out");
+    }
+
+    /// Score the first attempt at a challenge question. Each question gets one scored try,
+    /// so the learner moves on whether or not it was right.
+    fn score_challenge_answer(&mut self, correct: bool, elapsed_ms: u64) {
+        let Some(challenge) = self.app.challenge.as_mut() else {
+            return;
+        };
+        let points = challenge.record_answer(correct, elapsed_ms);
+        self.app.learning_feedback = Some(if correct {
+            format!(
+                "{} +{} points.",
+                self.app.learning_feedback.as_deref().unwrap_or("Correct!"),
+                points
+            )
         } else {
-            self.app.learning_feedback = Some("Not quite. Try another option.".to_string());
-            self.app.learning_summary_revealed = false;
-            self.app.learning_waiting_for_next = false;
+            "Not quite. No points for this question.".to_string()
+        });
+        self.after_challenge_answer();
+    }
+
+    fn after_challenge_answer(&mut self) {
+        self.app.learning_summary_revealed = true;
+        self.app.learning_waiting_for_next = true;
+        let complete = self
+            .app
+            .challenge
+            .as_ref()
+            .is_some_and(ChallengeState::is_complete);
+        if complete {
+            self.finish_challenge();
+        } else if let Some(feedback) = self.app.learning_feedback.as_mut() {
+            feedback.push_str(" Press any key for the next question.");
+        }
+    }
+
+    /// Store the finished run and show its result. Runs are kept in the knowledge store when
+    /// artifacts are enabled, like answer attempts.
+    fn finish_challenge(&mut self) {
+        let Some(challenge) = self.app.challenge.take() else {
+            return;
+        };
+        let Some(lesson) = self.app.learning_response.as_ref() else {
+            return;
+        };
+        let run = challenge.to_run(&self.app.session_date, lesson);
+        let mut result = format!(
+            "Challenge complete! Score {} ({} of {} correct, {} timed out).",
+            run.score, run.correct_count, run.question_count, run.timed_out_count
+        );
+        if self.app.write_output_artifacts {
+            match knowledge_store::record_challenge_run(&run) {
+                Ok(true) => result.push_str(" New personal best!"),
+                Ok(false) => {}
+                Err(err) => App::push_error(
+                    &mut self.app.error,
+                    format!("Failed to save challenge run: {}", err),
+                ),
+            }
+        } else {
+            log_debug("App: skipping challenge run persistence (artifacts disabled)");
+        }
+        if let Some(feedback) = self.app.learning_feedback.as_mut() {
+            feedback.push_str("\n\n");
+            feedback.push_str(&result);
+        }
+        self.app.ai_status = Some(result);
+        log_debug(&format!("App: finished challenge with score {}", run.score));
+    }
+
+    /// Jump to the Events view with the session event behind the active question selected.
+    fn show_source_event(&mut self) {
+        let Some(source_refs) = self
+            .active_question()
+            .map(|question| question.source_refs.clone())
+        else {
+            return;
+        };
+        if source_refs.is_empty() {
+            self.app.learning_feedback =
+                Some("This question has no linked session events.".to_string());
+            return;
+        }
+        match find_source_event(&self.app.events, &source_refs) {
+            Some(index) => {
+                self.app.selected_event = Some(index);
+                EventsManager::show_events(self.app);
+                log_debug(&format!("App: jumped to source event {}", index));
+            }
+            None => {
+                self.app.learning_feedback = Some(format!(
+                    "Source event {} is not in the loaded session.",
+                    source_refs.join(", ")
+                ));
+            }
+        }
+    }
+
+    fn is_active_question_marked(&self, kind: QuestionMarkKind) -> bool {
+        self.active_question().is_some_and(|question| {
+            self.app
+                .question_marks
+                .contains(&(knowledge_store::question_id(question), kind))
+        })
+    }
+
+    /// Add or remove a bookmark or flag on the active question. Marks are kept in the
+    /// knowledge store when artifacts are enabled, like answer attempts.
+    fn set_question_mark(&mut self, kind: QuestionMarkKind, marked: bool, note: &str) {
+        let Some(group) = self
+            .app
+            .learning_response
+            .as_ref()
+            .and_then(|response| response.response.get(self.app.learning_group_index))
+            .cloned()
+        else {
+            return;
+        };
+        let Some(question) = group.quiz.get(self.app.learning_quiz_index) else {
+            return;
+        };
+        let id = knowledge_store::question_id(question);
+
+        if self.app.write_output_artifacts {
+            let result = if marked {
+                knowledge_store::set_question_mark(kind, &group, question, note)
+            } else {
+                knowledge_store::clear_question_mark(kind, &id)
+            };
+            if let Err(err) = result {
+                App::push_error(
+                    &mut self.app.error,
+                    format!("Failed to save {}: {}", kind.as_str(), err),
+                );
+                return;
+            }
+        } else {
+            log_debug("App: skipping question mark persistence (artifacts disabled)");
+        }
+        if marked {
+            self.app.question_marks.insert((id, kind));
+        } else {
+            self.app.question_marks.remove(&(id, kind));
         }
 
+        self.app.ai_status = Some(
+            match (kind, marked) {
+                (QuestionMarkKind::Bookmark, true) => "Bookmarked question.",
+                (QuestionMarkKind::Bookmark, false) => "Removed bookmark.",
+                (QuestionMarkKind::Flag, true) => {
+                    "Flagged question as incorrect; it is left out of reviews and analytics."
+                }
+                (QuestionMarkKind::Flag, false) => "Removed flag.",
+            }
+            .to_string(),
+        );
+    }
+
+    /// Publish the current lesson to the configured shared question bank.
+    fn publish_to_shared_bank(&mut self) {
+        let Some(bank_dir) = shared_bank::configured_bank_dir() else {
+            App::push_error(
+                &mut self.app.error,
+                "Set a shared question bank path in the Config view before publishing.".to_string(),
+            );
+            return;
+        };
+        let Some(response) = self.app.learning_response.as_ref() else {
+            return;
+        };
+        let author = shared_bank::publishing_author();
+        match shared_bank::publish_response(&bank_dir, response, &author, Utc::now()) {
+            Ok((path, count)) => {
+                self.app.ai_status = Some(format!(
+                    "Published {} question(s) as {} to {}",
+                    count,
+                    author,
+                    path.display()
+                ));
+                log_debug(&format!("App: published lesson to {}", path.display()));
+            }
+            Err(err) => App::push_error(
+                &mut self.app.error,
+                format!("Failed to publish to the shared bank: {}", err),
+            ),
+        }
+    }
+
+    /// Open the follow-up panel for the active question, restoring any saved conversation.
+    fn open_followup(&mut self) {
+        let group_index = self.app.learning_group_index;
+        let quiz_index = self.app.learning_quiz_index;
+        if !self
+            .app
+            .quiz_first_attempts
+            .contains_key(&(group_index, quiz_index))
+        {
+            self.app.learning_feedback = Some(
+                "Answer the question first, then press x to ask follow-up questions.".to_string(),
+            );
+            return;
+        }
+        let Some(group) = self
+            .app
+            .learning_response
+            .as_ref()
+            .and_then(|response| response.response.get(group_index))
+        else {
+            return;
+        };
+        let Some(question) = group.quiz.get(quiz_index) else {
+            return;
+        };
+        let context = FollowupContext::new(
+            group,
+            question,
+            self.app.summary_content.as_deref().unwrap_or_default(),
+        );
+
+        let messages = if self.app.write_output_artifacts {
+            knowledge_store::load_followup_messages(
+                &self.app.session_date,
+                &context.knowledge_type_group,
+                &context.question.question,
+            )
+            .unwrap_or_else(|err| {
+                log_debug(&format!("App: failed to load follow-up messages: {}", err));
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+
+        self.app.followup = Some(FollowupChat {
+            group_index,
+            quiz_index,
+            context,
+            messages,
+            input: String::new(),
+        });
+        log_debug("App: opened follow-up panel");
+    }
+
+    fn send_followup_question(&mut self) {
+        if self.app.followup_receiver.is_some() {
+            return;
+        }
+        let Some(chat) = self.app.followup.as_mut() else {
+            return;
+        };
+        let question = chat.input.trim().to_string();
+        if question.is_empty() {
+            return;
+        }
+        chat.input.clear();
+        self.add_followup_message(ChatMessage {
+            role: ChatRole::User,
+            content: question,
+        });
+        ai_manager::trigger_followup_reply(self.app);
+    }
+
+    /// Append a message to the open follow-up conversation and save it next to the quiz.
+    pub(crate) fn add_followup_message(&mut self, message: ChatMessage) {
+        let Some(chat) = self.app.followup.as_mut() else {
+            log_debug("App: dropping follow-up message because the panel was closed");
+            return;
+        };
+        if self.app.write_output_artifacts
+            && let Err(err) = knowledge_store::record_followup_message(
+                &self.app.session_date,
+                &chat.context.knowledge_type_group,
+                &chat.context.question.question,
+                &message,
+            )
+        {
+            App::push_error(
+                &mut self.app.error,
+                format!("Failed to save follow-up message: {}", err),
+            );
+        }
+        chat.messages.push(message);
+    }
+
+    /// Record a graded free-text answer. Feedback is only shown if the learner is still on
+    /// the question that was graded, and the grade is dropped if the lesson no longer holds it.
+    pub(crate) fn apply_free_text_grade(&mut self, request: GradingRequest, grade: FreeTextGrade) {
+        let graded_question_loaded = self
+            .app
+            .learning_response
+            .as_ref()
+            .and_then(|response| response.response.get(request.group_index))
+            .and_then(|group| group.quiz.get(request.quiz_index))
+            .is_some_and(|question| knowledge_store::question_id(question) == request.question_id);
+        if !graded_question_loaded {
+            log_debug("App: dropping free-text grade for a question that is no longer loaded");
+            return;
+        }
+        let passed = grade.passed();
+        let first_attempt = !self
+            .app
+            .quiz_first_attempts
+            .contains_key(&(request.group_index, request.quiz_index));
+        let elapsed_ms = request.elapsed_ms;
+        self.app.record_quiz_attempt(
+            request.group_index,
+            request.quiz_index,
+            passed,
+            &AttemptAnswer {
+                answer_text: Some(request.answer),
+                score: Some(grade.score),
+                elapsed_ms: request.elapsed_ms,
+                ..Default::default()
+            },
+        );
         log_debug(&format!(
-            "App: evaluated option {} (correct: {})",
-            label, correct
+            "App: graded free-text answer (score: {}, passed: {})",
+            grade.score, passed
+        ));
+
+        if (request.group_index, request.quiz_index)
+            != (self.app.learning_group_index, self.app.learning_quiz_index)
+        {
+            return;
+        }
+        let next_step = if passed {
+            ""
+        } else {
+            " Press Enter to try again."
+        };
+        self.app.learning_feedback = Some(format!(
+            "Score {}/100. {}{}",
+            grade.score,
+            grade.feedback.trim(),
+            next_step
         ));
+        self.app.learning_summary_revealed = passed;
+        self.app.learning_waiting_for_next = passed;
+        if first_attempt {
+            self.score_challenge_answer(passed, elapsed_ms.unwrap_or_default());
+        }
+    }
+
+    fn active_question(&self) -> Option<&QuizItem> {
+        self.app
+            .learning_response
+            .as_ref()?
+            .response
+            .get(self.app.learning_group_index)?
+            .quiz
+            .get(self.app.learning_quiz_index)
     }
 
     fn total_groups(&self) -> Option<usize> {
@@ -343,17 +1110,19 @@ impl<'a> LearningManager<'a> {
         }
     }
 
+    /// Advance to the first question of the next group that has one. Stops at the last group
+    /// instead of wrapping around, so the lesson can end.
     fn move_to_next_group_with_quiz(&mut self) -> bool {
         let Some(total_groups) = self.total_groups() else {
             return false;
         };
 
-        for offset in 1..=total_groups {
-            let next_index = (self.app.learning_group_index + offset) % total_groups;
+        for next_index in self.app.learning_group_index + 1..total_groups {
             if let Some(next_quiz_len) = self.group_quiz_len(next_index) {
                 self.app.learning_group_index = next_index;
                 self.app.learning_quiz_index = 0;
                 self.app.learning_option_index = 0;
+                Self::reset_answer_state(self.app);
                 self.reset_feedback();
                 log_debug(&format!(
                     "App: auto-advanced to learning group {} of {} with {} question(s)",
@@ -369,14 +1138,14 @@ impl<'a> LearningManager<'a> {
         false
     }
 
+    /// Moving past the last question ends the lesson and opens its results.
     fn on_quiz_complete(&mut self) {
+        if self.total_groups().is_none() {
+            return;
+        }
         self.reset_feedback();
-        self.app.learning_summary_revealed = true;
-        self.app.learning_waiting_for_next = false;
-        self.app.learning_feedback = Some(
-            "You answered every question in the current lesson! Press g to generate new questions or m to return to the menu.".to_string(),
-        );
-        log_debug("App: user completed all quiz questions");
+        log_debug("App: user reached the end of the lesson");
+        ResultsManager::show_results(self.app);
     }
 
     fn move_to_previous_group_with_quiz(&mut self) -> bool {
@@ -390,6 +1159,7 @@ impl<'a> LearningManager<'a> {
                 self.app.learning_group_index = prev_index;
                 self.app.learning_quiz_index = prev_quiz_len - 1;
                 self.app.learning_option_index = 0;
+                Self::reset_answer_state(self.app);
                 self.reset_feedback();
                 log_debug(&format!(
                     "App: auto-rewound to learning group {} of {} with {} question(s)",
@@ -408,9 +1178,16 @@ impl<'a> LearningManager<'a> {
     fn reset_question_state(&mut self) {
         self.app.learning_quiz_index = 0;
         self.app.learning_option_index = 0;
+        Self::reset_answer_state(self.app);
         self.reset_feedback();
     }
 
+    /// Clear marked options and typed answers when moving to a different question.
+    pub(crate) fn reset_answer_state(app: &mut App) {
+        app.learning_marked_options.clear();
+        app.learning_text_input = None;
+    }
+
     fn reset_feedback(&mut self) {
         Self::reset_feedback_state(self.app);
     }
@@ -424,16 +1201,99 @@ impl<'a> LearningManager<'a> {
     }
 }
 
+fn option_label(index: usize) -> String {
+    ((b'A' + (index % 26) as u8) as char).to_string()
+}
+
+/// Append an option's explanation to a feedback fragment, or nothing if it has none.
+fn with_explanation(prefix: &str, explanation: &str) -> String {
+    match explanation.trim() {
+        "" => String::new(),
+        text => format!("{} {}", prefix, text),
+    }
+}
+
+/// Text of the given options in the order they were marked, as stored in the answer log.
+fn joined_selections(question: &QuizItem, indices: &[usize], separator: &str) -> String {
+    indices
+        .iter()
+        .filter_map(|&index| question.options.get(index))
+        .map(|option| option.selection.trim())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Multi-select answers are correct when exactly the correct options are marked.
+fn is_multi_select_correct(question: &QuizItem, marked: &[usize]) -> bool {
+    question
+        .options
+        .iter()
+        .enumerate()
+        .all(|(index, option)| option.is_correct_answer == marked.contains(&index))
+}
+
+/// Order-lines answers are correct when the picked lines read the same as the original order.
+/// Lines are compared by text so repeated lines such as closing braces are interchangeable.
+fn is_order_correct(question: &QuizItem, picked: &[usize]) -> bool {
+    let mut expected: Vec<(usize, &str)> = question
+        .options
+        .iter()
+        .enumerate()
+        .map(|(index, option)| (option.position.unwrap_or(index), option.selection.as_str()))
+        .collect();
+    expected.sort_by_key(|(position, _)| *position);
+    picked.len() == expected.len()
+        && picked.iter().zip(&expected).all(|(&index, (_, line))| {
+            question
+                .options
+                .get(index)
+                .map(|option| option.selection == *line)
+                .unwrap_or(false)
+        })
+}
+
+/// Compare a typed answer with the accepted answers, ignoring surrounding and repeated whitespace.
+fn is_text_answer_accepted(question: &QuizItem, answer: &str) -> bool {
+    let normalized = normalize_answer(answer);
+    !normalized.is_empty()
+        && question
+            .accepted_answers
+            .iter()
+            .any(|accepted| normalize_answer(accepted) == normalized)
+}
+
+fn normalize_answer(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Index of the first event whose call id or timestamp matches one of the references.
+fn find_source_event(events: &[SessionEvent], source_refs: &[String]) -> Option<usize> {
+    source_refs.iter().find_map(|reference| {
+        let reference = reference.trim();
+        events.iter().position(|event| {
+            event.call_id.as_deref() == Some(reference) || event.timestamp == reference
+        })
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ai_manager::{KnowledgeResponse, QuizItem, QuizOption};
+    use crate::FollowupTaskMessage;
+    use crate::ai_manager::{KnowledgeResponse, QuizOption};
     use crate::config::{AppConfig, ConfigForm, OpenAiModelKind};
+    use crate::ui_renderer::UiRenderer;
+    use crate::view_managers::{
+        BookmarksState, HistoryState, LeaderboardState, ResultsState, TopicsState,
+    };
+    use ratatui::{Terminal, backend::TestBackend, style::Color};
     use serde_json::from_str;
     use std::{
-        collections::HashSet,
+        collections::{HashMap, HashSet},
         fs,
         path::{Path, PathBuf},
+        sync::mpsc,
+        time::Duration,
     };
 
     fn load_learning_response(filename: &str) -> StructuredLearningResponse {
@@ -468,6 +1328,9 @@ mod tests {
             ai_loading: false,
             ai_loading_frame: 0,
             ai_result_receiver: None,
+            grading_receiver: None,
+            followup: None,
+            followup_receiver: None,
             learning_response: Some(response),
             learning_group_index: 0,
             learning_quiz_index: 0,
@@ -475,18 +1338,31 @@ mod tests {
             learning_feedback: None,
             learning_summary_revealed: false,
             learning_waiting_for_next: false,
+            learning_marked_options: Vec::new(),
+            learning_text_input: None,
+            learning_flag_note: None,
             config_form: ConfigForm::from_config(AppConfig::default()),
             write_output_artifacts: false,
             openai_model: OpenAiModelKind::Gpt5Mini,
-            quiz_first_attempts: HashSet::new(),
+            quiz_first_attempts: HashMap::new(),
+            learning_started_at: None,
+            learning_retry: false,
+            learning_question_shown_at: None,
+            question_marks: HashSet::new(),
+            challenge: None,
             analytics_snapshot: None,
             analytics_error: None,
             analytics_refreshed_at: None,
+            topics: TopicsState::default(),
+            history: HistoryState::default(),
+            bookmarks: BookmarksState::default(),
+            leaderboard: LeaderboardState::default(),
+            results: ResultsState::default(),
         }
     }
 
     #[test]
-    fn multiple_group_quiz_advances_and_wraps_groups() {
+    fn multiple_group_quiz_advances_groups_and_ends_with_results() {
         let response = load_learning_response("test_fixtures/multiple_knowledge_type_groups.json");
         let mut app = app_with_response(response);
 
@@ -523,8 +1399,13 @@ mod tests {
             "fixture should include multiple knowledge groups"
         );
 
+        let last_group_len = app
+            .learning_response
+            .as_ref()
+            .map(|resp| resp.response[total_groups - 1].quiz.len())
+            .unwrap_or_default();
         app.learning_group_index = total_groups - 1;
-        app.learning_quiz_index = 0;
+        app.learning_quiz_index = last_group_len - 1;
 
         {
             let mut manager = LearningManager::new(&mut app);
@@ -532,17 +1413,17 @@ mod tests {
         }
 
         assert_eq!(
-            app.learning_group_index, 0,
-            "navigation should wrap back to the first group"
+            app.view,
+            AppView::Results,
+            "moving past the last question should open the results"
         );
-        assert_eq!(app.learning_quiz_index, 0);
-        assert_eq!(app.learning_option_index, 0);
+        assert_eq!(app.results.results.groups.len(), total_groups);
         assert!(!app.learning_summary_revealed);
         assert!(!app.learning_waiting_for_next);
     }
 
     #[test]
-    fn single_group_quiz_cycles_questions_without_group_change() {
+    fn single_group_quiz_ends_with_results_after_last_question() {
         let response = load_learning_response("test_fixtures/single_knowledge_type_group.json");
         let mut app = app_with_response(response);
 
@@ -571,24 +1452,22 @@ mod tests {
         }
 
         assert_eq!(
-            app.learning_group_index, 0,
-            "single group quiz should remain on the same group"
+            app.view,
+            AppView::Results,
+            "the lesson should end instead of cycling back to the first question"
         );
         assert_eq!(
-            app.learning_quiz_index, 0,
-            "question index should cycle back to the beginning"
-        );
-        assert_eq!(
-            app.learning_option_index, 0,
-            "option index should reset when cycling questions"
+            app.learning_quiz_index,
+            total_questions - 1,
+            "the last question stays selected for going back to the lesson"
         );
         assert!(
             !app.learning_summary_revealed,
-            "cycling should clear summary state"
+            "ending the lesson should clear summary state"
         );
         assert!(
             !app.learning_waiting_for_next,
-            "cycling should clear waiting state"
+            "ending the lesson should clear waiting state"
         );
     }
 
@@ -604,13 +1483,15 @@ mod tests {
                         QuizOption {
                             selection: "The borrow checker".to_string(),
                             is_correct_answer: true,
+                            ..Default::default()
                         },
                         QuizOption {
                             selection: "Manual memory management".to_string(),
                             is_correct_answer: false,
+                            ..Default::default()
                         },
                     ],
-                    resources: vec![],
+                    ..Default::default()
                 }],
                 knowledge_type_language: "Rust".to_string(),
             }],
@@ -631,4 +1512,550 @@ mod tests {
         }
         assert_eq!(app.quiz_first_attempts.len(), 1);
     }
+
+    #[test]
+    fn chosen_option_explanation_is_shown_and_serialised() {
+        let mut question = quiz_item(
+            QuizKind::MultipleChoice,
+            &[("Box<T>", false), ("Rc<T>", true)],
+        );
+        question.options[0].explanation = "Box has a single owner.".to_string();
+        question.options[1].explanation = "Rc counts shared owners.".to_string();
+        let serialised = serde_json::to_string(&question).unwrap();
+        assert!(serialised.contains("\"explanation\":\"Box has a single owner.\""));
+
+        let response = StructuredLearningResponse {
+            response: vec![KnowledgeResponse {
+                knowledge_type_group: "Smart pointers".to_string(),
+                quiz: vec![question],
+                ..Default::default()
+            }],
+        };
+        let mut app = app_with_response(response);
+
+        {
+            let mut manager = LearningManager::new(&mut app);
+            manager.select_option();
+        }
+        assert_eq!(
+            app.learning_feedback.as_deref(),
+            Some("Not quite. Box has a single owner. Try another option.")
+        );
+
+        app.learning_option_index = 1;
+        {
+            let mut manager = LearningManager::new(&mut app);
+            manager.select_option();
+        }
+        assert_eq!(
+            app.learning_feedback.as_deref(),
+            Some("Correct! Option B is the right answer. Rc counts shared owners.")
+        );
+    }
+
+    fn quiz_item(kind: QuizKind, options: &[(&str, bool)]) -> QuizItem {
+        QuizItem {
+            question: "Question".to_string(),
+            kind,
+            options: options
+                .iter()
+                .map(|(selection, correct)| QuizOption {
+                    selection: selection.to_string(),
+                    is_correct_answer: *correct,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn grading_helpers_cover_each_question_kind() {
+        let multi = quiz_item(
+            QuizKind::MultiSelect,
+            &[("i32", true), ("f64", false), ("u8", true)],
+        );
+        assert!(is_multi_select_correct(&multi, &[2, 0]));
+        assert!(!is_multi_select_correct(&multi, &[0]));
+        assert!(!is_multi_select_correct(&multi, &[0, 1, 2]));
+
+        let mut order = quiz_item(
+            QuizKind::OrderLines,
+            &[
+                ("fn main() {", false),
+                ("}", false),
+                ("    run();", false),
+                ("}", false),
+            ],
+        );
+        order.options[0].position = Some(0);
+        order.options[1].position = Some(2);
+        order.options[2].position = Some(1);
+        order.options[3].position = Some(3);
+        assert!(is_order_correct(&order, &[0, 2, 1, 3]));
+        assert!(
+            is_order_correct(&order, &[0, 2, 3, 1]),
+            "identical lines are interchangeable"
+        );
+        assert!(!is_order_correct(&order, &[2, 0, 1, 3]));
+        assert!(!is_order_correct(&order, &[0, 2]));
+
+        let mut blank = quiz_item(QuizKind::FillInBlank, &[]);
+        blank.accepted_answers = vec!["Vec::new()".to_string()];
+        assert!(is_text_answer_accepted(&blank, "  Vec::new() "));
+        assert!(!is_text_answer_accepted(&blank, "vec::new()"));
+        assert!(!is_text_answer_accepted(&blank, "   "));
+    }
+
+    #[test]
+    fn fill_in_blank_feedback_survives_index_checks() {
+        let mut blank = quiz_item(QuizKind::FillInBlank, &[]);
+        blank.accepted_answers = vec!["mut".to_string()];
+        let response = StructuredLearningResponse {
+            response: vec![KnowledgeResponse {
+                knowledge_type_group: "Bindings".to_string(),
+                quiz: vec![blank],
+                ..Default::default()
+            }],
+        };
+        let mut app = app_with_response(response);
+        app.learning_text_input = Some("mut".to_string());
+
+        LearningManager::new(&mut app).submit_text_answer();
+        // Runs on every render frame.
+        LearningManager::ensure_indices_for(&mut app);
+
+        assert!(app.learning_waiting_for_next);
+        assert!(app.learning_summary_revealed);
+        assert!(
+            app.learning_feedback
+                .as_deref()
+                .is_some_and(|feedback| feedback.starts_with("Correct!"))
+        );
+    }
+
+    #[test]
+    fn multi_select_requires_marks_and_grades_on_submit() {
+        let response = StructuredLearningResponse {
+            response: vec![KnowledgeResponse {
+                knowledge_type_group: "Rust Types".to_string(),
+                quiz: vec![quiz_item(
+                    QuizKind::MultiSelect,
+                    &[("i32", true), ("f64", false), ("u8", true)],
+                )],
+                ..Default::default()
+            }],
+        };
+        let mut app = app_with_response(response);
+
+        {
+            let mut manager = LearningManager::new(&mut app);
+            manager.select_option();
+        }
+        assert!(
+            app.quiz_first_attempts.is_empty(),
+            "no marks means no attempt"
+        );
+
+        {
+            let mut manager = LearningManager::new(&mut app);
+            manager.mark_or_select_option();
+            manager.next_option();
+            manager.next_option();
+            manager.mark_or_select_option();
+            manager.select_option();
+        }
+        assert_eq!(app.learning_marked_options, vec![0, 2]);
+        assert!(app.learning_waiting_for_next);
+        assert_eq!(app.quiz_first_attempts.len(), 1);
+    }
+
+    #[test]
+    fn typed_answers_keep_feedback_and_free_text_grades_record_attempts() {
+        let mut blank = quiz_item(QuizKind::FillInBlank, &[]);
+        blank.accepted_answers = vec!["mut".to_string()];
+        let mut explain = quiz_item(QuizKind::FreeText, &[]);
+        explain.rubric = "Mentions exclusive access".to_string();
+        let explain_id = knowledge_store::question_id(&explain);
+        let response = StructuredLearningResponse {
+            response: vec![KnowledgeResponse {
+                knowledge_type_group: "Borrowing".to_string(),
+                quiz: vec![blank, explain],
+                ..Default::default()
+            }],
+        };
+        let mut app = app_with_response(response);
+
+        {
+            let mut manager = LearningManager::new(&mut app);
+            manager.select_option();
+        }
+        assert_eq!(app.learning_text_input.as_deref(), Some(""));
+        app.learning_text_input = Some(" mut ".to_string());
+        {
+            let mut manager = LearningManager::new(&mut app);
+            manager.submit_text_answer();
+            manager.ensure_indices();
+        }
+        assert!(app.learning_waiting_for_next, "feedback survives re-render");
+        assert!(app.learning_text_input.is_none());
+
+        app.learning_waiting_for_next = false;
+        app.learning_quiz_index = 1;
+        {
+            let mut manager = LearningManager::new(&mut app);
+            manager.apply_free_text_grade(
+                GradingRequest {
+                    group_index: 0,
+                    quiz_index: 1,
+                    question_id: explain_id.clone(),
+                    question: "Explain".to_string(),
+                    summary: String::new(),
+                    rubric: "Mentions exclusive access".to_string(),
+                    answer: "Only one mutable borrow".to_string(),
+                    elapsed_ms: None,
+                },
+                FreeTextGrade {
+                    score: 40,
+                    feedback: "Mention aliasing.".to_string(),
+                },
+            );
+        }
+        assert!(app.quiz_first_attempts.contains_key(&(0, 1)));
+        assert!(!app.learning_waiting_for_next);
+        assert_eq!(
+            app.learning_feedback.as_deref(),
+            Some("Score 40/100. Mention aliasing. Press Enter to try again.")
+        );
+    }
+
+    #[test]
+    fn free_text_grades_for_a_replaced_question_are_dropped() {
+        let mut explain = quiz_item(QuizKind::FreeText, &[]);
+        explain.rubric = "Mentions exclusive access".to_string();
+        let graded_id = knowledge_store::question_id(&explain);
+        let mut replacement = explain.clone();
+        replacement.question = "Explain lifetimes".to_string();
+        let response = StructuredLearningResponse {
+            response: vec![KnowledgeResponse {
+                knowledge_type_group: "Borrowing".to_string(),
+                quiz: vec![replacement],
+                ..Default::default()
+            }],
+        };
+        let mut app = app_with_response(response);
+
+        LearningManager::new(&mut app).apply_free_text_grade(
+            GradingRequest {
+                group_index: 0,
+                quiz_index: 0,
+                question_id: graded_id,
+                question: "Question".to_string(),
+                summary: String::new(),
+                rubric: "Mentions exclusive access".to_string(),
+                answer: "Only one mutable borrow".to_string(),
+                elapsed_ms: None,
+            },
+            FreeTextGrade {
+                score: 90,
+                feedback: "Good.".to_string(),
+            },
+        );
+
+        assert!(app.quiz_first_attempts.is_empty());
+        assert!(app.learning_feedback.is_none());
+        assert!(!app.learning_waiting_for_next);
+    }
+
+    #[test]
+    fn followup_panel_opens_after_answering_and_captures_typing() {
+        let response = StructuredLearningResponse {
+            response: vec![KnowledgeResponse {
+                knowledge_type_group: "Ownership".to_string(),
+                quiz: vec![quiz_item(
+                    QuizKind::MultipleChoice,
+                    &[("Move", true), ("Copy", false)],
+                )],
+                ..Default::default()
+            }],
+        };
+        let mut app = app_with_response(response);
+        app.write_output_artifacts = false;
+        let press = |app: &mut App, code: KeyCode| {
+            LearningManager::new(app).handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+        };
+
+        press(&mut app, KeyCode::Char('x'));
+        assert!(app.followup.is_none(), "follow-ups unlock after an answer");
+
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('x'));
+        let chat = app.followup.as_ref().expect("panel opens");
+        assert_eq!(chat.context.knowledge_type_group, "Ownership");
+        assert!(chat.messages.is_empty());
+
+        for ch in "why?".chars() {
+            press(&mut app, KeyCode::Char(ch));
+        }
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(app.followup.as_ref().unwrap().input, "why");
+
+        press(&mut app, KeyCode::Esc);
+        assert!(app.followup.is_none());
+    }
+
+    #[test]
+    fn followup_replies_only_land_on_the_question_they_answer() {
+        let response = StructuredLearningResponse {
+            response: vec![KnowledgeResponse {
+                knowledge_type_group: "Ownership".to_string(),
+                quiz: vec![
+                    quiz_item(QuizKind::MultipleChoice, &[("Move", true), ("Copy", false)]),
+                    quiz_item(QuizKind::TrueFalse, &[("True", true), ("False", false)]),
+                ],
+                ..Default::default()
+            }],
+        };
+        let ids: Vec<String> = response.response[0]
+            .quiz
+            .iter()
+            .map(knowledge_store::question_id)
+            .collect();
+        let mut app = app_with_response(response);
+        app.write_output_artifacts = false;
+        let press = |app: &mut App, code: KeyCode| {
+            LearningManager::new(app).handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+        };
+        let reply = |quiz_index: usize, text: &str| FollowupTaskMessage::Reply {
+            group_index: 0,
+            quiz_index,
+            question_id: ids[quiz_index].clone(),
+            reply: text.to_string(),
+        };
+
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('x'));
+        let (_sender, receiver) = mpsc::channel();
+        app.followup_receiver = Some(receiver);
+        press(&mut app, KeyCode::Esc);
+        assert!(
+            app.followup_receiver.is_none(),
+            "closing drops the pending reply"
+        );
+
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.learning_quiz_index, 1);
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('x'));
+        assert_eq!(app.followup.as_ref().unwrap().quiz_index, 1);
+
+        let (sender, receiver) = mpsc::channel();
+        app.followup_receiver = Some(receiver);
+        sender.send(reply(0, "About moves")).unwrap();
+        ai_manager::poll_followup_messages(&mut app);
+        assert!(app.followup.as_ref().unwrap().messages.is_empty());
+
+        let (sender, receiver) = mpsc::channel();
+        app.followup_receiver = Some(receiver);
+        sender.send(reply(1, "About booleans")).unwrap();
+        ai_manager::poll_followup_messages(&mut app);
+        let messages = &app.followup.as_ref().unwrap().messages;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].content, "About booleans");
+    }
+
+    #[test]
+    fn rendered_options_highlight_their_code() {
+        let response = StructuredLearningResponse {
+            response: vec![KnowledgeResponse {
+                knowledge_type_group: "Ownership".to_string(),
+                knowledge_type_language: "Rust".to_string(),
+                quiz: vec![quiz_item(
+                    QuizKind::MultipleChoice,
+                    &[
+                        ("```rust\nlet moved = owner;\n```", true),
+                        ("Plain words", false),
+                    ],
+                )],
+                ..Default::default()
+            }],
+        };
+        let mut app = app_with_response(response);
+        app.view = AppView::Learning;
+        let mut terminal = Terminal::new(TestBackend::new(80, 40)).unwrap();
+
+        terminal
+            .draw(|frame| UiRenderer::new(&mut app).render(frame))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row_of = |needle: &str| {
+            (0..buffer.area.height)
+                .find(|&y| {
+                    let row: String = (0..buffer.area.width)
+                        .map(|x| buffer[(x, y)].symbol())
+                        .collect();
+                    row.contains(needle)
+                })
+                .unwrap_or_else(|| panic!("{} is rendered", needle))
+        };
+        let styled = |y: u16| {
+            (0..buffer.area.width)
+                .filter(|&x| matches!(buffer[(x, y)].fg, Color::Rgb(..)))
+                .count()
+        };
+        assert!(styled(row_of("let moved = owner;")) > 0);
+        assert_eq!(styled(row_of("Plain words")), 0);
+    }
+
+    #[test]
+    fn bookmark_and_flag_keys_toggle_marks_on_the_active_question() {
+        let response = StructuredLearningResponse {
+            response: vec![KnowledgeResponse {
+                knowledge_type_group: "Ownership".to_string(),
+                quiz: vec![quiz_item(
+                    QuizKind::MultipleChoice,
+                    &[("Move", true), ("Copy", false)],
+                )],
+                ..Default::default()
+            }],
+        };
+        let id = knowledge_store::question_id(&response.response[0].quiz[0]);
+        let mut app = app_with_response(response);
+        app.write_output_artifacts = false;
+        let press = |app: &mut App, code: KeyCode| {
+            LearningManager::new(app).handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+        };
+
+        press(&mut app, KeyCode::Char('b'));
+        assert!(
+            app.question_marks
+                .contains(&(id.clone(), QuestionMarkKind::Bookmark))
+        );
+        press(&mut app, KeyCode::Char('b'));
+        assert!(app.question_marks.is_empty());
+
+        press(&mut app, KeyCode::Char('w'));
+        for ch in "typo".chars() {
+            press(&mut app, KeyCode::Char(ch));
+        }
+        assert_eq!(app.learning_flag_note.as_deref(), Some("typo"));
+        assert!(app.question_marks.is_empty(), "flag waits for the note");
+        press(&mut app, KeyCode::Enter);
+        assert!(app.learning_flag_note.is_none());
+        assert!(
+            app.question_marks
+                .contains(&(id.clone(), QuestionMarkKind::Flag))
+        );
+        assert!(
+            app.quiz_first_attempts.is_empty(),
+            "typing the note answers nothing"
+        );
+
+        press(&mut app, KeyCode::Char('w'));
+        assert!(app.learning_flag_note.is_none());
+        assert!(app.question_marks.is_empty());
+    }
+
+    #[test]
+    fn challenge_scores_each_question_once_and_times_out_unanswered_ones() {
+        let response = StructuredLearningResponse {
+            response: vec![KnowledgeResponse {
+                knowledge_type_group: "Ownership".to_string(),
+                quiz: vec![
+                    quiz_item(QuizKind::MultipleChoice, &[("Move", true), ("Copy", false)]),
+                    quiz_item(
+                        QuizKind::MultipleChoice,
+                        &[("Borrow", true), ("Clone", false)],
+                    ),
+                    quiz_item(QuizKind::TrueFalse, &[("True", true), ("False", false)]),
+                ],
+                ..Default::default()
+            }],
+        };
+        let mut app = app_with_response(response);
+        app.view = AppView::Learning;
+        app.write_output_artifacts = false;
+        app.learning_quiz_index = 2;
+        app.learning_started_at = Instant::now().checked_sub(Duration::from_secs(600));
+        let press = |app: &mut App, code: KeyCode| {
+            LearningManager::new(app).handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+        };
+
+        press(&mut app, KeyCode::Char('t'));
+        assert_eq!(app.learning_quiz_index, 0, "challenges start from the top");
+        assert!(
+            app.learning_started_at.unwrap().elapsed() < Duration::from_secs(60),
+            "the challenge is timed from its own start"
+        );
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.learning_quiz_index, 0, "navigation is locked");
+
+        press(&mut app, KeyCode::Enter);
+        let challenge = app.challenge.as_ref().unwrap();
+        assert_eq!(challenge.correct, 1);
+        assert!(challenge.score > challenge::CORRECT_POINTS);
+        for code in [KeyCode::Char('t'), KeyCode::Char('v'), KeyCode::Char('x')] {
+            press(&mut app, code);
+        }
+        let challenge = app.challenge.as_ref().unwrap();
+        assert_eq!(
+            (challenge.answered, challenge.correct),
+            (1, 1),
+            "t does not restart the run between questions"
+        );
+        assert!(app.followup.is_none());
+        assert!(matches!(app.view, AppView::Learning));
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.learning_quiz_index, 1);
+
+        app.learning_option_index = 1;
+        press(&mut app, KeyCode::Enter);
+        assert!(app.learning_waiting_for_next, "wrong answers also move on");
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.learning_quiz_index, 2);
+
+        let expired = Instant::now() - Duration::from_secs(challenge::SECONDS_PER_QUESTION + 1);
+        app.learning_question_shown_at = Some(((0, 2), expired));
+        app.on_tick();
+        assert!(app.challenge.is_none(), "the last timeout finishes the run");
+        let result = app.ai_status.as_deref().unwrap();
+        assert!(result.contains("1 of 3 correct, 1 timed out"), "{}", result);
+    }
+
+    #[test]
+    fn source_refs_jump_to_matching_session_event() {
+        let mut question = quiz_item(QuizKind::MultipleChoice, &[("Move", true)]);
+        question.source_refs = vec!["missing".to_string(), "call-2".to_string()];
+        let response = StructuredLearningResponse {
+            response: vec![KnowledgeResponse {
+                quiz: vec![question],
+                ..Default::default()
+            }],
+        };
+        let mut app = app_with_response(response);
+        app.view = AppView::Learning;
+        app.events = ["call-1", "call-2"]
+            .iter()
+            .enumerate()
+            .map(|(index, call_id)| SessionEvent {
+                timestamp: format!("2025-01-01T00:00:0{}Z", index),
+                payload_type: "function_call".to_string(),
+                call_id: Some(call_id.to_string()),
+                arguments: None,
+                output: None,
+                content_texts: Vec::new(),
+            })
+            .collect();
+
+        LearningManager::new(&mut app)
+            .handle_key(KeyEvent::new(KeyCode::Char('v'), KeyModifiers::NONE));
+
+        assert_eq!(app.view, AppView::Events);
+        assert_eq!(app.selected_event, Some(1));
+        assert_eq!(
+            find_source_event(&app.events, &["2025-01-01T00:00:00Z".to_string()]),
+            Some(0)
+        );
+    }
 }
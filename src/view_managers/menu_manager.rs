@@ -1,17 +1,27 @@
 use super::{
-    analytics_manager::AnalyticsManager, config_manager::ConfigManager,
-    events_manager::EventsManager, learning_manager::LearningManager,
+    analytics_manager::AnalyticsManager, bookmarks_manager::BookmarksManager,
+    config_manager::ConfigManager, events_manager::EventsManager, history_manager::HistoryManager,
+    leaderboard_manager::LeaderboardManager, learning_manager::LearningManager,
+    topics_manager::TopicsManager,
 };
 use crate::{App, ai_manager};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-pub(crate) const MENU_OPTIONS: [&str; 4] = [
+pub(crate) const MENU_OPTIONS: [&str; 9] = [
     "1. Generate learning lesson",
     "2. View analytics dashboard",
-    "3. View session events",
-    "4. Configure details",
+    "3. Browse past lessons",
+    "4. Review missed and shared questions",
+    "5. View bookmarked questions",
+    "6. Challenge leaderboard",
+    "7. View session events",
+    "8. Configure details",
+    "9. Manage topics",
 ];
 
+/// Leading menu options shown under "Actions"; the rest are listed under "Config".
+pub(crate) const ACTION_OPTIONS: usize = 6;
+
 pub(crate) struct MenuManager<'a> {
     app: &'a mut App,
 }
@@ -42,6 +52,26 @@ impl<'a> MenuManager<'a> {
                 self.app.menu_index = 3;
                 self.activate_menu_option();
             }
+            (KeyModifiers::NONE, KeyCode::Char('5')) => {
+                self.app.menu_index = 4;
+                self.activate_menu_option();
+            }
+            (KeyModifiers::NONE, KeyCode::Char('6')) => {
+                self.app.menu_index = 5;
+                self.activate_menu_option();
+            }
+            (KeyModifiers::NONE, KeyCode::Char('7')) => {
+                self.app.menu_index = 6;
+                self.activate_menu_option();
+            }
+            (KeyModifiers::NONE, KeyCode::Char('8')) => {
+                self.app.menu_index = 7;
+                self.activate_menu_option();
+            }
+            (KeyModifiers::NONE, KeyCode::Char('9')) => {
+                self.app.menu_index = 8;
+                self.activate_menu_option();
+            }
             (KeyModifiers::NONE, KeyCode::Char('c') | KeyCode::Char('C')) => {
                 ConfigManager::new(self.app).show_config()
             }
@@ -80,8 +110,13 @@ impl<'a> MenuManager<'a> {
         match self.app.menu_index {
             0 => ai_manager::trigger_learning_response(self.app),
             1 => AnalyticsManager::show_analytics(self.app),
-            2 => EventsManager::show_events(self.app),
-            3 => ConfigManager::new(self.app).show_config(),
+            2 => HistoryManager::show_history(self.app),
+            3 => ai_manager::start_review(self.app),
+            4 => BookmarksManager::show_bookmarks(self.app),
+            5 => LeaderboardManager::show_leaderboard(self.app),
+            6 => EventsManager::show_events(self.app),
+            7 => ConfigManager::new(self.app).show_config(),
+            8 => TopicsManager::show_topics(self.app),
             _ => {}
         }
     }
@@ -1,10 +1,20 @@
 pub mod analytics_manager;
+pub mod bookmarks_manager;
 pub mod config_manager;
 pub mod events_manager;
+pub mod history_manager;
+pub mod leaderboard_manager;
 pub mod learning_manager;
 pub mod menu_manager;
+pub mod results_manager;
+pub mod topics_manager;
 
 pub(crate) use analytics_manager::AnalyticsManager;
+pub(crate) use bookmarks_manager::{BookmarksManager, BookmarksState};
 pub(crate) use config_manager::ConfigManager;
+pub(crate) use history_manager::{HistoryManager, HistoryState};
+pub(crate) use leaderboard_manager::{LeaderboardManager, LeaderboardState};
 pub(crate) use learning_manager::LearningManager;
 pub(crate) use menu_manager::MenuManager;
+pub(crate) use results_manager::{ResultsManager, ResultsState};
+pub(crate) use topics_manager::{TopicsManager, TopicsState};
@@ -0,0 +1,323 @@
+use super::learning_manager::LearningManager;
+use crate::{
+    App, AppView,
+    ai_manager::{self, QuizItem, QuizKind, StructuredLearningResponse},
+    knowledge_store::StoredResponse,
+    log_util::log_debug,
+    quiz_export,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::{collections::HashMap, time::Duration};
+
+/// First-try score for one knowledge group of a finished lesson.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct GroupResult {
+    pub(crate) knowledge_type_group: String,
+    pub(crate) questions: usize,
+    pub(crate) answered: usize,
+    pub(crate) first_try_correct: usize,
+}
+
+/// A question answered wrong on the first try, with the answer it expected.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MissedQuestion {
+    pub(crate) knowledge_type_group: String,
+    pub(crate) question: String,
+    pub(crate) correct_answer: String,
+}
+
+/// How a lesson went, shown once its last question has been passed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct LessonResults {
+    pub(crate) groups: Vec<GroupResult>,
+    pub(crate) missed: Vec<MissedQuestion>,
+    pub(crate) time_spent: Duration,
+}
+
+/// State of the end-of-lesson results screen.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ResultsState {
+    pub(crate) results: LessonResults,
+    pub(crate) selected: usize,
+    pub(crate) status: Option<String>,
+}
+
+pub(crate) struct ResultsManager<'a> {
+    app: &'a mut App,
+}
+
+impl<'a> ResultsManager<'a> {
+    pub(crate) fn new(app: &'a mut App) -> Self {
+        Self { app }
+    }
+
+    pub(crate) fn show_results(app: &'a mut App) {
+        let Some(response) = app.learning_response.as_ref() else {
+            return;
+        };
+        let time_spent = app
+            .learning_started_at
+            .map(|started| started.elapsed())
+            .unwrap_or_default();
+        app.results = ResultsState {
+            results: lesson_results(response, &app.quiz_first_attempts, time_spent),
+            ..Default::default()
+        };
+        app.view = AppView::Results;
+        log_debug("App: opened lesson results");
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) {
+        match (key.modifiers, key.code) {
+            (KeyModifiers::NONE, KeyCode::Down | KeyCode::Char('j')) => self.select_next(),
+            (KeyModifiers::NONE, KeyCode::Up | KeyCode::Char('k')) => self.select_previous(),
+            (KeyModifiers::NONE, KeyCode::Char('r')) => self.retry_missed(),
+            (KeyModifiers::NONE, KeyCode::Char('e')) => self.export_lesson(),
+            (KeyModifiers::NONE, KeyCode::Char('l')) => LearningManager::show_learning(self.app),
+            (KeyModifiers::NONE, KeyCode::Char('m')) => self.app.return_to_menu(),
+            _ => {}
+        }
+    }
+
+    fn select_next(&mut self) {
+        let count = self.app.results.results.missed.len();
+        if count > 0 {
+            self.app.results.selected = (self.app.results.selected + 1) % count;
+        }
+    }
+
+    fn select_previous(&mut self) {
+        let count = self.app.results.results.missed.len();
+        if count > 0 {
+            self.app.results.selected = (self.app.results.selected + count - 1) % count;
+        }
+    }
+
+    /// Practice only the questions missed on the first try.
+    fn retry_missed(&mut self) {
+        if self.app.results.results.missed.is_empty() {
+            self.app.results.status = Some("No missed questions to retry.".to_string());
+            return;
+        }
+        ai_manager::retry_missed_questions(self.app);
+    }
+
+    /// Export the finished lesson as an Anki deck, like the Past lessons view does.
+    fn export_lesson(&mut self) {
+        let Some(response) = self.app.learning_response.as_ref() else {
+            return;
+        };
+        let responses: Vec<StoredResponse> = response
+            .response
+            .iter()
+            .map(|group| StoredResponse {
+                session_date: self.app.session_date.clone(),
+                response: group.clone(),
+            })
+            .collect();
+        let result = quiz_export::write_anki_deck(&responses, None, &self.app.session_date);
+        self.app.results.status = Some(match result {
+            Ok((path, notes)) => {
+                log_debug(&format!("App: exported Anki deck to {}", path.display()));
+                format!("Exported {} note(s) to {}.", notes, path.display())
+            }
+            Err(err) => format!("Anki export failed: {}", err),
+        });
+    }
+}
+
+/// The questions of `response` answered wrong on the first try, grouped as in the lesson.
+/// Groups without a miss are dropped.
+pub(crate) fn missed_questions(
+    response: &StructuredLearningResponse,
+    first_attempts: &HashMap<(usize, usize), bool>,
+) -> StructuredLearningResponse {
+    let mut missed = response.clone();
+    for (group_index, group) in missed.response.iter_mut().enumerate() {
+        group.quiz = group
+            .quiz
+            .drain(..)
+            .enumerate()
+            .filter(|(quiz_index, _)| {
+                first_attempts.get(&(group_index, *quiz_index)) == Some(&false)
+            })
+            .map(|(_, question)| question)
+            .collect();
+    }
+    missed.response.retain(|group| !group.quiz.is_empty());
+    missed
+}
+
+/// Score a lesson from the first-try result of each answered question.
+pub(crate) fn lesson_results(
+    response: &StructuredLearningResponse,
+    first_attempts: &HashMap<(usize, usize), bool>,
+    time_spent: Duration,
+) -> LessonResults {
+    let mut results = LessonResults {
+        time_spent,
+        ..Default::default()
+    };
+    for (group_index, group) in response.response.iter().enumerate() {
+        if group.quiz.is_empty() {
+            continue;
+        }
+        let mut group_result = GroupResult {
+            knowledge_type_group: group.knowledge_type_group.clone(),
+            questions: group.quiz.len(),
+            answered: 0,
+            first_try_correct: 0,
+        };
+        for (quiz_index, question) in group.quiz.iter().enumerate() {
+            match first_attempts.get(&(group_index, quiz_index)) {
+                Some(true) => {
+                    group_result.answered += 1;
+                    group_result.first_try_correct += 1;
+                }
+                Some(false) => {
+                    group_result.answered += 1;
+                    results.missed.push(MissedQuestion {
+                        knowledge_type_group: group.knowledge_type_group.clone(),
+                        question: question.question.clone(),
+                        correct_answer: correct_answer(question),
+                    });
+                }
+                None => {}
+            }
+        }
+        results.groups.push(group_result);
+    }
+    results
+}
+
+/// The expected answer to a question as plain text.
+fn correct_answer(question: &QuizItem) -> String {
+    match question.kind {
+        QuizKind::FillInBlank => question.accepted_answers.join(" / "),
+        QuizKind::FreeText => question.rubric.trim().to_string(),
+        QuizKind::OrderLines => {
+            let mut lines: Vec<(usize, &str)> = question
+                .options
+                .iter()
+                .enumerate()
+                .map(|(index, option)| {
+                    (option.position.unwrap_or(index), option.selection.as_str())
+                })
+                .collect();
+            lines.sort_by_key(|(position, _)| *position);
+            lines
+                .iter()
+                .map(|(_, line)| *line)
+                .collect::<Vec<_>>()
+                .join(" → ")
+        }
+        QuizKind::MultipleChoice | QuizKind::TrueFalse | QuizKind::MultiSelect => question
+            .options
+            .iter()
+            .filter(|option| option.is_correct_answer)
+            .map(|option| option.selection.trim())
+            .collect::<Vec<_>>()
+            .join(" | "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_manager::{KnowledgeResponse, QuizOption};
+
+    #[test]
+    fn results_score_groups_and_retry_only_the_misses() {
+        let question = |text: &str, correct: &str| QuizItem {
+            question: text.to_string(),
+            options: vec![
+                QuizOption {
+                    selection: correct.to_string(),
+                    is_correct_answer: true,
+                    ..Default::default()
+                },
+                QuizOption {
+                    selection: "Something else".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let response = StructuredLearningResponse {
+            response: vec![
+                KnowledgeResponse {
+                    knowledge_type_group: "Git".to_string(),
+                    quiz: vec![
+                        question("Undo a commit?", "git revert"),
+                        question("Stash changes?", "git stash"),
+                        question("Rename a branch?", "git branch -m"),
+                    ],
+                    ..Default::default()
+                },
+                KnowledgeResponse {
+                    knowledge_type_group: "Empty".to_string(),
+                    ..Default::default()
+                },
+                KnowledgeResponse {
+                    knowledge_type_group: "Rust".to_string(),
+                    quiz: vec![question("Borrow mutably?", "&mut")],
+                    ..Default::default()
+                },
+            ],
+        };
+        let first_attempts = HashMap::from([((0, 0), true), ((0, 1), false), ((2, 0), false)]);
+
+        let results = lesson_results(&response, &first_attempts, Duration::from_secs(90));
+
+        assert_eq!(
+            results.groups,
+            vec![
+                GroupResult {
+                    knowledge_type_group: "Git".to_string(),
+                    questions: 3,
+                    answered: 2,
+                    first_try_correct: 1,
+                },
+                GroupResult {
+                    knowledge_type_group: "Rust".to_string(),
+                    questions: 1,
+                    answered: 1,
+                    first_try_correct: 0,
+                },
+            ]
+        );
+        let missed: Vec<(&str, &str)> = results
+            .missed
+            .iter()
+            .map(|missed| (missed.question.as_str(), missed.correct_answer.as_str()))
+            .collect();
+        assert_eq!(
+            missed,
+            vec![("Stash changes?", "git stash"), ("Borrow mutably?", "&mut")]
+        );
+        assert_eq!(results.time_spent, Duration::from_secs(90));
+
+        let retry = missed_questions(&response, &first_attempts);
+        let retried: Vec<(&str, Vec<&str>)> = retry
+            .response
+            .iter()
+            .map(|group| {
+                (
+                    group.knowledge_type_group.as_str(),
+                    group
+                        .quiz
+                        .iter()
+                        .map(|item| item.question.as_str())
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            retried,
+            vec![
+                ("Git", vec!["Stash changes?"]),
+                ("Rust", vec!["Borrow mutably?"])
+            ]
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{
+    App, AppView,
+    knowledge_store::{self, Topic},
+    log_util::log_debug,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// State of the topic management screen.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TopicsState {
+    pub(crate) topics: Vec<Topic>,
+    pub(crate) selected: usize,
+    /// Topic chosen as the source of a merge, waiting for a target.
+    pub(crate) merge_source: Option<i64>,
+    /// New name being typed for the selected topic.
+    pub(crate) rename_buffer: Option<String>,
+    pub(crate) status: Option<String>,
+}
+
+impl TopicsState {
+    pub(crate) fn selected_topic(&self) -> Option<&Topic> {
+        self.topics.get(self.selected)
+    }
+}
+
+pub(crate) struct TopicsManager<'a> {
+    app: &'a mut App,
+}
+
+impl<'a> TopicsManager<'a> {
+    pub(crate) fn new(app: &'a mut App) -> Self {
+        Self { app }
+    }
+
+    pub(crate) fn show_topics(app: &'a mut App) {
+        let mut manager = Self::new(app);
+        manager.app.topics.merge_source = None;
+        manager.app.topics.rename_buffer = None;
+        manager.app.topics.status = None;
+        manager.refresh();
+        manager.app.view = AppView::Topics;
+        log_debug("App: opened topics view");
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) {
+        if let Some(buffer) = self.app.topics.rename_buffer.as_mut() {
+            match key.code {
+                KeyCode::Esc => self.app.topics.rename_buffer = None,
+                KeyCode::Enter => self.apply_rename(),
+                KeyCode::Backspace => {
+                    buffer.pop();
+                }
+                KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    buffer.push(ch);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match (key.modifiers, key.code) {
+            (KeyModifiers::NONE, KeyCode::Down | KeyCode::Char('j')) => self.select_next(),
+            (KeyModifiers::NONE, KeyCode::Up | KeyCode::Char('k')) => self.select_previous(),
+            (KeyModifiers::NONE, KeyCode::Char('r')) => {
+                if let Some(topic) = self.app.topics.selected_topic() {
+                    self.app.topics.rename_buffer = Some(topic.name.clone());
+                }
+            }
+            (KeyModifiers::NONE, KeyCode::Char('g')) => self.merge_selected(),
+            (KeyModifiers::NONE, KeyCode::Char('m')) => self.app.return_to_menu(),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn refresh(&mut self) {
+        match knowledge_store::list_topics() {
+            Ok(topics) => {
+                self.app.topics.selected =
+                    self.app.topics.selected.min(topics.len().saturating_sub(1));
+                self.app.topics.topics = topics;
+            }
+            Err(err) => {
+                self.app.topics.topics.clear();
+                self.app.topics.status = Some(format!("Unable to load topics: {}", err));
+                log_debug(&format!("App: failed to load topics: {}", err));
+            }
+        }
+    }
+
+    fn select_next(&mut self) {
+        let count = self.app.topics.topics.len();
+        if count > 0 {
+            self.app.topics.selected = (self.app.topics.selected + 1) % count;
+        }
+    }
+
+    fn select_previous(&mut self) {
+        let count = self.app.topics.topics.len();
+        if count > 0 {
+            self.app.topics.selected = (self.app.topics.selected + count - 1) % count;
+        }
+    }
+
+    fn apply_rename(&mut self) {
+        let Some(new_name) = self.app.topics.rename_buffer.take() else {
+            return;
+        };
+        let Some(topic) = self.app.topics.selected_topic().cloned() else {
+            return;
+        };
+        let new_name = new_name.trim().to_string();
+        if new_name == topic.name {
+            return;
+        }
+        match knowledge_store::rename_topic(topic.id, &new_name) {
+            Ok(_) => {
+                self.rename_loaded_groups(&topic.name, &new_name);
+                self.app.topics.status =
+                    Some(format!("Renamed '{}' to '{}'.", topic.name, new_name));
+                self.refresh();
+            }
+            Err(err) => self.app.topics.status = Some(format!("Rename failed: {}", err)),
+        }
+    }
+
+    /// First press picks the topic to fold away, second press picks the topic to keep.
+    fn merge_selected(&mut self) {
+        let Some(selected) = self.app.topics.selected_topic().cloned() else {
+            return;
+        };
+        let Some(source_id) = self.app.topics.merge_source else {
+            self.app.topics.merge_source = Some(selected.id);
+            self.app.topics.status = Some(format!(
+                "Merging '{}': select the topic to keep and press g again, or g on it to cancel.",
+                selected.name
+            ));
+            return;
+        };
+        self.app.topics.merge_source = None;
+        if source_id == selected.id {
+            self.app.topics.status = Some("Merge cancelled.".to_string());
+            return;
+        }
+        let Some(source_name) = self
+            .app
+            .topics
+            .topics
+            .iter()
+            .find(|topic| topic.id == source_id)
+            .map(|topic| topic.name.clone())
+        else {
+            return;
+        };
+        match knowledge_store::merge_topics(source_id, selected.id) {
+            Ok(_) => {
+                self.rename_loaded_groups(&source_name, &selected.name);
+                self.app.topics.status = Some(format!(
+                    "Merged '{}' into '{}'.",
+                    source_name, selected.name
+                ));
+                self.refresh();
+            }
+            Err(err) => self.app.topics.status = Some(format!("Merge failed: {}", err)),
+        }
+    }
+
+    /// Keep the loaded lesson in step so later attempts are recorded under the new name.
+    fn rename_loaded_groups(&mut self, old_name: &str, new_name: &str) {
+        if let Some(response) = self.app.learning_response.as_mut() {
+            for group in &mut response.response {
+                if group.knowledge_type_group == old_name {
+                    group.knowledge_type_group = new_name.to_string();
+                }
+            }
+        }
+        self.app.analytics_snapshot = None;
+        self.app.analytics_refreshed_at = None;
+    }
+}